@@ -1,8 +1,8 @@
 use crate::{
     operations::{
-        add_address_to_whitelist, add_liquidity, register_lp_token, register_pair_token,
-        remove_addresses_from_whitelist, remove_liquidity, set_staking_contract, swap,
-        update_viewing_key,
+        add_address_to_whitelist, add_liquidity, assert_staking_replaceable, create_staking_contract,
+        register_lp_token, register_pair_token, remove_addresses_from_whitelist, remove_liquidity,
+        set_staking_contract, swap, update_viewing_key,
     },
     query::{self, fee_info},
     state::{config_r, config_w, trade_count_r, whitelist_r, Config},
@@ -16,9 +16,12 @@ use shadeswap_shared::{
     admin::helpers::{validate_admin, AdminPermissions},
     core::{create_viewing_key, TokenAmount, TokenType},
     lp_token::{InitConfig, InstantiateMsg},
-    msg::amm_pair::{ExecuteMsg, InitMsg, InvokeMsg, QueryMsg, QueryMsgResponse},
+    msg::amm_pair::{ExecuteMsg, InitMsg, InvokeMsg, MigrateMsg, QueryMsg, QueryMsgResponse},
     snip20::helpers::send_msg,
-    utils::{pad_query_result, pad_response_result, try_addr_validate_option},
+    utils::{
+        migrate::{assert_can_migrate, set_contract_version},
+        pad_query_result, pad_response_result, try_addr_validate_option,
+    },
     Contract,
 };
 
@@ -117,11 +120,24 @@ pub fn instantiate(
     };
 
     config_w(deps.storage).save(&config)?;
+    set_contract_version(deps.storage, AMM_PAIR_CONTRACT_VERSION)?;
     response.data = Some(env.contract.address.as_bytes().into());
 
     Ok(response)
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_can_migrate(deps.storage, AMM_PAIR_CONTRACT_VERSION)?;
+    // No stored state has changed between versions yet, so only the version is recorded
+    set_contract_version(deps.storage, AMM_PAIR_CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", AMM_PAIR_CONTRACT_VERSION.to_string()))
+}
+
 #[entry_point]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     pad_response_result(
@@ -171,6 +187,34 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 config_w(deps.storage).save(&config)?;
                 Ok(Response::default())
             }
+            ExecuteMsg::CreateStakingContract { staking_init } => {
+                let config = config_r(deps.storage).load()?;
+                // The factory deploys staking for the pairs it registered
+                let from_factory = config
+                    .factory_contract
+                    .as_ref()
+                    .map_or(false, |factory| factory.address == info.sender);
+                if !from_factory {
+                    validate_admin(
+                        &deps.querier,
+                        AdminPermissions::ShadeSwapAdmin,
+                        &info.sender,
+                        &config.admin_auth,
+                    )?;
+                }
+                create_staking_contract(deps, &env, staking_init)
+            }
+            ExecuteMsg::SetStakingContract { staking_contract, force } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                assert_staking_replaceable(deps.as_ref(), &config, force.unwrap_or(false))?;
+                set_staking_contract(deps.storage, staking_contract)
+            }
             ExecuteMsg::AddWhiteListAddress { address } => {
                 let config = config_r(deps.storage).load()?;
                 validate_admin(
@@ -200,6 +244,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 expected_return,
                 to,
                 execute_arbitrage,
+                max_price_impact_bps,
             } => {
                 if !offer.token.is_native_token() {
                     return Err(StdError::generic_err("Use the receive interface"));
@@ -217,6 +262,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     offer,
                     expected_return,
                     execute_arbitrage,
+                    max_price_impact_bps,
                 )
             }
             ExecuteMsg::SetViewingKey { viewing_key } => {
@@ -305,6 +351,7 @@ fn receiver_callback(
                 to,
                 expected_return,
                 execute_arbitrage,
+                max_price_impact_bps,
             } => {
                 for token in config.pair.into_iter() {
                     match token {
@@ -331,6 +378,7 @@ fn receiver_callback(
                                     offer,
                                     expected_return,
                                     execute_arbitrage,
+                                    max_price_impact_bps,
                                 );
                             }
                         }
@@ -405,15 +453,18 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             }
             QueryMsg::GetTradeHistory {
                 api_key,
-                pagination,
+                start_after,
+                limit,
+                order,
             } => {
                 let config = config_r(deps.storage).load()?;
 
                 match config.factory_contract {
                     Some(factory_contract) => {
                         query::factory_authorize_api_key(deps, &factory_contract, api_key)?;
-                        let data = query::trade_history_page(deps, pagination)?;
-                        to_binary(&QueryMsgResponse::GetTradeHistory { data })
+                        let (data, next) =
+                            query::trade_history_page(deps, start_after, limit, order)?;
+                        to_binary(&QueryMsgResponse::GetTradeHistory { data, next })
                     }
                     None => Err(StdError::generic_err(
                         "Cannot get trade history if no factory contract is set.",
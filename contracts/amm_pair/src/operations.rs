@@ -13,7 +13,7 @@ use shadeswap_shared::{
     core::{Fee, TokenAmount, TokenPairAmount, TokenType, ViewingKey},
     msg::{
         amm_pair::{ArbitrageCallback, SwapInfo, SwapResult, TradeHistory},
-        staking::{InitMsg as StakingInitMsg, InvokeMsg as StakingInvokeMsg},
+        staking::{InitMsg as StakingInitMsg, InvokeMsg as StakingInvokeMsg, StakingContractInit},
     },
     snip20::{
         helpers::{
@@ -21,7 +21,7 @@ use shadeswap_shared::{
         },
         ExecuteMsg as SNIP20ExecuteMsg,
     },
-    utils::calc::sqrt,
+    utils::calc::{sqrt, BASIS_POINTS},
     Contract,
 };
 
@@ -67,80 +67,13 @@ pub fn register_lp_token(
     )?);
 
     // Initialize Staking Contract
-    match config.staking_contract_init {
-        Some(c) => {
-            match config.factory_contract {
-                Some(factory_contract) => {
-                    // Gets config from factory
-                    let factory_config = factory_config(deps.as_ref(), &factory_contract)?;
-                    response = response.add_submessage(SubMsg::reply_on_success(
-                        CosmosMsg::Wasm(WasmMsg::Instantiate {
-                            code_id: c.contract_info.id,
-                            label: c.custom_label.unwrap_or(format!(
-                                "ShadeSwap-Pair-Staking-Contract-{}",
-                                &env.contract.address
-                            )),
-                            msg: to_binary(&StakingInitMsg {
-                                daily_reward_amount: c.daily_reward_amount,
-                                reward_token: c.reward_token.clone(),
-                                pair_contract: Contract {
-                                    address: env.contract.address.clone(),
-                                    code_hash: env.contract.code_hash.clone(),
-                                },
-                                prng_seed: config.prng_seed.clone(),
-                                lp_token: Contract {
-                                    address: lp_token_address.address.clone(),
-                                    code_hash: lp_token_address.code_hash.clone(),
-                                },
-                                //default to same permit authenticator as factory
-                                authenticator: factory_config.authenticator,
-                                //default to same admin as factory
-                                admin_auth: factory_config.admin_auth,
-                                valid_to: c.valid_to,
-                            })?,
-                            code_hash: c.contract_info.code_hash.clone(),
-                            funds: vec![],
-                        }),
-                        INSTANTIATE_STAKING_CONTRACT_REPLY_ID,
-                    ));
-                }
-                None => {
-                    response = response.add_submessage(SubMsg::reply_on_success(
-                        CosmosMsg::Wasm(WasmMsg::Instantiate {
-                            code_id: c.contract_info.id,
-                            label: c.custom_label.unwrap_or(format!(
-                                "ShadeSwap-Pair-Staking-Contract-{}",
-                                &env.contract.address
-                            )),
-                            msg: to_binary(&StakingInitMsg {
-                                daily_reward_amount: c.daily_reward_amount,
-                                reward_token: c.reward_token.clone(),
-                                pair_contract: Contract {
-                                    address: env.contract.address.clone(),
-                                    code_hash: env.contract.code_hash.clone(),
-                                },
-                                prng_seed: config.prng_seed.clone(),
-                                lp_token: Contract {
-                                    address: lp_token_address.address.clone(),
-                                    code_hash: lp_token_address.code_hash.clone(),
-                                },
-                                //default to same permit authenticator as factory
-                                authenticator: None,
-                                //default to same admin as factory
-                                admin_auth: config.admin_auth,
-                                valid_to: c.valid_to,
-                            })?,
-                            code_hash: c.contract_info.code_hash.clone(),
-                            funds: vec![],
-                        }),
-                        INSTANTIATE_STAKING_CONTRACT_REPLY_ID,
-                    ));
-                }
-            }
-        }
-        _ => {
-            ();
-        }
+    if let Some(staking_init) = config.staking_contract_init.clone() {
+        response = response.add_submessage(instantiate_staking_msg(
+            deps.as_ref(),
+            env,
+            &config,
+            staking_init,
+        )?);
     }
 
     Ok(response)
@@ -190,6 +123,7 @@ pub fn swap(
     offer: TokenAmount,
     expected_return: Option<Uint128>,
     arbitrage_info: Option<ArbitrageCallback>,
+    max_price_impact_bps: Option<u64>,
 ) -> StdResult<Response> {
     let swaper_receiver = recipient.unwrap_or(sender.clone());
 
@@ -216,6 +150,16 @@ pub fn swap(
         }
     }
 
+    // check the price impact against the maximum accepted by the sender
+    if let Some(max_price_impact_bps) = max_price_impact_bps {
+        if swap_result.price_impact_bps > max_price_impact_bps {
+            return Err(StdError::generic_err(format!(
+                "Price impact exceeds maximum ({} > {} bps).",
+                swap_result.price_impact_bps, max_price_impact_bps
+            )));
+        }
+    }
+
     //get non-offer token
     let non_offer_token = if &config.pair.0 == &offer.token {
         &config.pair.1
@@ -308,6 +252,9 @@ pub fn swap(
             Attribute::new("token_in_key", &config.pair.get_token(input_token_index).expect("Failed to find input token").unique_key()),
             Attribute::new("token_out_key", output_token.unique_key()),
             Attribute::new("shade_dao_fee_amount", swap_result.shade_dao_fee_amount),
+            Attribute::new("price", &swap_result.price),
+            Attribute::new("mid_price", &swap_result.mid_price),
+            Attribute::new("price_impact_bps", swap_result.price_impact_bps.to_string()),
         ])
         .set_data(to_binary(&ExecuteMsgResponse::SwapResult {
             price: swap_result.price,
@@ -319,7 +266,83 @@ pub fn swap(
         })?))
 }
 
-// Set staking contract within the config
+// Instantiate a staking contract for the LP token, the reply sets it on the pair
+pub fn instantiate_staking_msg(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    staking_init: StakingContractInit,
+) -> StdResult<SubMsg> {
+    //default to same permit authenticator and admin as factory
+    let (authenticator, admin_auth) = match &config.factory_contract {
+        Some(factory_contract) => {
+            let factory_config = factory_config(deps, factory_contract, &config.pair)?;
+            (factory_config.authenticator, factory_config.admin_auth)
+        }
+        None => (None, config.admin_auth.clone()),
+    };
+
+    Ok(SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Instantiate {
+            code_id: staking_init.contract_info.id,
+            label: staking_init.custom_label.unwrap_or(format!(
+                "ShadeSwap-Pair-Staking-Contract-{}",
+                &env.contract.address
+            )),
+            msg: to_binary(&StakingInitMsg {
+                reward_token: staking_init.reward_token.clone(),
+                pair_contract: Contract {
+                    address: env.contract.address.clone(),
+                    code_hash: env.contract.code_hash.clone(),
+                },
+                prng_seed: config.prng_seed.clone(),
+                lp_token: config.lp_token.clone(),
+                authenticator,
+                admin_auth,
+            })?,
+            code_hash: staking_init.contract_info.code_hash.clone(),
+            funds: vec![],
+        }),
+        INSTANTIATE_STAKING_CONTRACT_REPLY_ID,
+    ))
+}
+
+pub fn create_staking_contract(
+    deps: DepsMut,
+    env: &Env,
+    staking_init: StakingContractInit,
+) -> StdResult<Response> {
+    let mut config = config_r(deps.storage).load()?;
+    assert_staking_replaceable(deps.as_ref(), &config, false)?;
+    // The reply reads the staking code hash from here
+    config.staking_contract_init = Some(staking_init.clone());
+    config_w(deps.storage).save(&config)?;
+
+    let mut response = Response::new().add_attribute("action", "create_staking_contract");
+    if let Some(staking_contract) = &config.staking_contract {
+        response = response.add_attribute("replaced_staking_contract", staking_contract.address.to_string());
+    }
+    Ok(response.add_submessage(instantiate_staking_msg(deps.as_ref(), env, &config, staking_init)?))
+}
+
+// Stakers would have to find the old contract to get their LP tokens back.
+// Forcing skips the check so an admin can drop a contract that no longer answers.
+pub fn assert_staking_replaceable(deps: Deps, config: &Config, force: bool) -> StdResult<()> {
+    if force {
+        return Ok(());
+    }
+    if let Some(staking_contract) = &config.staking_contract {
+        let total_staked = query::staking_total_staked(deps, staking_contract)?;
+        if !total_staked.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "Staking contract {} still holds {} staked LP tokens.",
+                staking_contract.address, total_staked
+            )));
+        }
+    }
+    Ok(())
+}
+
 pub fn set_staking_contract(
     storage: &mut dyn Storage,
     staking_contract: Option<Contract>
@@ -361,23 +384,25 @@ pub fn calculate_swap_result(
         token_in_pool = token_in_pool.checked_sub(offer.amount)?;
     }
     let swap_return_before_fee = calculate_price(offer.amount, token_in_pool, token_out_pool)?;
-    
+    let mid_price = if token_in_pool.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(token_out_pool, token_in_pool)
+    };
+    let price_impact_bps = calculate_price_impact(
+        offer.amount,
+        token_in_pool,
+        token_out_pool,
+        swap_return_before_fee,
+    )?;
 
     let mut lp_fee_amount = Uint128::zero();
     let mut shade_dao_fee_amount = Uint128::zero();
 
     if exclude_fee.is_none() || !exclude_fee.unwrap() {
         //unwrap safe because of conditional short circuiting
-        match &config.custom_fee {
-            Some(f) => {
-                lp_fee_amount = calculate_fee(swap_return_before_fee, f.lp_fee)?;
-                shade_dao_fee_amount = calculate_fee(swap_return_before_fee, f.shade_dao_fee)?;
-            }
-            None => {
-                lp_fee_amount = calculate_fee(swap_return_before_fee, lp_fee)?;
-                shade_dao_fee_amount = calculate_fee(swap_return_before_fee, shade_dao_fee)?;
-            }
-        }
+        lp_fee_amount = calculate_fee(swap_return_before_fee, lp_fee)?;
+        shade_dao_fee_amount = calculate_fee(swap_return_before_fee, shade_dao_fee)?;
     }
     let total_fee_amount = lp_fee_amount + shade_dao_fee_amount;
     let final_swap_return = swap_return_before_fee - total_fee_amount;
@@ -399,6 +424,8 @@ pub fn calculate_swap_result(
         total_fee_amount,
         result: result_swap,
         price: Decimal::from_ratio(final_swap_return, offer.amount).to_string(),
+        mid_price: mid_price.to_string(),
+        price_impact_bps,
         new_input_pool: token_in_pool.checked_add(offer.amount)?,
         new_output_pool: token_out_pool.checked_sub(final_swap_return.checked_add(shade_dao_fee_amount)?)?, // output pool is "original_pool - (swap + shade_dao_fee)" because it keeps the lp fee
         index_of_input_token,
@@ -686,6 +713,36 @@ pub fn calculate_price(
     Ok(token_out_pool_balance.multiply_ratio(amount, token_in_pool_balance + amount))
 }
 
+// Calculate the price impact in basis points of a trade, comparing the return before fees
+// with the return the same amount would get at the mid price of the pool
+pub fn calculate_price_impact(
+    amount: Uint128,
+    token_in_pool_balance: Uint128,
+    token_out_pool_balance: Uint128,
+    swap_return_before_fee: Uint128,
+) -> StdResult<u64> {
+    if amount.is_zero() {
+        return Ok(0u64);
+    }
+    if token_in_pool_balance.is_zero() {
+        return Ok(BASIS_POINTS);
+    }
+
+    let mid_price_return = Uint256::from(token_out_pool_balance) * Uint256::from(amount)
+        / Uint256::from(token_in_pool_balance);
+    if mid_price_return.is_zero() {
+        return Ok(0u64);
+    }
+
+    let shortfall = mid_price_return
+        .checked_sub(Uint256::from(swap_return_before_fee))
+        .unwrap_or(Uint256::zero());
+    let impact = Uint128::try_from(
+        shortfall * Uint256::from(BASIS_POINTS) / mid_price_return,
+    )?;
+    Ok(impact.u128() as u64)
+}
+
 // Add liquidity to pool
 pub fn add_liquidity(
     deps: DepsMut,
@@ -4,10 +4,11 @@ use cosmwasm_std::{
 };
 use shadeswap_shared::{
     amm_pair::{AMMSettings, QueryMsgResponse, TradeHistory, FeeInfo},
-    core::{Fee, TokenAmount, TokenPairAmount, TokenType},
+    core::{CustomFee, Fee, TokenAmount, TokenPair, TokenPairAmount, TokenType},
     factory::{QueryMsg as FactoryQueryMsg, QueryResponse as FactoryQueryResponse},
+    staking::{QueryMsg as StakingQueryMsg, QueryResponse as StakingQueryResponse},
     snip20::helpers::token_info,
-    Contract, Pagination,
+    cursor_page, Contract, PageOrder,
 };
 
 use crate::{
@@ -19,9 +20,39 @@ pub struct FactoryConfig {
     pub amm_settings: AMMSettings,
     pub authenticator: Option<Contract>,
     pub admin_auth: Contract,
+    pub fee_override: Option<CustomFee>,
 }
 
-pub fn factory_config(deps: Deps, factory: &Contract) -> StdResult<FactoryConfig> {
+// The factory applies its fee override for the pair to the returned AMM settings
+pub fn factory_config(deps: Deps, factory: &Contract, pair: &TokenPair) -> StdResult<FactoryConfig> {
+    let result: StdResult<FactoryQueryResponse> =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: factory.address.to_string(),
+            msg: to_binary(&FactoryQueryMsg::GetPairSettings { pair: pair.clone() })?,
+            code_hash: factory.code_hash.to_string(),
+        }));
+
+    match result {
+        Ok(FactoryQueryResponse::GetPairSettings {
+            amm_settings,
+            authenticator,
+            admin_auth,
+            fee_override,
+        }) => Ok(FactoryConfig {
+            amm_settings,
+            authenticator,
+            admin_auth,
+            fee_override,
+        }),
+        // A factory not yet upgraded to GetPairSettings has no fee overrides
+        Err(_) => legacy_factory_config(deps, factory),
+        _ => Err(StdError::generic_err(
+            "An error occurred while trying to retrieve factory settings.",
+        )),
+    }
+}
+
+fn legacy_factory_config(deps: Deps, factory: &Contract) -> StdResult<FactoryConfig> {
     let result: FactoryQueryResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: factory.address.to_string(),
@@ -31,15 +62,15 @@ pub fn factory_config(deps: Deps, factory: &Contract) -> StdResult<FactoryConfig
 
     match result {
         FactoryQueryResponse::GetConfig {
-            pair_contract: _,
             amm_settings,
-            lp_token_contract: _,
             authenticator,
             admin_auth,
+            ..
         } => Ok(FactoryConfig {
             amm_settings,
             authenticator,
             admin_auth,
+            fee_override: None,
         }),
         _ => Err(StdError::generic_err(
             "An error occurred while trying to retrieve factory settings.",
@@ -47,6 +78,21 @@ pub fn factory_config(deps: Deps, factory: &Contract) -> StdResult<FactoryConfig
     }
 }
 
+pub fn staking_total_staked(deps: Deps, staking_contract: &Contract) -> StdResult<Uint128> {
+    let result: StakingQueryResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: staking_contract.address.to_string(),
+        msg: to_binary(&StakingQueryMsg::GetConfig {})?,
+        code_hash: staking_contract.code_hash.to_string(),
+    }))?;
+
+    match result {
+        StakingQueryResponse::GetConfig { total_staked_lp_token, .. } => Ok(total_staked_lp_token),
+        _ => Err(StdError::generic_err(
+            "An error occurred while trying to retrieve the staking config.",
+        )),
+    }
+}
+
 pub fn swap_simulation(deps: Deps, env: Env, offer: TokenAmount, exclude_fee: Option<bool>) -> StdResult<Binary> {
     let config = config_r(deps.storage).load()?;
 
@@ -68,6 +114,8 @@ pub fn swap_simulation(deps: Deps, env: Env, offer: TokenAmount, exclude_fee: Op
         shade_dao_fee_amount: swap_result.shade_dao_fee_amount,
         result: swap_result.result,
         price: swap_result.price,
+        mid_price: swap_result.mid_price,
+        price_impact_bps: swap_result.price_impact_bps,
     };
     to_binary(&simulation_result)
 }
@@ -79,10 +127,11 @@ pub fn fee_info(deps: Deps) -> StdResult<FeeInfo> {
 
     let config = config_r(deps.storage).load()?;
 
-    let amm_settings: Option<AMMSettings> = if let Some(factory_contract) = &config.factory_contract {
-        Some(factory_config(deps, factory_contract)?.amm_settings)
+    let (amm_settings, fee_override) = if let Some(factory_contract) = &config.factory_contract {
+        let factory_config = factory_config(deps, factory_contract, &config.pair)?;
+        (Some(factory_config.amm_settings), factory_config.fee_override)
     } else {
-        None
+        (None, None)
     };
 
     //set dao address
@@ -93,8 +142,8 @@ pub fn fee_info(deps: Deps) -> StdResult<FeeInfo> {
         Addr::unchecked("")
     };
 
-    //set fees
-    match &config.custom_fee {
+    //set fees, the factory override for the pair wins over the fee the pair was instantiated with
+    match fee_override.as_ref().or(config.custom_fee.as_ref()) {
         Some(custom_fee) => {
             lp_fee = custom_fee.lp_fee;
             shade_dao_fee = custom_fee.shade_dao_fee;
@@ -265,23 +314,26 @@ pub fn trade_history(deps: Deps, count: u64) -> StdResult<TradeHistory> {
     Ok(trade_history)
 }
 
-pub fn trade_history_page(deps: Deps, pagination: Pagination) -> StdResult<Vec<TradeHistory>> {
+pub fn trade_history_page(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u8>,
+    order: Option<PageOrder>,
+) -> StdResult<(Vec<TradeHistory>, Option<u64>)> {
     let count = trade_count_r(deps.storage).may_load()?.unwrap_or(0u64);
-
-    if pagination.start >= count {
-        return Ok(vec![]);
-    }
-
-    let limit = pagination.limit.min(PAGINATION_LIMIT);
-    let end = (pagination.start + limit as u64).min(count);
-
-    let mut result = Vec::with_capacity((end - pagination.start) as usize);
-
-    for i in pagination.start..end {
-        let temp_index = i + 1;
-        let trade_history: TradeHistory = trade_history(deps, temp_index)?;
-        result.push(trade_history);
+    let limit = limit.unwrap_or(PAGINATION_LIMIT).min(PAGINATION_LIMIT);
+    let (indexes, next) = cursor_page(
+        1,
+        count,
+        start_after,
+        limit,
+        order.unwrap_or(PageOrder::Ascending),
+    );
+
+    let mut result = Vec::with_capacity(indexes.len());
+    for i in indexes {
+        result.push(trade_history(deps, i)?);
     }
 
-    Ok(result)
+    Ok((result, next))
 }
@@ -18,6 +18,10 @@ pub const CUSTOM_TOKEN_3: &str = "secret13q9rgw3ez5mf808vm6k0naya090hh0m5fe2436"
 pub const CUSTOM_TOKEN_4: &str = "secret1pf42ypa2awg0pxkx8lfyyrjcm28vq0qpffa8qx";
 pub const STAKING_CONTRACT: &str = "secret1pf42ypa2awg0pxkx8lfyyrjvm28vq0qpffa8qx";
 pub const FACTORY_CONTRACT_ADDRESS:& str = "secret1nulgwu6es24us9urgyvms7y02txyg0s02msgzw";
+// A factory from before GetPairSettings, it only answers GetConfig
+pub const LEGACY_FACTORY_ADDRESS:& str = "secret1nulgwu6es24us9urgyvms7y02txyg0s02msgzx";
+// A staking contract that fails every query
+pub const BROKEN_STAKING_CONTRACT:& str = "secret1pf42ypa2awg0pxkx8lfyyrjvm28vq0qpffa8qz";
 pub const ADMIN_CONTRACT:& str = "secret1pf42ypa2awg0pxkx8lfyyrjvm28vq0qpffa8qx";
 pub const SENDER:& str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czcmy2";
 use crate::state::config_r;
@@ -35,9 +39,12 @@ pub mod tests {
     use crate::operations::{
         add_address_to_whitelist, add_whitelist_address, calculate_hash, lp_virtual_swap,
         swap, calculate_swap_result, remove_addresses_from_whitelist, is_address_in_whitelist,
+        assert_staking_replaceable, set_staking_contract,
     };
     use crate::query::{self, estimated_liquidity};
-    use crate::state::{trade_count_r, config_w};
+    use crate::state::{trade_count_r, trade_count_w, trade_history_w, config_w};
+    use shadeswap_shared::amm_pair::TradeHistory;
+    use shadeswap_shared::PageOrder;
     use crate::test::help_test_lib::{
         mk_custom_token_amount, mk_native_token_pair, mock_custom_env, mock_dependencies
     };
@@ -110,6 +117,43 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn assert_trade_history_page_follows_cursor() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        for height in 1..=5u64 {
+            let trade = TradeHistory {
+                price: "1".to_string(),
+                amount_out: Uint128::new(10u128),
+                amount_in: Uint128::new(10u128),
+                timestamp: height,
+                direction: "Sell".to_string(),
+                total_fee_amount: Uint128::zero(),
+                lp_fee_amount: Uint128::zero(),
+                shade_dao_fee_amount: Uint128::zero(),
+                height,
+            };
+            trade_history_w(&mut deps.storage).save(height.to_string().as_bytes(), &trade)?;
+        }
+        trade_count_w(&mut deps.storage).save(&5u64)?;
+
+        let heights = |trades: Vec<TradeHistory>| trades.iter().map(|t| t.height).collect::<Vec<u64>>();
+        let (trades, next) = query::trade_history_page(deps.as_ref(), None, Some(2), None)?;
+        assert_eq!(heights(trades), vec![1, 2]);
+        assert_eq!(next, Some(2));
+        let (trades, next) = query::trade_history_page(deps.as_ref(), Some(4), Some(2), None)?;
+        assert_eq!(heights(trades), vec![5]);
+        assert_eq!(next, None);
+        let (trades, next) =
+            query::trade_history_page(deps.as_ref(), None, Some(3), Some(PageOrder::Descending))?;
+        assert_eq!(heights(trades), vec![5, 4, 3]);
+        assert_eq!(next, Some(3));
+        let (trades, next) =
+            query::trade_history_page(deps.as_ref(), next, Some(3), Some(PageOrder::Descending))?;
+        assert_eq!(heights(trades), vec![2, 1]);
+        assert_eq!(next, None);
+        Ok(())
+    }
+
     #[test]
     fn assert_add_address_to_whitelist_success() -> StdResult<()> {
         let seed = to_binary(&"SEED".to_string())?;
@@ -209,6 +253,7 @@ pub mod tests {
             None,
             mk_custom_token_amount(Uint128::from(1000u128), &token_pair),
             None,
+            None,
             None
         )?;
         assert_eq!(native_swap.messages.len(), 2);
@@ -234,6 +279,7 @@ pub mod tests {
             None,
             mk_custom_token_amount(Uint128::from(1000u128), &token_pair),
             None,
+            None,
             None
         )?;
         assert_eq!(native_swap.messages.len(), 2);
@@ -294,6 +340,26 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn assert_force_replaces_unresponsive_staking_contract() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let token_pair = mk_token_pair();
+        make_init_config(token_pair, &mut deps)?;
+        let mut config = config_r(&deps.storage).load()?;
+        config.staking_contract = Some(Contract {
+            address: Addr::unchecked(BROKEN_STAKING_CONTRACT),
+            code_hash: "".to_string(),
+        });
+        config_w(&mut deps.storage).save(&config)?;
+
+        assert!(assert_staking_replaceable(deps.as_ref(), &config, false).is_err());
+        assert_staking_replaceable(deps.as_ref(), &config, true)?;
+
+        set_staking_contract(&mut deps.storage, None)?;
+        assert!(config_r(&deps.storage).load()?.staking_contract.is_none());
+        Ok(())
+    }
+
     #[test]
     pub fn assert_trader_address_hash() -> StdResult<()> {
         let trader = Addr::unchecked("test");
@@ -318,8 +384,10 @@ pub mod tests_calculation_price_and_fee {
     use shadeswap_shared::msg::amm_pair::QueryMsgResponse;
     use crate::operations::lp_virtual_swap;
     use crate::contract;
+    use crate::state::config_w;
+    use shadeswap_shared::Contract;
     use crate::operations::{ calculate_lp_tokens,
-        add_liquidity, add_whitelist_address, calculate_price, calculate_swap_result, swap, remove_liquidity, is_address_in_whitelist   };
+        add_liquidity, add_whitelist_address, calculate_price, calculate_price_impact, calculate_swap_result, swap, remove_liquidity, is_address_in_whitelist   };
     use crate::test::help_test_lib::mk_custom_token_pair;
     use crate::query::{self, estimated_liquidity};
     use crate::test::help_test_lib::{
@@ -433,6 +501,34 @@ pub mod tests_calculation_price_and_fee {
         Ok(())
     }
 
+    #[test]
+    fn assert_fee_info_falls_back_to_legacy_factory_config() -> StdResult<()>{
+        let mut deps = mock_dependencies(&[]);
+        let token_pair = mk_token_pair_test_calculation_price_fee();
+        let mut config = make_init_config_test_calculate_price_fee(deps.as_mut(), token_pair, None, Some(LP_TOKEN.to_string()))?;
+        config.factory_contract = Some(Contract {
+            address: Addr::unchecked(LEGACY_FACTORY_ADDRESS),
+            code_hash: "".to_string(),
+        });
+        config_w(deps.as_mut().storage).save(&config)?;
+
+        let fee_info = query::fee_info(deps.as_ref())?;
+        assert_eq!(fee_info.shade_dao_address, Addr::unchecked("LEGACY_DAO"));
+        assert_eq!(fee_info.lp_fee, Fee::new(3, 100));
+        assert_eq!(fee_info.shade_dao_fee, Fee::new(1, 100));
+
+        // Without a factory override the pair's own fee applies
+        config.custom_fee = Some(CustomFee {
+            shade_dao_fee: Fee::new(8, 100),
+            lp_fee: Fee::new(1, 100),
+        });
+        config_w(deps.as_mut().storage).save(&config)?;
+        let fee_info = query::fee_info(deps.as_ref())?;
+        assert_eq!(fee_info.lp_fee, Fee::new(1, 100));
+        assert_eq!(fee_info.shade_dao_fee, Fee::new(8, 100));
+        Ok(())
+    }
+
     #[test]
     fn assert_calculate_swap_result_without_custom_fee() -> StdResult<()>{
         let _custom_fee: Option<CustomFee> = None;
@@ -592,6 +688,7 @@ pub mod tests_calculation_price_and_fee {
             Some(address_a.clone()),          
             mk_custom_token_amount_test_calculation_price_fee(Uint128::from(offer_amount), token), 
             Some(Uint128::from(40000u128)),
+            None,
             None
         );
 
@@ -622,6 +719,7 @@ pub mod tests_calculation_price_and_fee {
             mk_custom_token_amount_test_calculation_price_fee(Uint128::from(offer_amount), 
                 mk_token_pair_custom_addr("CUSTOMER_TOKEN_3", CUSTOM_TOKEN_1)), 
             Some(Uint128::from(400u128)),
+            None,
             None
         );
 
@@ -651,6 +749,7 @@ pub mod tests_calculation_price_and_fee {
             Some(Addr::unchecked(address_a.clone())),          
             mk_custom_token_amount_test_calculation_price_fee(Uint128::from(offer_amount), token), 
             Some(Uint128::from(400u128)),
+            None,
             None
         );
          assert_eq!(
@@ -659,6 +758,43 @@ pub mod tests_calculation_price_and_fee {
         Ok(())
     }
 
+    #[test]
+    fn assert_calculate_price_impact() -> StdResult<()>{
+        // 1000 in against a 10000/10000 pool returns 909 instead of 1000 at mid price
+        let return_amount = calculate_price(Uint128::from(1000u128), Uint128::from(10000u128), Uint128::from(10000u128))?;
+        assert_eq!(return_amount, Uint128::from(909u128));
+        let impact = calculate_price_impact(Uint128::from(1000u128), Uint128::from(10000u128), Uint128::from(10000u128), return_amount)?;
+        assert_eq!(impact, 910u64);
+        let impact = calculate_price_impact(Uint128::zero(), Uint128::from(10000u128), Uint128::from(10000u128), Uint128::zero())?;
+        assert_eq!(impact, 0u64);
+        let impact = calculate_price_impact(Uint128::from(1000u128), Uint128::zero(), Uint128::from(10000u128), Uint128::from(10000u128))?;
+        assert_eq!(impact, 10000u64);
+        Ok(())
+    }
+
+    #[test]
+    fn assert_swap_with_price_impact_above_max_throw_exception() -> StdResult<()>{
+        let mut deps = mock_dependencies(&[]);
+        let token_pair = mk_token_pair_test_calculation_price_fee();
+        let config = make_init_config_test_calculate_price_fee(deps.as_mut(), token_pair, None,Some(LP_TOKEN.to_string()))?;         
+        let offer_amount: u128 = 2000;          
+        let address_a = Addr::unchecked("TESTA".to_string());
+        let token = config.pair.clone();
+        let swap_result = swap(
+            deps.as_mut(),
+            mock_custom_env(FACTORY_CONTRACT_ADDRESS),
+            config,
+            address_a.clone(),
+            Some(address_a.clone()),          
+            mk_custom_token_amount_test_calculation_price_fee(Uint128::from(offer_amount), token), 
+            None,
+            None,
+            Some(0u64)
+        );
+        assert!(swap_result.unwrap_err().to_string().contains("Price impact exceeds maximum"));
+        Ok(())
+    }
+
     #[test]
     fn assert_slippage_add_liqudity_with_less_expected_throw_error() -> StdResult<()>{
         let mut deps = mock_dependencies(&[]);
@@ -1023,7 +1159,7 @@ pub mod help_test_lib {
 
     use crate::contract::instantiate;
     use shadeswap_shared::core::{CustomFee, Fee, TokenPair, TokenPairAmount};
-    use shadeswap_shared::msg::factory::QueryResponse as FactoryQueryResponse;
+    use shadeswap_shared::msg::factory::{QueryMsg as FactoryQueryMsg, QueryResponse as FactoryQueryResponse};
     use shadeswap_shared::snip20::manager::Balance;
     use shadeswap_shared::snip20::QueryAnswer;
     use shadeswap_shared::snip20::QueryMsg;
@@ -1173,12 +1309,10 @@ pub mod help_test_lib {
                     code_hash: "".to_string(),
                     id: 1,
                 },
-                daily_reward_amount: Uint128::from(1000u128),
                 reward_token: TokenType::CustomToken {
                     contract_addr: Addr::unchecked("".to_string()),
                     token_code_hash: "".to_string(),
                 }, 
-                valid_to: Uint128::new(3747905010000u128),
                 custom_label: None,
             }),
             prng_seed: to_binary(&"to_string".to_string())?,
@@ -1303,17 +1437,44 @@ pub mod help_test_lib {
                                     code_hash: "".to_string(),
                                 },
                             };
-                            let response = FactoryQueryResponse::GetConfig {
-                                pair_contract: ContractInstantiationInfo { code_hash: "".to_string(), id: 1_u64 },
+                            let response = FactoryQueryResponse::GetPairSettings {
                                 amm_settings: amm_settings,
-                                lp_token_contract: ContractInstantiationInfo { code_hash: "".to_string(), id: 2_u64 },
                                 authenticator: None,
-                                admin_auth: Contract { address: Addr::unchecked(ADMIN_CONTRACT), code_hash: "".to_string() }
+                                admin_auth: Contract { address: Addr::unchecked(ADMIN_CONTRACT), code_hash: "".to_string() },
+                                fee_override: None,
                             };
                             QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
                                 to_binary(&response).unwrap(),
                             ))
                         }
+                        LEGACY_FACTORY_ADDRESS => match from_binary(&msg).unwrap() {
+                            FactoryQueryMsg::GetConfig {} => {
+                                let response = FactoryQueryResponse::GetConfig {
+                                    pair_contract: ContractInstantiationInfo { code_hash: "".to_string(), id: 1 },
+                                    amm_settings: shadeswap_shared::amm_pair::AMMSettings {
+                                        lp_fee: Fee::new(3, 100),
+                                        shade_dao_fee: Fee::new(1, 100),
+                                        shade_dao_address: Contract {
+                                            address: Addr::unchecked("LEGACY_DAO"),
+                                            code_hash: "".to_string(),
+                                        },
+                                    },
+                                    lp_token_contract: ContractInstantiationInfo { code_hash: "".to_string(), id: 2 },
+                                    authenticator: None,
+                                    admin_auth: Contract { address: Addr::unchecked(ADMIN_CONTRACT), code_hash: "".to_string() },
+                                    creation_policy: Default::default(),
+                                };
+                                QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
+                                    to_binary(&response).unwrap(),
+                                ))
+                            }
+                            _ => QuerierResult::Ok(cosmwasm_std::ContractResult::Err(
+                                "unknown variant".to_string(),
+                            )),
+                        },
+                        BROKEN_STAKING_CONTRACT => QuerierResult::Ok(cosmwasm_std::ContractResult::Err(
+                            "staking contract is broken".to_string(),
+                        )),
                         CUSTOM_TOKEN_1 => {
                             match from_binary(&msg).unwrap(){
                                 QueryMsg::TokenInfo { /* fields */ } =>{
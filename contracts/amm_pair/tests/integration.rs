@@ -93,12 +93,10 @@ pub fn amm_pair_integration_tests_with_custom_token() {
             contract_info:  ContractInstantiationInfo { 
                 code_hash: staking_contract_info.code_hash.to_owned(), 
                 id: staking_contract_info.code_id},
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.to_owned(), 
                 token_code_hash: reward_contract.code_hash.to_owned()
             },
-            valid_to: Uint128::new(3747905010000u128),
             custom_label: None
         }), 
         custom_fee: None,
@@ -277,6 +275,7 @@ pub fn amm_pair_integration_tests_with_custom_token() {
         expected_return: Some(Uint128::new(500u128)), 
         to: Some(owner_addr.to_string()),
         execute_arbitrage: None,
+        max_price_impact_bps: None,
     }).unwrap();
 
     let _ = send_snip20_with_msg(
@@ -420,12 +419,10 @@ pub fn amm_pair_integration_tests_native_token() {
             contract_info:  ContractInstantiationInfo { 
                 code_hash: staking_contract_info.code_hash.to_owned(), 
                 id: staking_contract_info.code_id},
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.to_owned(), 
                 token_code_hash: reward_contract.code_hash.to_owned()
             },
-            valid_to: Uint128::new(3747905010000u128),
             custom_label: None
         }), 
         custom_fee: None,
@@ -588,7 +585,8 @@ pub fn amm_pair_integration_tests_native_token() {
         }, 
         expected_return: Some(Uint128::new(500u128)), 
         to: Some(owner_addr.to_string()),
-        execute_arbitrage: None
+        execute_arbitrage: None,
+        max_price_impact_bps: None,
     };
 
     let result = router.execute_contract(
@@ -756,12 +754,10 @@ pub fn test_sslp_with_two_virtual_providers() {
             contract_info:  ContractInstantiationInfo { 
                 code_hash: staking_contract_info.code_hash.to_owned(), 
                 id: staking_contract_info.code_id},
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.to_owned(), 
                 token_code_hash: reward_contract.code_hash.to_owned()
             },
-            valid_to: Uint128::new(3747905010000u128),
             custom_label: None
         }), 
         custom_fee: None,
@@ -1,7 +1,15 @@
 use crate::{
-    operations::{add_amm_pairs, create_pair, register_amm_pair, set_config},
+    operations::{
+        accept_admin_auth, add_amm_pairs, assert_creation_funds, cancel_config, clear_pair_fee,
+        create_pair, create_staking_for_pair, creation_fee_msgs, execute_config, index_amm_pairs,
+        migrate_pairs, propose_admin_auth, propose_config, register_amm_pair, remove_amm_pairs,
+        set_config, set_pair_fee,
+    },
     query,
-    state::{config_r, config_w, ephemeral_storage_r, ephemeral_storage_w, prng_seed_w, Config},
+    state::{
+        config_r, config_w, ephemeral_storage_r, ephemeral_storage_w, pair_labels_w, prng_seed_w,
+        Config,
+    },
 };
 use cosmwasm_std::{
     entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
@@ -11,12 +19,18 @@ use shadeswap_shared::{
     admin::helpers::{validate_admin, AdminPermissions},
     amm_pair::AMMPair,
     core::ViewingKey,
-    msg::factory::{ExecuteMsg, InitMsg, QueryMsg, QueryResponse},
-    utils::{pad_query_result, pad_response_result},
+    msg::factory::{
+        ConfigChanges, CreationPolicy, ExecuteMsg, InitMsg, MigrateMsg, QueryMsg, QueryResponse,
+    },
+    utils::{
+        migrate::{assert_can_migrate, set_contract_version},
+        pad_query_result, pad_response_result,
+    },
     BLOCK_SIZE,
 };
 
 pub const INSTANTIATE_REPLY_ID: u64 = 1u64;
+pub const FACTORY_CONTRACT_VERSION: u32 = 2;
 
 #[entry_point]
 pub fn instantiate(
@@ -27,14 +41,29 @@ pub fn instantiate(
 ) -> StdResult<Response> {
     prng_seed_w(deps.storage).save(&msg.prng_seed)?;
     config_w(deps.storage).save(&Config::from_init_msg(msg))?;
+    set_contract_version(deps.storage, FACTORY_CONTRACT_VERSION)?;
     Ok(Response::default())
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_can_migrate(deps.storage, FACTORY_CONTRACT_VERSION)?;
+    // Version 1 registries have no address or token lookups
+    if stored_version < 2 {
+        index_amm_pairs(deps.storage)?;
+    }
+    set_contract_version(deps.storage, FACTORY_CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", FACTORY_CONTRACT_VERSION.to_string()))
+}
+
 #[entry_point]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     pad_response_result(
         match msg {
-            //Only admins can create pairs via factory
             ExecuteMsg::CreateAMMPair {
                 pair,
                 entropy,
@@ -44,13 +73,39 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 amm_pair_custom_label,
             } => {
                 let config = config_r(deps.storage).load()?;
-                validate_admin(
-                    &deps.querier,
-                    AdminPermissions::ShadeSwapAdmin,
-                    &info.sender,
-                    &config.admin_auth,
-                )?;
-                create_pair(
+                let charged_fee = match &config.creation_policy {
+                    CreationPolicy::Open => None,
+                    CreationPolicy::AdminOnly => {
+                        validate_admin(
+                            &deps.querier,
+                            AdminPermissions::ShadeSwapAdmin,
+                            &info.sender,
+                            &config.admin_auth,
+                        )?;
+                        None
+                    }
+                    CreationPolicy::FeeGated { fee } => {
+                        // Admins create pairs for free
+                        if validate_admin(
+                            &deps.querier,
+                            AdminPermissions::ShadeSwapAdmin,
+                            &info.sender,
+                            &config.admin_auth,
+                        )
+                        .is_ok()
+                        {
+                            None
+                        } else {
+                            Some(fee)
+                        }
+                    }
+                };
+                assert_creation_funds(&info, charged_fee)?;
+                let fee_msgs = match charged_fee {
+                    Some(fee) => creation_fee_msgs(&info, fee, &config)?,
+                    None => vec![],
+                };
+                Ok(create_pair(
                     deps,
                     env,
                     pair,
@@ -59,7 +114,8 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     lp_token_decimals,
                     amm_pair_custom_label,
                     lp_token_custom_label,
-                )
+                )?
+                .add_messages(fee_msgs))
             }
             ExecuteMsg::SetConfig {
                 pair_contract,
@@ -67,6 +123,8 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 amm_settings,
                 api_key,
                 admin_auth,
+                creation_policy,
+                config_timelock,
             } => {
                 let config = config_r(deps.storage).load()?;
                 validate_admin(
@@ -75,15 +133,66 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     &info.sender,
                     &config.admin_auth,
                 )?;
+                if admin_auth.is_some() {
+                    return Err(StdError::generic_err(
+                        "Use ProposeAdminAuth to change the admin auth contract.",
+                    ));
+                }
                 set_config(
-                    pair_contract,
-                    lp_token_contract,
-                    amm_settings,
                     deps.storage,
-                    api_key,
-                    admin_auth,
+                    ConfigChanges {
+                        pair_contract,
+                        lp_token_contract,
+                        amm_settings,
+                        api_key,
+                        creation_policy,
+                        config_timelock,
+                        pair_fees: None,
+                        cleared_pair_fees: None,
+                    },
                 )
             }
+            ExecuteMsg::ProposeConfig { changes } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                propose_config(deps.storage, &env, changes)
+            }
+            ExecuteMsg::ExecuteConfig { id } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                execute_config(deps.storage, &env, id)
+            }
+            ExecuteMsg::CancelConfig { id } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                cancel_config(deps.storage, id)
+            }
+            ExecuteMsg::ProposeAdminAuth { admin_auth } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                propose_admin_auth(deps.storage, admin_auth)
+            }
+            ExecuteMsg::AcceptAdminAuth {} => accept_admin_auth(deps, &info.sender),
             ExecuteMsg::AddAMMPairs { amm_pairs } => {
                 let config = config_r(deps.storage).load()?;
                 validate_admin(
@@ -94,13 +203,68 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 )?;
                 add_amm_pairs(deps.storage, amm_pairs)
             }
+            ExecuteMsg::RemoveAMMPairs { pairs } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                remove_amm_pairs(deps.storage, pairs)
+            }
+            ExecuteMsg::MigratePairs {
+                code_id,
+                code_hash,
+                pairs,
+                msg,
+            } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                migrate_pairs(deps.storage, code_id, code_hash, pairs, msg)
+            }
+            ExecuteMsg::SetPairFee { pair, fee } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_pair_fee(deps.storage, pair, fee)
+            }
+            ExecuteMsg::ClearPairFee { pair } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                clear_pair_fee(deps.storage, pair)
+            }
+            ExecuteMsg::CreateStakingForPair { pair, staking_init } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                create_staking_for_pair(deps.storage, pair, staking_init)
+            }
         },
         BLOCK_SIZE,
     )
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     pad_query_result(
         match msg {
             QueryMsg::GetConfig {} => {
@@ -111,6 +275,8 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                     api_key: _,
                     authenticator,
                     admin_auth,
+                    creation_policy,
+                    config_timelock: _,
                 } = config_r(deps.storage).load()?;
                 to_binary(&QueryResponse::GetConfig {
                     pair_contract,
@@ -118,10 +284,29 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                     lp_token_contract,
                     authenticator,
                     admin_auth,
+                    creation_policy,
                 })
             }
-            QueryMsg::ListAMMPairs { pagination } => query::pairs_page(deps, pagination),
+            QueryMsg::ListAMMPairs {
+                start_after,
+                limit,
+                order,
+            } => query::pairs_page(deps, start_after, limit, order),
+            QueryMsg::GetPairCount {} => query::pair_count(deps),
             QueryMsg::GetAMMPairAddress { pair } => query::amm_pair_address(&deps, pair),
+            QueryMsg::ListPairsForToken { token, pagination } => {
+                query::pairs_for_token(deps, token, pagination)
+            }
+            QueryMsg::ListTokens { pagination } => query::tokens_page(deps, pagination),
+            QueryMsg::GetPairsByAddress { addresses } => query::pairs_by_address(deps, addresses),
+            QueryMsg::GetPairSettings { pair } => query::pair_settings(deps, pair),
+            QueryMsg::ListFeeOverrides { pagination } => query::fee_overrides_page(deps, pagination),
+            QueryMsg::GetPairByLabel { label } => query::pair_by_label(deps, label),
+            QueryMsg::GetNextPairLabel {} => query::next_pair_label(deps, env),
+            QueryMsg::PendingConfigChanges {} => query::pending_config_changes(deps),
+            QueryMsg::ListAMMPairsWithInfo { pagination } => {
+                query::pairs_with_info_page(deps, pagination)
+            }
             QueryMsg::AuthorizeApiKey { api_key } => {
                 let config = config_r(deps.storage).load()?;
                 to_binary(&QueryResponse::AuthorizeApiKey {
@@ -141,11 +326,13 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
                 Some(x) => {
                     let contract_address = String::from_utf8(x.to_vec())?;
                     let config = ephemeral_storage_r(deps.storage).load()?;
+                    let address = deps.api.addr_validate(&contract_address.replace(" ", ""))?;
+                    pair_labels_w(deps.storage).save(config.label.as_bytes(), &address)?;
                     register_amm_pair(
                         deps.storage,
                         AMMPair {
                             pair: config.pair,
-                            address: deps.api.addr_validate(&contract_address.replace(" ", ""))?,
+                            address,
                             enabled: true,
                             code_hash: config.code_hash,
                         },
@@ -1,18 +1,33 @@
 use crate::{
     contract::INSTANTIATE_REPLY_ID,
     state::{
-        amm_pair_keys_r, amm_pair_keys_w, amm_pairs_w, config_r, config_w, ephemeral_storage_w,
-        prng_seed_r, total_amm_pairs_r, total_amm_pairs_w, NextPairKey, amm_pairs_r,
+        Config,
+        amm_pair_index_by_address_r, amm_pair_index_by_address_w, amm_pair_keys_r,
+        amm_pair_keys_w, amm_pairs_w, config_r, token_pair_counts_r, token_pair_counts_w,
+        token_pairs_r, token_pairs_w, token_pair_positions_r, token_pair_positions_w,
+        config_w, ephemeral_storage_w, fee_override_index_r, fee_override_index_w,
+        fee_overrides_r, fee_overrides_w, total_fee_overrides_r, total_fee_overrides_w, prng_seed_r, tokens_r, tokens_w, total_amm_pairs_r,
+        total_amm_pairs_w, total_tokens_r, total_tokens_w, NextPairKey, amm_pairs_r,
+        pair_label, pair_labels_r, pair_nonce_r, pair_nonce_w, pending_admin_auth_r,
+        pending_admin_auth_w, pending_config_changes_r, pending_config_changes_w,
+        next_config_change_id_r, next_config_change_id_w, load_next_amm_pair_id,
+        next_amm_pair_id_w, PAGINATION_LIMIT,
     },
 };
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, DepsMut, Env, Response, StdError, StdResult, Storage, SubMsg,
-    WasmMsg,
+    to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo,
+    Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 use shadeswap_shared::{
-    amm_pair::{generate_pair_key, AMMPair, AMMSettings},
-    core::{ContractInstantiationInfo, TokenPair, ViewingKey},
-    msg::{amm_pair::InitMsg as AMMPairInitMsg, staking::StakingContractInit},
+    admin::helpers::{validate_admin, AdminPermissions},
+    amm_pair::{generate_pair_key, AMMPair},
+    core::{CustomFee, TokenAmount, TokenPair, TokenType, ViewingKey},
+    msg::{
+        amm_pair::{ExecuteMsg as AMMPairExecuteMsg, InitMsg as AMMPairInitMsg},
+        factory::{ConfigChanges, CreationPolicy, PairFeeOverride, PendingConfigChange},
+        staking::StakingContractInit,
+    },
+    snip20::helpers::{token_info, transfer_from_msg},
     Contract,
 };
 
@@ -24,24 +39,25 @@ pub fn add_amm_pairs(storage: &mut dyn Storage, amm_pairs: Vec<AMMPair>) -> StdR
     for amm_pair in amm_pairs {
         let new_key = generate_pair_key(&amm_pair.pair);
         let existing_pair = amm_pair_keys_r(storage).may_load(&new_key)?;
-        let total_count_singleton: u64 = total_amm_pairs_r(storage).may_load()?.unwrap_or(0u64);
 
         match existing_pair {
             Some(e_p) => {
                 amm_pair_keys_w(storage).save(&new_key, &amm_pair.address)?;
-                for i in 0..total_count_singleton {
-                    let existing_pair = amm_pairs_r(storage).load(&i.to_string().as_bytes())?;
-                    if existing_pair.pair == amm_pair.pair {
-                        amm_pairs_w(storage).save(&i.to_string().as_bytes(), &amm_pair)?;
-                        break;
-                    }
+                let index = amm_pair_index_by_address_r(storage).load(e_p.as_bytes())?;
+                amm_pairs_w(storage).save(&index.to_string().as_bytes(), &amm_pair)?;
+                if e_p != amm_pair.address {
+                    amm_pair_index_by_address_w(storage).remove(e_p.as_bytes());
                 }
+                index_amm_pair(storage, index, &amm_pair)?;
             }
             None => {
-                let next_count = total_count_singleton;
+                let id = load_next_amm_pair_id(storage)?;
+                let total_count = total_amm_pairs_r(storage).may_load()?.unwrap_or(0u64);
                 amm_pair_keys_w(storage).save(&new_key, &amm_pair.address)?;
-                amm_pairs_w(storage).save(&next_count.to_string().as_bytes(), &amm_pair)?;
-                total_amm_pairs_w(storage).save(&(next_count + 1))?;
+                amm_pairs_w(storage).save(&id.to_string().as_bytes(), &amm_pair)?;
+                next_amm_pair_id_w(storage).save(&(id + 1))?;
+                total_amm_pairs_w(storage).save(&(total_count + 1))?;
+                index_amm_pair(storage, id, &amm_pair)?;
             }
         }
     }
@@ -49,36 +65,476 @@ pub fn add_amm_pairs(storage: &mut dyn Storage, amm_pairs: Vec<AMMPair>) -> StdR
     Ok(Response::new().add_attribute("action", "register_amm_pairs"))
 }
 
-pub fn set_config(
-    pair_contract: Option<ContractInstantiationInfo>,
-    lp_token_contract: Option<ContractInstantiationInfo>,
-    amm_settings: Option<AMMSettings>,
+/// Deregister pairs, the ids of the other pairs are left untouched so page cursors stay valid
+pub fn remove_amm_pairs(storage: &mut dyn Storage, pairs: Vec<Addr>) -> StdResult<Response> {
+    let mut events = vec![];
+    for address in pairs {
+        let index = match amm_pair_index_by_address_r(storage).may_load(address.as_bytes())? {
+            Some(index) => index,
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "Pair {} is not registered.",
+                    address
+                )))
+            }
+        };
+        let amm_pair = amm_pairs_r(storage).load(index.to_string().as_bytes())?;
+
+        let pair_key = generate_pair_key(&amm_pair.pair);
+        if amm_pair_keys_r(storage).may_load(&pair_key)?.as_ref() == Some(&amm_pair.address) {
+            amm_pair_keys_w(storage).remove(&pair_key);
+            // A pair registered again for the tokens starts from the default fees
+            remove_fee_override(storage, &pair_key)?;
+        }
+        amm_pair_index_by_address_w(storage).remove(amm_pair.address.as_bytes());
+        for token in [&amm_pair.pair.0, &amm_pair.pair.1] {
+            remove_token_pair_index(storage, token, index)?;
+        }
+
+        amm_pairs_w(storage).remove(index.to_string().as_bytes());
+        let total_count = total_amm_pairs_r(storage).load()?;
+        total_amm_pairs_w(storage).save(&(total_count - 1))?;
+
+        events.push(
+            Event::new("deregister_amm_pair")
+                .add_attribute("pair_address", amm_pair.address.to_string())
+                .add_attribute("token_0", amm_pair.pair.0.unique_key())
+                .add_attribute("token_1", amm_pair.pair.1.unique_key()),
+        );
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_amm_pairs")
+        .add_events(events))
+}
+
+pub fn set_pair_fee(storage: &mut dyn Storage, pair: TokenPair, fee: CustomFee) -> StdResult<Response> {
+    assert_config_not_timelocked(storage)?;
+    save_pair_fee(storage, &pair, &fee)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_pair_fee")
+        .add_attribute("pair", pair.to_string())
+        .add_attribute("lp_fee", format!("{}/{}", fee.lp_fee.nom, fee.lp_fee.denom))
+        .add_attribute(
+            "shade_dao_fee",
+            format!("{}/{}", fee.shade_dao_fee.nom, fee.shade_dao_fee.denom),
+        ))
+}
+
+pub fn clear_pair_fee(storage: &mut dyn Storage, pair: TokenPair) -> StdResult<Response> {
+    assert_config_not_timelocked(storage)?;
+    clear_fee_override(storage, &pair)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "clear_pair_fee")
+        .add_attribute("pair", pair.to_string()))
+}
+
+fn validate_pair_fee(fee: &CustomFee) -> StdResult<()> {
+    //Don't allow for fees with invalid zeros
+    if (fee.lp_fee.denom == 0u64 && fee.lp_fee.nom != 0u64)
+        || (fee.shade_dao_fee.denom == 0u64 && fee.shade_dao_fee.nom != 0u64)
+    {
+        return Err(StdError::generic_err(
+            "One of the fee denoms are zero and nom is not 0.",
+        ));
+    }
+    Ok(())
+}
+
+fn save_pair_fee(storage: &mut dyn Storage, pair: &TokenPair, fee: &CustomFee) -> StdResult<()> {
+    validate_pair_fee(fee)?;
+
+    let pair_key = generate_pair_key(pair);
+    if amm_pair_keys_r(storage).may_load(&pair_key)?.is_none() {
+        return Err(StdError::generic_err(format!(
+            "Pair {} is not registered.",
+            pair
+        )));
+    }
+
+    let index = match fee_override_index_r(storage).may_load(&pair_key)? {
+        Some(index) => index,
+        None => {
+            let index = total_fee_overrides_r(storage).may_load()?.unwrap_or(0u64);
+            fee_override_index_w(storage).save(&pair_key, &index)?;
+            total_fee_overrides_w(storage).save(&(index + 1))?;
+            index
+        }
+    };
+    fee_overrides_w(storage).save(
+        index.to_string().as_bytes(),
+        &PairFeeOverride {
+            pair: pair.clone(),
+            fee: fee.clone(),
+        },
+    )
+}
+
+fn clear_fee_override(storage: &mut dyn Storage, pair: &TokenPair) -> StdResult<()> {
+    if !remove_fee_override(storage, &generate_pair_key(pair))? {
+        return Err(StdError::generic_err(format!(
+            "Pair {} has no fee override.",
+            pair
+        )));
+    }
+    Ok(())
+}
+
+/// Drop the fee override stored for a pair key, returns false when there was none
+fn remove_fee_override(storage: &mut dyn Storage, pair_key: &[u8]) -> StdResult<bool> {
+    let index = match fee_override_index_r(storage).may_load(pair_key)? {
+        Some(index) => index,
+        None => return Ok(false),
+    };
+    fee_override_index_w(storage).remove(pair_key);
+
+    // Move the last override into the freed slot to keep the list dense
+    let last_index = total_fee_overrides_r(storage).load()? - 1;
+    if index != last_index {
+        let last = fee_overrides_r(storage).load(last_index.to_string().as_bytes())?;
+        fee_overrides_w(storage).save(index.to_string().as_bytes(), &last)?;
+        fee_override_index_w(storage).save(&generate_pair_key(&last.pair), &index)?;
+    }
+    fee_overrides_w(storage).remove(last_index.to_string().as_bytes());
+    total_fee_overrides_w(storage).save(&last_index)?;
+
+    Ok(true)
+}
+
+pub fn create_staking_for_pair(
+    storage: &dyn Storage,
+    pair: Addr,
+    staking_init: StakingContractInit,
+) -> StdResult<Response> {
+    let amm_pair = match amm_pair_index_by_address_r(storage).may_load(pair.as_bytes())? {
+        Some(index) => amm_pairs_r(storage).load(index.to_string().as_bytes())?,
+        None => {
+            return Err(StdError::generic_err(format!(
+                "Pair {} is not registered.",
+                pair
+            )))
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "create_staking_for_pair")
+        .add_attribute("pair", amm_pair.address.to_string())
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: amm_pair.address.to_string(),
+            code_hash: amm_pair.code_hash,
+            msg: to_binary(&AMMPairExecuteMsg::CreateStakingContract { staking_init })?,
+            funds: vec![],
+        })))
+}
+
+/// Index every registered pair, for registries written before the lookups existed
+pub fn index_amm_pairs(storage: &mut dyn Storage) -> StdResult<()> {
+    for id in 0..load_next_amm_pair_id(storage)? {
+        if let Some(amm_pair) = amm_pairs_r(storage).may_load(id.to_string().as_bytes())? {
+            index_amm_pair(storage, id, &amm_pair)?;
+        }
+    }
+    Ok(())
+}
+
+/// Maintain the address and token lookups for the pair stored at `index`
+fn index_amm_pair(storage: &mut dyn Storage, index: u64, amm_pair: &AMMPair) -> StdResult<()> {
+    amm_pair_index_by_address_w(storage).save(amm_pair.address.as_bytes(), &index)?;
+
+    for token in [&amm_pair.pair.0, &amm_pair.pair.1] {
+        let unique_key = token.unique_key();
+        let token_key = unique_key.as_bytes();
+        let index_key = index.to_string();
+        if token_pair_positions_r(storage, token_key).may_load(index_key.as_bytes())?.is_some() {
+            continue;
+        }
+
+        let position = match token_pair_counts_r(storage).may_load(token_key)? {
+            Some(count) => count,
+            None => {
+                let total_tokens = total_tokens_r(storage).may_load()?.unwrap_or(0u64);
+                tokens_w(storage).save(total_tokens.to_string().as_bytes(), token)?;
+                total_tokens_w(storage).save(&(total_tokens + 1))?;
+                0
+            }
+        };
+        token_pairs_w(storage, token_key).save(position.to_string().as_bytes(), &index)?;
+        token_pair_positions_w(storage, token_key).save(index_key.as_bytes(), &position)?;
+        token_pair_counts_w(storage).save(token_key, &(position + 1))?;
+    }
+
+    Ok(())
+}
+
+/// Drop a pair id from the token lookup, moving the token's last pair into its position
+fn remove_token_pair_index(storage: &mut dyn Storage, token: &TokenType, index: u64) -> StdResult<()> {
+    let unique_key = token.unique_key();
+    let token_key = unique_key.as_bytes();
+    let index_key = index.to_string();
+    let position = match token_pair_positions_r(storage, token_key).may_load(index_key.as_bytes())? {
+        Some(position) => position,
+        None => return Ok(()),
+    };
+    token_pair_positions_w(storage, token_key).remove(index_key.as_bytes());
+
+    let last_position = token_pair_counts_r(storage).load(token_key)? - 1;
+    if position != last_position {
+        let last_index = token_pairs_r(storage, token_key).load(last_position.to_string().as_bytes())?;
+        token_pairs_w(storage, token_key).save(position.to_string().as_bytes(), &last_index)?;
+        token_pair_positions_w(storage, token_key).save(last_index.to_string().as_bytes(), &position)?;
+    }
+    token_pairs_w(storage, token_key).remove(last_position.to_string().as_bytes());
+    if last_position > 0 {
+        return token_pair_counts_w(storage).save(token_key, &last_position);
+    }
+
+    // No pair trades the token anymore, drop it from the dense token list
+    token_pair_counts_w(storage).remove(token_key);
+    let total_tokens = total_tokens_r(storage).may_load()?.unwrap_or(0u64);
+    for i in 0..total_tokens {
+        if tokens_r(storage).load(i.to_string().as_bytes())?.unique_key() == unique_key {
+            let last_index = total_tokens - 1;
+            if i != last_index {
+                let last_token = tokens_r(storage).load(last_index.to_string().as_bytes())?;
+                tokens_w(storage).save(i.to_string().as_bytes(), &last_token)?;
+            }
+            tokens_w(storage).remove(last_index.to_string().as_bytes());
+            total_tokens_w(storage).save(&last_index)?;
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Migrate the given pairs, or the next page of pairs not yet on `code_hash`, and record the
+/// new code hash in the registry
+pub fn migrate_pairs(
     storage: &mut dyn Storage,
-    api_key: Option<String>,
-    admin_auth: Option<Contract>,
+    code_id: u64,
+    code_hash: String,
+    pairs: Option<Vec<Addr>>,
+    msg: Binary,
 ) -> StdResult<Response> {
+    let ids = match pairs {
+        Some(pairs) => {
+            if pairs.len() > PAGINATION_LIMIT as usize {
+                return Err(StdError::generic_err(format!(
+                    "Can not migrate more than {} pairs at once.",
+                    PAGINATION_LIMIT
+                )));
+            }
+            let mut ids = Vec::with_capacity(pairs.len());
+            for address in pairs {
+                match amm_pair_index_by_address_r(storage).may_load(address.as_bytes())? {
+                    Some(id) => ids.push(id),
+                    None => {
+                        return Err(StdError::generic_err(format!(
+                            "Pair {} is not registered.",
+                            address
+                        )))
+                    }
+                }
+            }
+            ids
+        }
+        None => {
+            // Pairs already on the new code are skipped, so repeated calls page through the registry
+            let end = load_next_amm_pair_id(storage)?;
+            let mut ids = vec![];
+            for id in 0..end {
+                if ids.len() == PAGINATION_LIMIT as usize {
+                    break;
+                }
+                if let Some(amm_pair) = amm_pairs_r(storage).may_load(id.to_string().as_bytes())? {
+                    if amm_pair.code_hash != code_hash {
+                        ids.push(id);
+                    }
+                }
+            }
+            ids
+        }
+    };
+
+    let mut messages = Vec::with_capacity(ids.len());
+    for id in ids {
+        let mut amm_pair = amm_pairs_r(storage).load(id.to_string().as_bytes())?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: amm_pair.address.to_string(),
+            code_hash: code_hash.clone(),
+            code_id,
+            msg: msg.clone(),
+        }));
+        amm_pair.code_hash = code_hash.clone();
+        amm_pairs_w(storage).save(id.to_string().as_bytes(), &amm_pair)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate_pairs")
+        .add_attribute("migrated_pairs", messages.len().to_string())
+        .add_messages(messages))
+}
+
+pub fn set_config(storage: &mut dyn Storage, changes: ConfigChanges) -> StdResult<Response> {
+    assert_config_not_timelocked(storage)?;
+    apply_config_changes(storage, changes)?;
+
+    Ok(Response::default())
+}
+
+fn assert_config_not_timelocked(storage: &dyn Storage) -> StdResult<()> {
+    let config = config_r(storage).load()?;
+    if config.config_timelock > 0 {
+        return Err(StdError::generic_err(format!(
+            "Config changes are timelocked for {} seconds, use ProposeConfig.",
+            config.config_timelock
+        )));
+    }
+    Ok(())
+}
+
+pub fn propose_config(storage: &mut dyn Storage, env: &Env, changes: ConfigChanges) -> StdResult<Response> {
+    validate_config_changes(&changes)?;
+    let config = config_r(storage).load()?;
+
+    let id = next_config_change_id_r(storage).may_load()?.unwrap_or(0u64);
+    next_config_change_id_w(storage).save(&(id + 1))?;
+    let executable_at = env.block.time.seconds() + config.config_timelock;
+
+    let mut pending = pending_config_changes_r(storage).may_load()?.unwrap_or_default();
+    pending.push(PendingConfigChange {
+        id,
+        changes,
+        executable_at,
+    });
+    pending_config_changes_w(storage).save(&pending)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_config")
+        .add_attribute("id", id.to_string())
+        .add_attribute("executable_at", executable_at.to_string()))
+}
+
+pub fn execute_config(storage: &mut dyn Storage, env: &Env, id: u64) -> StdResult<Response> {
+    let executable_at = pending_config_changes_r(storage)
+        .may_load()?
+        .unwrap_or_default()
+        .iter()
+        .find(|change| change.id == id)
+        .map(|change| change.executable_at);
+    if let Some(executable_at) = executable_at {
+        if env.block.time.seconds() < executable_at {
+            return Err(StdError::generic_err(format!(
+                "Config change {} can not be executed before {}.",
+                id, executable_at
+            )));
+        }
+    }
+    let change = take_pending_config_change(storage, id)?;
+    apply_config_changes(storage, change.changes)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_config")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn cancel_config(storage: &mut dyn Storage, id: u64) -> StdResult<Response> {
+    take_pending_config_change(storage, id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_config")
+        .add_attribute("id", id.to_string()))
+}
+
+fn take_pending_config_change(storage: &mut dyn Storage, id: u64) -> StdResult<PendingConfigChange> {
+    let mut pending = pending_config_changes_r(storage).may_load()?.unwrap_or_default();
+    let position = pending
+        .iter()
+        .position(|change| change.id == id)
+        .ok_or_else(|| StdError::generic_err(format!("No pending config change with id {}.", id)))?;
+    let change = pending.remove(position);
+    pending_config_changes_w(storage).save(&pending)?;
+    Ok(change)
+}
+
+fn validate_config_changes(changes: &ConfigChanges) -> StdResult<()> {
+    if let Some(CreationPolicy::FeeGated { fee }) = &changes.creation_policy {
+        if fee.amount.is_zero() {
+            return Err(StdError::generic_err(
+                "Pair creation fee must be greater than zero.",
+            ));
+        }
+    }
+    for fee_override in changes.pair_fees.iter().flatten() {
+        validate_pair_fee(&fee_override.fee)?;
+    }
+    Ok(())
+}
+
+fn apply_config_changes(storage: &mut dyn Storage, changes: ConfigChanges) -> StdResult<()> {
+    validate_config_changes(&changes)?;
+
     let mut config = config_r(storage).load()?;
-    if let Some(new_value) = pair_contract {
+    if let Some(new_value) = changes.pair_contract {
         config.pair_contract = new_value;
     }
 
-    if let Some(new_value) = lp_token_contract {
+    if let Some(new_value) = changes.lp_token_contract {
         config.lp_token_contract = new_value;
     }
 
-    if let Some(new_value) = amm_settings {
+    if let Some(new_value) = changes.amm_settings {
         config.amm_settings = new_value;
     }
-    if let Some(new_value) = api_key {
+    if let Some(new_value) = changes.api_key {
         config.api_key = ViewingKey(new_value);
     }
-    if let Some(new_value) = admin_auth {
-        config.admin_auth = new_value;
+    if let Some(new_value) = changes.creation_policy {
+        config.creation_policy = new_value;
+    }
+    if let Some(new_value) = changes.config_timelock {
+        config.config_timelock = new_value;
     }
-
     config_w(storage).save(&config)?;
 
-    Ok(Response::default())
+    for fee_override in changes.pair_fees.unwrap_or_default() {
+        save_pair_fee(storage, &fee_override.pair, &fee_override.fee)?;
+    }
+    for pair in changes.cleared_pair_fees.unwrap_or_default() {
+        clear_fee_override(storage, &pair)?;
+    }
+    Ok(())
+}
+
+pub fn propose_admin_auth(storage: &mut dyn Storage, admin_auth: Contract) -> StdResult<Response> {
+    pending_admin_auth_w(storage).save(&admin_auth)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_admin_auth")
+        .add_attribute("admin_auth", admin_auth.address.to_string()))
+}
+
+// The sender must be an admin in the proposed contract, so a wrong contract can not lock admins out
+pub fn accept_admin_auth(deps: DepsMut, sender: &Addr) -> StdResult<Response> {
+    let admin_auth = pending_admin_auth_r(deps.storage)
+        .may_load()?
+        .ok_or_else(|| StdError::generic_err("No admin auth change is pending."))?;
+    validate_admin(
+        &deps.querier,
+        AdminPermissions::ShadeSwapAdmin,
+        sender,
+        &admin_auth,
+    )?;
+
+    let mut config = config_r(deps.storage).load()?;
+    config.admin_auth = admin_auth;
+    config_w(deps.storage).save(&config)?;
+    pending_admin_auth_w(deps.storage).remove();
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin_auth")
+        .add_attribute("admin_auth", config.admin_auth.address.to_string()))
 }
 
 pub fn create_pair(
@@ -91,20 +547,37 @@ pub fn create_pair(
     amm_pair_custom_label: Option<String>,
     lp_token_custom_label: Option<String>
 ) -> StdResult<Response> {
+    validate_pair_tokens(deps.as_ref(), &pair)?;
+
+    if let Some(address) = amm_pair_keys_r(deps.storage).may_load(&generate_pair_key(&pair))? {
+        return Err(StdError::generic_err(format!(
+            "Pair {} is already registered at {}.",
+            pair, address
+        )));
+    }
+
+    let nonce = pair_nonce_r(deps.storage).may_load()?.unwrap_or(0u64);
+    pair_nonce_w(deps.storage).save(&(nonce + 1))?;
+    let label = amm_pair_custom_label.unwrap_or(pair_label(&env.contract.address, nonce));
+    if let Some(address) = pair_labels_r(deps.storage).may_load(label.as_bytes())? {
+        return Err(StdError::generic_err(format!(
+            "Label {} is already used by pair {}.",
+            label, address
+        )));
+    }
+
     let config = config_r(deps.storage).load()?;
     ephemeral_storage_w(deps.storage).save(&NextPairKey {
         pair: pair.clone(),
         code_hash: config.pair_contract.code_hash.to_string(),
+        label: label.clone(),
     })?;
 
     let mut messages = vec![];
     messages.push(SubMsg::reply_on_success(
         CosmosMsg::Wasm(WasmMsg::Instantiate {
             code_id: config.pair_contract.id,
-            label: amm_pair_custom_label.unwrap_or(format!(
-                "{}-{}-pair-{}-{}",
-                pair.0, pair.1, env.contract.address, config.pair_contract.id
-            )),
+            label,
             msg: to_binary(&AMMPairInitMsg {
                 pair: pair.clone(),
                 lp_token_contract: config.lp_token_contract.clone(),
@@ -129,3 +602,84 @@ pub fn create_pair(
 
     Ok(Response::new().add_submessages(messages))
 }
+
+/// Rejects funds other than a native creation fee, the factory would otherwise keep them.
+pub fn assert_creation_funds(info: &MessageInfo, fee: Option<&TokenAmount>) -> StdResult<()> {
+    let fee_denom = match fee {
+        Some(TokenAmount {
+            token: TokenType::NativeToken { denom },
+            ..
+        }) => Some(denom),
+        _ => None,
+    };
+    if info.funds.iter().any(|coin| Some(&coin.denom) != fee_denom) {
+        return Err(StdError::generic_err(
+            "Pair creation only accepts the native creation fee as funds.",
+        ));
+    }
+    Ok(())
+}
+
+/// Charges the pair creation fee and forwards it to the DAO. Native fees must be
+/// sent with the message, SNIP20 fees are pulled with an allowance given to the factory.
+pub fn creation_fee_msgs(
+    info: &MessageInfo,
+    fee: &TokenAmount,
+    config: &Config,
+) -> StdResult<Vec<CosmosMsg>> {
+    let dao_address = config.amm_settings.shade_dao_address.address.to_string();
+    match &fee.token {
+        TokenType::NativeToken { denom } => {
+            fee.assert_sent_native_token_balance(info)?;
+            Ok(vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: dao_address,
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount: fee.amount,
+                }],
+            })])
+        }
+        TokenType::CustomToken {
+            contract_addr,
+            token_code_hash,
+        } => Ok(vec![transfer_from_msg(
+            info.sender.to_string(),
+            dao_address,
+            fee.amount,
+            None,
+            None,
+            &Contract {
+                address: contract_addr.clone(),
+                code_hash: token_code_hash.clone(),
+            },
+        )?]),
+    }
+}
+
+/// Rejects pairs of the same token and SNIP20s that do not answer a token info query.
+pub fn validate_pair_tokens(deps: Deps, pair: &TokenPair) -> StdResult<()> {
+    if pair.0.unique_key() == pair.1.unique_key() {
+        return Err(StdError::generic_err("Can not create a pair of identical tokens."));
+    }
+
+    for token in pair {
+        if let TokenType::CustomToken {
+            contract_addr,
+            token_code_hash,
+        } = token
+        {
+            token_info(
+                &deps.querier,
+                &Contract {
+                    address: contract_addr.clone(),
+                    code_hash: token_code_hash.clone(),
+                },
+            )
+            .map_err(|_| {
+                StdError::generic_err(format!("{} is not a valid SNIP20 token.", contract_addr))
+            })?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,32 +1,50 @@
-use cosmwasm_std::{Deps, StdResult, to_binary, Binary};
-use shadeswap_shared::{Pagination, amm_pair::{AMMPair, generate_pair_key}, core::TokenPair, factory::QueryResponse};
+use cosmwasm_std::{Deps, Env, StdError, StdResult, Storage, to_binary, Binary, QueryRequest, WasmQuery};
+use shadeswap_shared::{
+    PageOrder, Pagination,
+    amm_pair::{AMMPair, generate_pair_key, QueryMsg as AMMPairQueryMsg, QueryMsgResponse as AMMPairQueryResponse},
+    core::{TokenPair, TokenType},
+    factory::{AMMPairInfo, AMMPairWithInfo, QueryResponse},
+};
 
-use crate::state::{total_amm_pairs_r, PAGINATION_LIMIT, amm_pairs_r, amm_pair_keys_r};
+use crate::state::{
+    total_amm_pairs_r, PAGINATION_LIMIT, amm_pairs_r, amm_pair_keys_r, amm_pair_index_by_address_r,
+    token_pair_counts_r, token_pairs_r, tokens_r, total_tokens_r, config_r, fee_override_index_r, fee_overrides_r,
+    total_fee_overrides_r, pair_label, pair_labels_r, pair_nonce_r, pending_admin_auth_r,
+    pending_config_changes_r, load_next_amm_pair_id,
+};
 
-pub fn amm_pairs_page(deps: Deps, pagination: Pagination) -> StdResult<Vec<AMMPair>> {
-    let count = total_amm_pairs_r(deps.storage).may_load()?;
-
-    match count {
-        Some(c) => {
-            if pagination.start >= c {
-                return Ok(vec![]);
-            }
-
-            let limit = pagination.limit.min(PAGINATION_LIMIT);
-            let end = (pagination.start + limit as u64).min(c);
-
-            let mut result = Vec::with_capacity((end - pagination.start) as usize);
+// Bounds the ids one page looks at when many pairs were removed
+const MAX_SCANNED_PAIR_IDS: usize = PAGINATION_LIMIT as usize * 10;
 
-            for i in pagination.start..end {
-                let exchange: AMMPair = amm_pairs_r(deps.storage).load(i.to_string().as_bytes())?;
+/// Pairs with an id from `pagination.start` on, the ids of removed pairs are skipped
+pub fn amm_pairs_page(deps: Deps, pagination: Pagination) -> StdResult<Vec<AMMPair>> {
+    let end = load_next_amm_pair_id(deps.storage)?;
+    let limit = pagination.limit.min(PAGINATION_LIMIT) as usize;
+    let (amm_pairs, _) = scan_amm_pairs(deps.storage, pagination.start..end, limit)?;
 
-                result.push(exchange);
-            }
+    Ok(amm_pairs)
+}
 
-            Ok(result)
+/// Loads up to `limit` pairs from `ids`, skipping the ids of removed pairs.
+/// Returns the last id looked at when ids were left unscanned.
+fn scan_amm_pairs(
+    storage: &dyn Storage,
+    ids: impl Iterator<Item = u64>,
+    limit: usize,
+) -> StdResult<(Vec<AMMPair>, Option<u64>)> {
+    let mut amm_pairs = vec![];
+    let mut last = None;
+    for (scanned, id) in ids.enumerate() {
+        if amm_pairs.len() == limit || scanned == MAX_SCANNED_PAIR_IDS {
+            return Ok((amm_pairs, last));
         }
-        None => Ok(vec![]),
+        if let Some(amm_pair) = amm_pairs_r(storage).may_load(id.to_string().as_bytes())? {
+            amm_pairs.push(amm_pair);
+        }
+        last = Some(id);
     }
+
+    Ok((amm_pairs, None))
 }
 
 pub fn amm_pair_address(deps: &Deps, pair: TokenPair) -> StdResult<Binary> {
@@ -36,10 +54,204 @@ pub fn amm_pair_address(deps: &Deps, pair: TokenPair) -> StdResult<Binary> {
     })
 }
 
-pub fn pairs_page(deps: Deps, pagination: Pagination) -> StdResult<Binary> {
-    let amm_pairs = amm_pairs_page(deps, pagination)?;
+pub fn pairs_page(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u8>,
+    order: Option<PageOrder>,
+) -> StdResult<Binary> {
+    let end = load_next_amm_pair_id(deps.storage)?;
+    let limit = limit.unwrap_or(PAGINATION_LIMIT).clamp(1, PAGINATION_LIMIT) as usize;
+    let (amm_pairs, next) = match order.unwrap_or(PageOrder::Ascending) {
+        PageOrder::Ascending => {
+            let start = start_after.map_or(0, |id| id.saturating_add(1));
+            scan_amm_pairs(deps.storage, start..end, limit)?
+        }
+        PageOrder::Descending => {
+            let stop = start_after.map_or(end, |id| id.min(end));
+            scan_amm_pairs(deps.storage, (0..stop).rev(), limit)?
+        }
+    };
+
+    to_binary(&QueryResponse::ListAMMPairs { amm_pairs, next })
+}
+
+pub fn pair_count(deps: Deps) -> StdResult<Binary> {
+    let count = total_amm_pairs_r(deps.storage).may_load()?.unwrap_or(0u64);
+    to_binary(&QueryResponse::GetPairCount { count })
+}
+
+pub fn pairs_for_token(deps: Deps, token: TokenType, pagination: Pagination) -> StdResult<Binary> {
+    let token_key = token.unique_key();
+    let count = token_pair_counts_r(deps.storage)
+        .may_load(token_key.as_bytes())?
+        .unwrap_or(0u64);
+
+    let limit = pagination.limit.min(PAGINATION_LIMIT);
+    let end = pagination.start.saturating_add(limit as u64).min(count);
+
+    let mut amm_pairs = vec![];
+    for position in pagination.start..end {
+        let i = token_pairs_r(deps.storage, token_key.as_bytes()).load(position.to_string().as_bytes())?;
+        amm_pairs.push(amm_pairs_r(deps.storage).load(i.to_string().as_bytes())?);
+    }
+
+    to_binary(&QueryResponse::ListPairsForToken { amm_pairs })
+}
+
+pub fn tokens_page(deps: Deps, pagination: Pagination) -> StdResult<Binary> {
+    let count = total_tokens_r(deps.storage).may_load()?.unwrap_or(0u64);
+
+    let limit = pagination.limit.min(PAGINATION_LIMIT);
+    let end = pagination.start.saturating_add(limit as u64).min(count);
+
+    let mut tokens = vec![];
+    for i in pagination.start..end {
+        tokens.push(tokens_r(deps.storage).load(i.to_string().as_bytes())?);
+    }
+
+    to_binary(&QueryResponse::ListTokens { tokens })
+}
+
+pub fn pairs_by_address(deps: Deps, addresses: Vec<String>) -> StdResult<Binary> {
+    if addresses.len() > PAGINATION_LIMIT as usize {
+        return Err(StdError::generic_err(format!(
+            "Can not look up more than {} addresses at once.",
+            PAGINATION_LIMIT
+        )));
+    }
+
+    let mut amm_pairs = vec![];
+    for address in addresses {
+        let address = deps.api.addr_validate(&address)?;
+        if let Some(i) = amm_pair_index_by_address_r(deps.storage).may_load(address.as_bytes())? {
+            amm_pairs.push(amm_pairs_r(deps.storage).load(i.to_string().as_bytes())?);
+        }
+    }
+
+    to_binary(&QueryResponse::GetPairsByAddress { amm_pairs })
+}
+
+pub fn pair_settings(deps: Deps, pair: TokenPair) -> StdResult<Binary> {
+    let config = config_r(deps.storage).load()?;
+    let mut amm_settings = config.amm_settings;
+
+    let fee_override = match fee_override_index_r(deps.storage).may_load(&generate_pair_key(&pair))? {
+        Some(i) => Some(fee_overrides_r(deps.storage).load(i.to_string().as_bytes())?.fee),
+        None => None,
+    };
+    if let Some(fee) = &fee_override {
+        amm_settings.lp_fee = fee.lp_fee;
+        amm_settings.shade_dao_fee = fee.shade_dao_fee;
+    }
+
+    to_binary(&QueryResponse::GetPairSettings {
+        amm_settings,
+        authenticator: config.authenticator,
+        admin_auth: config.admin_auth,
+        fee_override,
+    })
+}
+
+pub fn fee_overrides_page(deps: Deps, pagination: Pagination) -> StdResult<Binary> {
+    let count = total_fee_overrides_r(deps.storage).may_load()?.unwrap_or(0u64);
+
+    let limit = pagination.limit.min(PAGINATION_LIMIT);
+    let end = pagination.start.saturating_add(limit as u64).min(count);
+
+    let mut overrides = vec![];
+    for i in pagination.start..end {
+        overrides.push(fee_overrides_r(deps.storage).load(i.to_string().as_bytes())?);
+    }
+
+    to_binary(&QueryResponse::ListFeeOverrides { overrides })
+}
+
+pub fn pairs_with_info_page(deps: Deps, pagination: Pagination) -> StdResult<Binary> {
+    let amm_pairs = amm_pairs_page(deps, pagination)?
+        .into_iter()
+        .map(|amm_pair| AMMPairWithInfo {
+            // A broken pair should not fail the whole page
+            info: amm_pair_info(deps, &amm_pair).ok(),
+            amm_pair,
+        })
+        .collect();
+
+    to_binary(&QueryResponse::ListAMMPairsWithInfo { amm_pairs })
+}
+
+fn amm_pair_info(deps: Deps, amm_pair: &AMMPair) -> StdResult<AMMPairInfo> {
+    let query_pair = |msg: &AMMPairQueryMsg| -> StdResult<AMMPairQueryResponse> {
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: amm_pair.address.to_string(),
+            code_hash: amm_pair.code_hash.clone(),
+            msg: to_binary(msg)?,
+        }))
+    };
+
+    match (
+        query_pair(&AMMPairQueryMsg::GetPairInfo {})?,
+        query_pair(&AMMPairQueryMsg::GetConfig {})?,
+    ) {
+        (
+            AMMPairQueryResponse::GetPairInfo {
+                liquidity_token,
+                amount_0,
+                amount_1,
+                total_liquidity,
+                contract_version,
+                fee_info,
+                ..
+            },
+            AMMPairQueryResponse::GetConfig {
+                staking_contract,
+                custom_fee,
+                ..
+            },
+        ) => Ok(AMMPairInfo {
+            liquidity_token,
+            amount_0,
+            amount_1,
+            total_liquidity,
+            contract_version,
+            fee_info,
+            staking_contract,
+            custom_fee,
+        }),
+        _ => Err(StdError::generic_err(format!(
+            "Unexpected query response from pair {}.",
+            amm_pair.address
+        ))),
+    }
+}
+
+pub fn pair_by_label(deps: Deps, label: String) -> StdResult<Binary> {
+    let address = pair_labels_r(deps.storage)
+        .may_load(label.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("No pair was created with label {}.", label)))?;
+    let index = amm_pair_index_by_address_r(deps.storage)
+        .may_load(address.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Pair {} is not registered.", address)))?;
+
+    to_binary(&QueryResponse::GetPairByLabel {
+        amm_pair: amm_pairs_r(deps.storage).load(index.to_string().as_bytes())?,
+    })
+}
+
+pub fn next_pair_label(deps: Deps, env: Env) -> StdResult<Binary> {
+    let nonce = pair_nonce_r(deps.storage).may_load()?.unwrap_or(0u64);
 
-    to_binary(&QueryResponse::ListAMMPairs { amm_pairs })
+    to_binary(&QueryResponse::GetNextPairLabel {
+        label: pair_label(&env.contract.address, nonce),
+    })
 }
 
+pub fn pending_config_changes(deps: Deps) -> StdResult<Binary> {
+    let config = config_r(deps.storage).load()?;
 
+    to_binary(&QueryResponse::PendingConfigChanges {
+        admin_auth: pending_admin_auth_r(deps.storage).may_load()?,
+        config_timelock: config.config_timelock,
+        changes: pending_config_changes_r(deps.storage).may_load()?.unwrap_or_default(),
+    })
+}
@@ -1,18 +1,33 @@
 use cosmwasm_std::{
-    Binary, Storage, Addr,
+    Binary, Storage, Addr, StdResult,
 };
 use cosmwasm_storage::{singleton, singleton_read, Singleton, ReadonlySingleton, ReadonlyBucket, bucket_read, bucket, Bucket};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use shadeswap_shared::{
     amm_pair::{AMMPair, AMMSettings},
-    core::{ContractInstantiationInfo, TokenPair, ViewingKey},
-    msg::factory::InitMsg, Contract
+    core::{ContractInstantiationInfo, TokenPair, TokenType, ViewingKey},
+    msg::factory::{CreationPolicy, InitMsg, PairFeeOverride, PendingConfigChange}, Contract
 };
 
 const AMM_PAIRS_KEYS: &[u8] = b"amm_pair_keys";
 const AMM_PAIRS: &[u8] = b"amm_pairs";
 const TOTAL_AMM_PAIR: &[u8] = b"total_amm_pairs";
+const NEXT_AMM_PAIR_ID: &[u8] = b"next_amm_pair_id";
+const AMM_PAIR_INDEX_BY_ADDRESS: &[u8] = b"amm_pair_index_by_address";
+const TOKEN_PAIR_COUNTS: &[u8] = b"token_pair_counts";
+const TOKEN_PAIRS: &[u8] = b"token_pairs";
+const TOKEN_PAIR_POSITIONS: &[u8] = b"token_pair_positions";
+const TOKENS: &[u8] = b"tokens";
+const TOTAL_TOKENS: &[u8] = b"total_tokens";
+const FEE_OVERRIDES: &[u8] = b"fee_overrides";
+const FEE_OVERRIDE_INDEX: &[u8] = b"fee_override_index";
+const TOTAL_FEE_OVERRIDES: &[u8] = b"total_fee_overrides";
+const PAIR_NONCE: &[u8] = b"pair_nonce";
+const PAIR_LABELS: &[u8] = b"pair_labels";
+const PENDING_ADMIN_AUTH: &[u8] = b"pending_admin_auth";
+const PENDING_CONFIG_CHANGES: &[u8] = b"pending_config_changes";
+const NEXT_CONFIG_CHANGE_ID: &[u8] = b"next_config_change_id";
 const PRNG_KEY: &[u8] = b"prng_seed";
 pub static CONFIG: &[u8] = b"config";
 pub const EPHEMERAL_STORAGE_KEY: &[u8] = b"ephemeral_storage";
@@ -25,7 +40,14 @@ pub struct Config {
     pub lp_token_contract: ContractInstantiationInfo,
     pub api_key: ViewingKey,
     pub authenticator: Option<Contract>,
-    pub admin_auth: Contract
+    pub admin_auth: Contract,
+    // Configs stored before creation policies existed allowed anyone to create pairs
+    #[serde(default)]
+    pub creation_policy: CreationPolicy,
+    // Seconds between proposing and executing config changes, 0 applies SetConfig instantly.
+    // Configs stored before the timelock existed load without one.
+    #[serde(default)]
+    pub config_timelock: u64
 }
 
 impl Config {
@@ -36,7 +58,9 @@ impl Config {
             lp_token_contract: msg.lp_token_contract,
             api_key: ViewingKey(msg.api_key),
             authenticator: msg.authenticator,
-            admin_auth: msg.admin_auth
+            admin_auth: msg.admin_auth,
+            creation_policy: msg.creation_policy.unwrap_or_default(),
+            config_timelock: 0
         }
     }
 }
@@ -44,7 +68,12 @@ impl Config {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct NextPairKey {
     pub pair: TokenPair,
-    pub code_hash: String
+    pub code_hash: String,
+    pub label: String
+}
+
+pub fn pair_label(factory: &Addr, nonce: u64) -> String {
+    format!("shadeswap-pair-{}-{}", factory, nonce)
 }
 
 pub fn config_w(storage: &mut dyn Storage) -> Singleton<Config> {
@@ -94,3 +123,139 @@ pub fn total_amm_pairs_w(storage: &mut dyn Storage) -> Singleton<u64> {
 pub fn total_amm_pairs_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
     singleton_read(storage, TOTAL_AMM_PAIR)
 }
+
+// Key of the next pair in AMM_PAIRS, ids of removed pairs are never reused
+pub fn next_amm_pair_id_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, NEXT_AMM_PAIR_ID)
+}
+
+pub fn next_amm_pair_id_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, NEXT_AMM_PAIR_ID)
+}
+
+/// Registries written before pairs could be removed hold the ids 0..total_amm_pairs
+pub fn load_next_amm_pair_id(storage: &dyn Storage) -> StdResult<u64> {
+    match next_amm_pair_id_r(storage).may_load()? {
+        Some(id) => Ok(id),
+        None => Ok(total_amm_pairs_r(storage).may_load()?.unwrap_or(0u64)),
+    }
+}
+
+// Id of the pair in AMM_PAIRS keyed by the pair contract address
+pub fn amm_pair_index_by_address_w(storage: &mut dyn Storage) -> Bucket<u64> {
+    bucket(storage, AMM_PAIR_INDEX_BY_ADDRESS)
+}
+
+pub fn amm_pair_index_by_address_r(storage: &dyn Storage) -> ReadonlyBucket<u64> {
+    bucket_read(storage, AMM_PAIR_INDEX_BY_ADDRESS)
+}
+
+// Number of registered pairs containing a token, keyed by TokenType::unique_key()
+pub fn token_pair_counts_w(storage: &mut dyn Storage) -> Bucket<u64> {
+    bucket(storage, TOKEN_PAIR_COUNTS)
+}
+
+pub fn token_pair_counts_r(storage: &dyn Storage) -> ReadonlyBucket<u64> {
+    bucket_read(storage, TOKEN_PAIR_COUNTS)
+}
+
+// Ids in AMM_PAIRS of the pairs containing a token, keyed by position under the token key
+pub fn token_pairs_w<'a>(storage: &'a mut dyn Storage, token_key: &[u8]) -> Bucket<'a, u64> {
+    Bucket::multilevel(storage, &[TOKEN_PAIRS, token_key])
+}
+
+pub fn token_pairs_r<'a>(storage: &'a dyn Storage, token_key: &[u8]) -> ReadonlyBucket<'a, u64> {
+    ReadonlyBucket::multilevel(storage, &[TOKEN_PAIRS, token_key])
+}
+
+// Position in TOKEN_PAIRS under the token key, keyed by pair id
+pub fn token_pair_positions_w<'a>(storage: &'a mut dyn Storage, token_key: &[u8]) -> Bucket<'a, u64> {
+    Bucket::multilevel(storage, &[TOKEN_PAIR_POSITIONS, token_key])
+}
+
+pub fn token_pair_positions_r<'a>(storage: &'a dyn Storage, token_key: &[u8]) -> ReadonlyBucket<'a, u64> {
+    ReadonlyBucket::multilevel(storage, &[TOKEN_PAIR_POSITIONS, token_key])
+}
+
+pub fn tokens_w(storage: &mut dyn Storage) -> Bucket<TokenType> {
+    bucket(storage, TOKENS)
+}
+
+pub fn tokens_r(storage: &dyn Storage) -> ReadonlyBucket<TokenType> {
+    bucket_read(storage, TOKENS)
+}
+
+pub fn total_tokens_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, TOTAL_TOKENS)
+}
+
+pub fn total_tokens_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, TOTAL_TOKENS)
+}
+
+pub fn fee_overrides_w(storage: &mut dyn Storage) -> Bucket<PairFeeOverride> {
+    bucket(storage, FEE_OVERRIDES)
+}
+
+pub fn fee_overrides_r(storage: &dyn Storage) -> ReadonlyBucket<PairFeeOverride> {
+    bucket_read(storage, FEE_OVERRIDES)
+}
+
+// Index of the override in FEE_OVERRIDES keyed by generate_pair_key()
+pub fn fee_override_index_w(storage: &mut dyn Storage) -> Bucket<u64> {
+    bucket(storage, FEE_OVERRIDE_INDEX)
+}
+
+pub fn fee_override_index_r(storage: &dyn Storage) -> ReadonlyBucket<u64> {
+    bucket_read(storage, FEE_OVERRIDE_INDEX)
+}
+
+pub fn total_fee_overrides_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, TOTAL_FEE_OVERRIDES)
+}
+
+pub fn total_fee_overrides_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, TOTAL_FEE_OVERRIDES)
+}
+
+// Number of pairs instantiated by the factory, used in the default pair label
+pub fn pair_nonce_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, PAIR_NONCE)
+}
+
+pub fn pair_nonce_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, PAIR_NONCE)
+}
+
+// Address of the pair instantiated with a label
+pub fn pair_labels_w(storage: &mut dyn Storage) -> Bucket<Addr> {
+    bucket(storage, PAIR_LABELS)
+}
+
+pub fn pair_labels_r(storage: &dyn Storage) -> ReadonlyBucket<Addr> {
+    bucket_read(storage, PAIR_LABELS)
+}
+
+pub fn pending_admin_auth_w(storage: &mut dyn Storage) -> Singleton<Contract> {
+    singleton(storage, PENDING_ADMIN_AUTH)
+}
+
+pub fn pending_admin_auth_r(storage: &dyn Storage) -> ReadonlySingleton<Contract> {
+    singleton_read(storage, PENDING_ADMIN_AUTH)
+}
+
+pub fn pending_config_changes_w(storage: &mut dyn Storage) -> Singleton<Vec<PendingConfigChange>> {
+    singleton(storage, PENDING_CONFIG_CHANGES)
+}
+
+pub fn pending_config_changes_r(storage: &dyn Storage) -> ReadonlySingleton<Vec<PendingConfigChange>> {
+    singleton_read(storage, PENDING_CONFIG_CHANGES)
+}
+
+pub fn next_config_change_id_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, NEXT_CONFIG_CHANGE_ID)
+}
+
+pub fn next_config_change_id_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, NEXT_CONFIG_CHANGE_ID)
+}
@@ -1,18 +1,21 @@
 use cosmwasm_std::{
-    from_slice,
+    from_binary, from_slice,
     testing::{mock_env, MockApi, MockStorage},
     to_binary, Addr, Coin, Empty, OwnedDeps, Querier, QuerierResult, QueryRequest, StdResult,
-    WasmQuery,
+    Uint128, WasmQuery,
 };
 
 use crate::state::Config;
 use serde::{Deserialize, Serialize};
 use shadeswap_shared::{
-    amm_pair::AMMSettings,
-    contract_interfaces::admin::ValidateAdminPermissionResponse,
-    core::{ContractInstantiationInfo, Fee},
+    amm_pair::{
+        AMMSettings, FeeInfo, QueryMsg as AMMPairQueryMsg,
+        QueryMsgResponse as AMMPairQueryResponse,
+    },
+    contract_interfaces::admin::{QueryMsg as AdminQueryMsg, ValidateAdminPermissionResponse},
+    core::{ContractInstantiationInfo, Fee, TokenPair, TokenType},
     msg::factory::{InitMsg, QueryResponse},
-    snip20::manager::Balance,
+    snip20::{manager::Balance, QueryAnswer},
     utils::asset::Contract,
     Pagination,
 };
@@ -22,20 +25,41 @@ pub mod test_contract {
     use super::*;
     use crate::contract::execute;
     use crate::contract::instantiate;
+    use crate::contract::migrate;
     use crate::contract::query;
+    use crate::contract::reply;
+    use crate::contract::INSTANTIATE_REPLY_ID;
     use crate::operations::create_pair;
     use crate::state::config_r;
-    use crate::state::PAGINATION_LIMIT;
-    use cosmwasm_std::from_binary;
+    use crate::state::{amm_pair_keys_w, amm_pairs_w, total_amm_pairs_w};
+    use crate::state::{CONFIG, PAGINATION_LIMIT};
+    use cosmwasm_storage::singleton;
+    use shadeswap_shared::core::ViewingKey;
     use cosmwasm_std::Addr;
+    use cosmwasm_std::BankMsg;
+    use cosmwasm_std::Binary;
+    use cosmwasm_std::CosmosMsg;
     use cosmwasm_std::MessageInfo;
+    use cosmwasm_std::Reply;
+    use cosmwasm_std::StdError;
+    use cosmwasm_std::SubMsgResponse;
+    use cosmwasm_std::SubMsgResult;
+    use cosmwasm_std::WasmMsg;
 
     use shadeswap_shared::amm_pair::AMMPair;
-    use shadeswap_shared::core::TokenPair;
-    use shadeswap_shared::core::TokenType;
+    use shadeswap_shared::amm_pair::ExecuteMsg as AMMPairExecuteMsg;
+    use shadeswap_shared::amm_pair::generate_pair_key;
+    use shadeswap_shared::core::CustomFee;
+    use shadeswap_shared::core::TokenAmount;
+    use shadeswap_shared::msg::factory::ConfigChanges;
+    use shadeswap_shared::msg::factory::CreationPolicy;
     use shadeswap_shared::msg::factory::ExecuteMsg;
+    use shadeswap_shared::msg::factory::MigrateMsg;
+    use shadeswap_shared::msg::factory::PairFeeOverride;
     use shadeswap_shared::msg::factory::QueryMsg;
-    pub use shadeswap_shared::{msg::factory::QueryResponse, Pagination};
+    use shadeswap_shared::msg::staking::StakingContractInit;
+    use shadeswap_shared::utils::migrate::set_contract_version;
+    pub use shadeswap_shared::{msg::factory::QueryResponse, PageOrder, Pagination};
 
     #[test]
     fn init_ok() -> StdResult<()> {
@@ -84,6 +108,8 @@ pub mod test_contract {
                 lp_token_contract: Some(new_config.lp_token_contract.clone()),
                 api_key: Some("api_key".to_string()),
                 admin_auth: None,
+                creation_policy: None,
+                config_timelock: None,
             },
         )
         .unwrap();
@@ -95,6 +121,110 @@ pub mod test_contract {
         Ok(())
     }
 
+    #[test]
+    fn timelocked_config_and_admin_handover_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let mut env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&mkconfig(0)))?;
+
+        let set_config = |changes: ConfigChanges| ExecuteMsg::SetConfig {
+            pair_contract: changes.pair_contract,
+            lp_token_contract: changes.lp_token_contract,
+            amm_settings: changes.amm_settings,
+            api_key: changes.api_key,
+            admin_auth: None,
+            creation_policy: changes.creation_policy,
+            config_timelock: changes.config_timelock,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            set_config(ConfigChanges { config_timelock: Some(100), ..Default::default() }),
+        )?;
+
+        let amm_pair = AMMPair {
+            pair: TokenPair(
+                TokenType::NativeToken { denom: "uscrt".to_string() },
+                TokenType::CustomToken {
+                    contract_addr: Addr::unchecked("token_addr"),
+                    token_code_hash: "token_hash".to_string(),
+                },
+            ),
+            address: Addr::unchecked("pair_addr"),
+            enabled: true,
+            code_hash: "".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::AddAMMPairs { amm_pairs: vec![amm_pair.clone()] })?;
+        let fee_override = PairFeeOverride {
+            pair: amm_pair.pair.clone(),
+            fee: CustomFee {
+                lp_fee: Fee::new(10, 10000),
+                shade_dao_fee: Fee::new(1, 10000),
+            },
+        };
+
+        let new_config = mkconfig(5);
+        let changes = ConfigChanges {
+            pair_contract: Some(new_config.pair_contract.clone()),
+            pair_fees: Some(vec![fee_override.clone()]),
+            ..Default::default()
+        };
+        assert!(execute(deps.as_mut(), env.clone(), admin.clone(), set_config(changes.clone())).is_err());
+        assert!(execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::SetPairFee { pair: fee_override.pair.clone(), fee: fee_override.fee.clone() },
+        )
+        .is_err());
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::ProposeConfig { changes: changes.clone() })?;
+
+        let early = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::ExecuteConfig { id: 0 });
+        assert!(early.is_err());
+        env.block.time = env.block.time.plus_seconds(100);
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::ExecuteConfig { id: 0 })?;
+        assert_eq!(config_r(deps.as_ref().storage).load()?.pair_contract, new_config.pair_contract);
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ListFeeOverrides { pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListFeeOverrides { overrides: vec![fee_override] });
+
+        // The admin auth only changes once an admin of the new contract accepts
+        let admin_auth = Contract {
+            address: Addr::unchecked("admin_2"),
+            code_hash: "".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::ProposeAdminAuth { admin_auth: admin_auth.clone() },
+        )?;
+        let response: QueryResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingConfigChanges {})?)?;
+        assert_eq!(response, QueryResponse::PendingConfigChanges {
+            admin_auth: Some(admin_auth.clone()),
+            config_timelock: 100,
+            changes: vec![],
+        });
+
+        let user = MessageInfo {
+            sender: Addr::unchecked("user"),
+            funds: vec![],
+        };
+        assert!(execute(deps.as_mut(), env.clone(), user, ExecuteMsg::AcceptAdminAuth {}).is_err());
+        execute(deps.as_mut(), env, admin, ExecuteMsg::AcceptAdminAuth {})?;
+        assert_eq!(config_r(deps.as_ref().storage).load()?.admin_auth, admin_auth);
+        Ok(())
+    }
+
     #[test]
     fn create_pair_ok() -> StdResult<()> {
         let ref mut deps = mock_dependencies(&[]);
@@ -117,7 +247,7 @@ pub mod test_contract {
                 token_code_hash: "diff".to_string(),
             },
             TokenType::CustomToken {
-                contract_addr: Addr::unchecked("token_addr"),
+                contract_addr: Addr::unchecked("token_addr_1"),
                 token_code_hash: "13123adasd".to_string(),
             },
         );
@@ -136,6 +266,355 @@ pub mod test_contract {
         assert!(result.is_ok());
         Ok(())
     }
+    #[test]
+    fn open_create_pair_by_anyone_without_funds_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+        let config = mkconfig(0);
+        assert_eq!(config.creation_policy, CreationPolicy::Open);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("admin"),
+                funds: vec![],
+            },
+            create_init_msg_from_config(&config),
+        )?;
+
+        let create_msg = ExecuteMsg::CreateAMMPair {
+            pair: TokenPair(
+                TokenType::NativeToken {
+                    denom: "uscrt".to_string(),
+                },
+                TokenType::CustomToken {
+                    contract_addr: Addr::unchecked("token_addr"),
+                    token_code_hash: "diff".to_string(),
+                },
+            ),
+            entropy: to_binary(&"entropy").unwrap(),
+            staking_contract: None,
+            lp_token_decimals: 18u8,
+            amm_pair_custom_label: None,
+            lp_token_custom_label: None,
+        };
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![Coin::new(100, "uscrt")],
+            },
+            create_msg.clone(),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("Pair creation only accepts the native creation fee as funds.")
+        );
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![],
+            },
+            create_msg,
+        )?;
+        assert_eq!(result.messages.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn fee_gated_create_pair_forwards_fee_to_dao_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&mkconfig(0)))?;
+
+        let fee = TokenAmount {
+            token: TokenType::NativeToken {
+                denom: "uscrt".to_string(),
+            },
+            amount: Uint128::new(100),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            ExecuteMsg::SetConfig {
+                pair_contract: None,
+                amm_settings: None,
+                lp_token_contract: None,
+                api_key: None,
+                admin_auth: None,
+                creation_policy: Some(CreationPolicy::FeeGated { fee: fee.clone() }),
+                config_timelock: None,
+            },
+        )?;
+
+        let create_msg = |pair: TokenPair| ExecuteMsg::CreateAMMPair {
+            pair,
+            entropy: to_binary(&"entropy").unwrap(),
+            staking_contract: None,
+            lp_token_decimals: 18u8,
+            amm_pair_custom_label: None,
+            lp_token_custom_label: None,
+        };
+        let pair = TokenPair(
+            fee.token.clone(),
+            TokenType::CustomToken {
+                contract_addr: Addr::unchecked("token_addr"),
+                token_code_hash: "diff".to_string(),
+            },
+        );
+
+        let unpaid = execute(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![],
+            },
+            create_msg(pair.clone()),
+        );
+        assert!(unpaid.is_err());
+
+        let identical = execute(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![Coin::new(100, "uscrt")],
+            },
+            create_msg(TokenPair(pair.1.clone(), pair.1.clone())),
+        );
+        assert_eq!(
+            identical.unwrap_err(),
+            StdError::generic_err("Can not create a pair of identical tokens.")
+        );
+
+        // Funds other than the fee would be kept by the factory
+        let overpaid = execute(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![Coin::new(100, "uscrt"), Coin::new(5, "uatom")],
+            },
+            create_msg(pair.clone()),
+        );
+        let funds_err = StdError::generic_err("Pair creation only accepts the native creation fee as funds.");
+        assert_eq!(overpaid.unwrap_err(), funds_err);
+        let admin_paid = execute(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked("admin"),
+                funds: vec![Coin::new(100, "uscrt")],
+            },
+            create_msg(pair.clone()),
+        );
+        assert_eq!(admin_paid.unwrap_err(), funds_err);
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            MessageInfo {
+                sender: Addr::unchecked("user"),
+                funds: vec![Coin::new(100, "uscrt")],
+            },
+            create_msg(pair),
+        )?;
+        assert_eq!(result.messages.len(), 2);
+        assert_eq!(
+            result.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "CALLBACKADDR".to_string(),
+                amount: vec![Coin::new(100, "uscrt")],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn create_staking_for_pair_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&mkconfig(0)))?;
+
+        let amm_pair = AMMPair {
+            pair: TokenPair(
+                TokenType::NativeToken { denom: "uscrt".to_string() },
+                TokenType::CustomToken {
+                    contract_addr: Addr::unchecked("token_addr"),
+                    token_code_hash: "token_hash".to_string(),
+                },
+            ),
+            address: Addr::unchecked("pair_addr"),
+            enabled: true,
+            code_hash: "pair_hash".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::AddAMMPairs { amm_pairs: vec![amm_pair] })?;
+
+        let staking_init = StakingContractInit {
+            contract_info: ContractInstantiationInfo {
+                code_hash: "staking_hash".to_string(),
+                id: 3,
+            },
+            reward_token: TokenType::NativeToken { denom: "uscrt".to_string() },
+            custom_label: None,
+        };
+        let unknown = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::CreateStakingForPair {
+                pair: Addr::unchecked("unknown"),
+                staking_init: staking_init.clone(),
+            },
+        );
+        assert!(unknown.is_err());
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            admin,
+            ExecuteMsg::CreateStakingForPair {
+                pair: Addr::unchecked("pair_addr"),
+                staking_init: staking_init.clone(),
+            },
+        )?;
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair_addr".to_string(),
+                code_hash: "pair_hash".to_string(),
+                msg: to_binary(&AMMPairExecuteMsg::CreateStakingContract { staking_init })?,
+                funds: vec![],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn list_amm_pairs_with_info_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&mkconfig(0)))?;
+
+        let amm_pairs: Vec<AMMPair> = ["live_pair", "broken_pair"]
+            .iter()
+            .enumerate()
+            .map(|(i, address)| AMMPair {
+                pair: TokenPair(
+                    TokenType::NativeToken { denom: "uscrt".to_string() },
+                    TokenType::NativeToken { denom: format!("denom_{}", i) },
+                ),
+                address: Addr::unchecked(*address),
+                enabled: true,
+                code_hash: "".to_string(),
+            })
+            .collect();
+        execute(deps.as_mut(), env, admin, ExecuteMsg::AddAMMPairs { amm_pairs: amm_pairs.clone() })?;
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListAMMPairsWithInfo { pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        match response {
+            QueryResponse::ListAMMPairsWithInfo { amm_pairs: with_info } => {
+                assert_eq!(with_info.len(), 2);
+                assert_eq!(with_info[0].amm_pair, amm_pairs[0]);
+                let info = with_info[0].info.clone().unwrap();
+                assert_eq!(info.amount_1, Uint128::new(200));
+                assert_eq!(info.total_liquidity, Uint128::new(150));
+                assert_eq!(info.staking_contract.unwrap().address, Addr::unchecked("staking"));
+                assert_eq!(with_info[1].amm_pair, amm_pairs[1]);
+                assert_eq!(with_info[1].info, None);
+            }
+            _ => panic!("Query Response does not match"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn create_pair_registers_label_and_rejects_duplicates_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&mkconfig(0)))?;
+
+        let response: QueryResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetNextPairLabel {})?)?;
+        let label = format!("shadeswap-pair-{}-0", env.contract.address);
+        assert_eq!(response, QueryResponse::GetNextPairLabel { label: label.clone() });
+
+        let pair = TokenPair(
+            TokenType::NativeToken { denom: "uscrt".to_string() },
+            TokenType::CustomToken {
+                contract_addr: Addr::unchecked("token_addr"),
+                token_code_hash: "token_hash".to_string(),
+            },
+        );
+        let create_msg = ExecuteMsg::CreateAMMPair {
+            pair: pair.clone(),
+            entropy: to_binary(&"entropy").unwrap(),
+            staking_contract: None,
+            lp_token_decimals: 18u8,
+            amm_pair_custom_label: None,
+            lp_token_custom_label: None,
+        };
+        let result = execute(deps.as_mut(), env.clone(), admin.clone(), create_msg.clone())?;
+        match &result.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { label: pair_label, .. }) => assert_eq!(pair_label, &label),
+            _ => panic!("Expected the pair instantiation"),
+        }
+        reply(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: INSTANTIATE_REPLY_ID,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: Some(Binary::from("pair_addr".as_bytes())),
+                }),
+            },
+        )?;
+
+        let response: QueryResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetPairByLabel { label })?)?;
+        match response {
+            QueryResponse::GetPairByLabel { amm_pair } => {
+                assert_eq!(amm_pair.address, Addr::unchecked("pair_addr"));
+                assert_eq!(amm_pair.pair, pair);
+            }
+            _ => panic!("Query Response does not match"),
+        }
+
+        let duplicate = execute(deps.as_mut(), env, admin, create_msg);
+        assert_eq!(
+            duplicate.unwrap_err(),
+            StdError::generic_err(format!("Pair {} is already registered at pair_addr.", pair))
+        );
+        Ok(())
+    }
+
     #[test]
     fn add_amm_pairs() {
         let ref mut deps = mock_dependencies(&[]);
@@ -190,7 +669,9 @@ pub mod test_contract {
             deps.as_ref(),
             mock_env(),
             QueryMsg::ListAMMPairs {
-                pagination: pagination(0, PAGINATION_LIMIT),
+                start_after: None,
+                limit: None,
+                order: None,
             },
         )
         .unwrap();
@@ -198,11 +679,467 @@ pub mod test_contract {
         let response: QueryResponse = from_binary(&result).unwrap();
 
         match response {
-            QueryResponse::ListAMMPairs { amm_pairs: stored } => {
-                assert_eq!(amm_pairs, stored)
+            QueryResponse::ListAMMPairs { amm_pairs: stored, next } => {
+                assert_eq!(amm_pairs, stored);
+                assert_eq!(next, None);
             }
             _ => panic!("QueryResponse::ListExchanges"),
         }
+
+        let response: QueryResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetPairCount {}).unwrap()).unwrap();
+        assert_eq!(response, QueryResponse::GetPairCount { count: 5 });
+
+        let list = |start_after: Option<u64>, order: PageOrder| -> QueryResponse {
+            from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::ListAMMPairs {
+                        start_after,
+                        limit: Some(2),
+                        order: Some(order),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(list(None, PageOrder::Ascending), QueryResponse::ListAMMPairs {
+            amm_pairs: amm_pairs[0..2].to_vec(),
+            next: Some(1),
+        });
+        assert_eq!(list(Some(2), PageOrder::Ascending), QueryResponse::ListAMMPairs {
+            amm_pairs: amm_pairs[3..5].to_vec(),
+            next: None,
+        });
+        assert_eq!(list(None, PageOrder::Descending), QueryResponse::ListAMMPairs {
+            amm_pairs: vec![amm_pairs[4].clone(), amm_pairs[3].clone()],
+            next: Some(3),
+        });
+        assert_eq!(list(Some(1), PageOrder::Descending), QueryResponse::ListAMMPairs {
+            amm_pairs: vec![amm_pairs[0].clone()],
+            next: None,
+        });
+    }
+
+    #[test]
+    fn migrate_pairs_pages_through_registry_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&config))?;
+
+        let amm_pairs: Vec<AMMPair> = (0..(PAGINATION_LIMIT as u64 + 4))
+            .map(|i| AMMPair {
+                pair: TokenPair(
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_0_addr_{}", i)),
+                        token_code_hash: format!("token_0_hash_{}", i),
+                    },
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_1_addr_{}", i)),
+                        token_code_hash: format!("token_1_hash_{}", i),
+                    },
+                ),
+                address: Addr::unchecked(format!("pair_addr_{}", i)),
+                enabled: true,
+                code_hash: "old_hash".to_string(),
+            })
+            .collect();
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::AddAMMPairs { amm_pairs })?;
+        // Removed pairs are skipped
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::RemoveAMMPairs { pairs: vec![Addr::unchecked("pair_addr_1")] },
+        )?;
+
+        let pair_msg = to_binary(&"{}")?;
+        let migrate_pair_msg = |address: &str| {
+            CosmosMsg::Wasm(WasmMsg::Migrate {
+                contract_addr: address.to_string(),
+                code_hash: "new_hash".to_string(),
+                code_id: 2,
+                msg: pair_msg.clone(),
+            })
+        };
+
+        // Explicitly listed pairs are skipped by the paged calls once on the new code
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::MigratePairs {
+                code_id: 2,
+                code_hash: "new_hash".to_string(),
+                pairs: Some(vec![Addr::unchecked("pair_addr_2")]),
+                msg: pair_msg.clone(),
+            },
+        )?;
+        assert_eq!(result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<_>>(), vec![migrate_pair_msg("pair_addr_2")]);
+
+        let migrate_msg = ExecuteMsg::MigratePairs {
+            code_id: 2,
+            code_hash: "new_hash".to_string(),
+            pairs: None,
+            msg: pair_msg.clone(),
+        };
+        let result = execute(deps.as_mut(), env.clone(), admin.clone(), migrate_msg.clone())?;
+        assert_eq!(result.messages.len(), PAGINATION_LIMIT as usize);
+        assert_eq!(result.messages[0].msg, migrate_pair_msg("pair_addr_0"));
+        assert_eq!(result.messages[1].msg, migrate_pair_msg("pair_addr_3"));
+        let result = execute(deps.as_mut(), env.clone(), admin.clone(), migrate_msg.clone())?;
+        let last = PAGINATION_LIMIT as u64 + 3;
+        assert_eq!(result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<_>>(), vec![
+            migrate_pair_msg(&format!("pair_addr_{}", last - 1)),
+            migrate_pair_msg(&format!("pair_addr_{}", last)),
+        ]);
+        let result = execute(deps.as_mut(), env.clone(), admin.clone(), migrate_msg)?;
+        assert_eq!(result.messages.len(), 0);
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsByAddress { addresses: vec!["pair_addr_0".to_string()] },
+        )?)?;
+        match response {
+            QueryResponse::GetPairsByAddress { amm_pairs } => {
+                assert_eq!(amm_pairs[0].code_hash, "new_hash".to_string())
+            }
+            _ => panic!("QueryResponse::GetPairsByAddress"),
+        }
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            admin,
+            ExecuteMsg::MigratePairs {
+                code_id: 2,
+                code_hash: "new_hash".to_string(),
+                pairs: Some(vec![Addr::unchecked("pair_addr_1")]),
+                msg: pair_msg,
+            },
+        );
+        assert_eq!(result.unwrap_err(), StdError::generic_err("Pair pair_addr_1 is not registered."));
+        Ok(())
+    }
+
+    #[test]
+    fn remove_amm_pairs_keeps_pair_ids_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&config))?;
+
+        let shd = TokenType::CustomToken {
+            contract_addr: Addr::unchecked("shd_addr"),
+            token_code_hash: "shd_hash".to_string(),
+        };
+        let amm_pairs: Vec<AMMPair> = (0..3)
+            .map(|i| AMMPair {
+                pair: TokenPair(
+                    shd.clone(),
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_addr_{}", i)),
+                        token_code_hash: format!("token_hash_{}", i),
+                    },
+                ),
+                address: Addr::unchecked(format!("pair_addr_{}", i)),
+                enabled: true,
+                code_hash: "".to_string(),
+            })
+            .collect();
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::AddAMMPairs { amm_pairs: amm_pairs.clone() })?;
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            admin,
+            ExecuteMsg::RemoveAMMPairs { pairs: vec![Addr::unchecked("pair_addr_0")] },
+        )?;
+        assert_eq!(result.events[0].ty, "deregister_amm_pair".to_string());
+
+        let expected = vec![amm_pairs[1].clone(), amm_pairs[2].clone()];
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListAMMPairs { start_after: None, limit: None, order: None },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListAMMPairs { amm_pairs: expected, next: None });
+
+        // A cursor handed out before the removal still resumes after the same pair
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListAMMPairs { start_after: Some(1), limit: None, order: None },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListAMMPairs { amm_pairs: vec![amm_pairs[2].clone()], next: None });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPairsForToken { token: shd.clone(), pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        // The token's last pair takes the freed position
+        assert_eq!(response, QueryResponse::ListPairsForToken {
+            amm_pairs: vec![amm_pairs[2].clone(), amm_pairs[1].clone()],
+        });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListTokens { pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListTokens {
+            tokens: vec![shd, amm_pairs[2].pair.1.clone(), amm_pairs[1].pair.1.clone()],
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_indexes_registered_pairs_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin, create_init_msg_from_config(&config))?;
+
+        // A version 1 registry only stored the pairs and their keys
+        let shd = TokenType::CustomToken {
+            contract_addr: Addr::unchecked("shd_addr"),
+            token_code_hash: "shd_hash".to_string(),
+        };
+        let amm_pairs: Vec<AMMPair> = (0..2)
+            .map(|i| AMMPair {
+                pair: TokenPair(
+                    shd.clone(),
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_addr_{}", i)),
+                        token_code_hash: format!("token_hash_{}", i),
+                    },
+                ),
+                address: Addr::unchecked(format!("pair_addr_{}", i)),
+                enabled: true,
+                code_hash: "".to_string(),
+            })
+            .collect();
+        for (i, amm_pair) in amm_pairs.iter().enumerate() {
+            amm_pair_keys_w(&mut deps.storage).save(&generate_pair_key(&amm_pair.pair), &amm_pair.address)?;
+            amm_pairs_w(&mut deps.storage).save(i.to_string().as_bytes(), amm_pair)?;
+        }
+        total_amm_pairs_w(&mut deps.storage).save(&2)?;
+        set_contract_version(&mut deps.storage, 1)?;
+
+        migrate(deps.as_mut(), env, MigrateMsg {})?;
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPairsForToken { token: shd.clone(), pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListPairsForToken { amm_pairs: amm_pairs.clone() });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsByAddress { addresses: vec!["pair_addr_1".to_string()] },
+        )?)?;
+        assert_eq!(response, QueryResponse::GetPairsByAddress { amm_pairs: vec![amm_pairs[1].clone()] });
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_loads_v1_config_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+
+        // Version 1 configs have no creation policy or config timelock
+        #[derive(Serialize)]
+        struct ConfigV1 {
+            pair_contract: ContractInstantiationInfo,
+            amm_settings: AMMSettings,
+            lp_token_contract: ContractInstantiationInfo,
+            api_key: ViewingKey,
+            authenticator: Option<Contract>,
+            admin_auth: Contract,
+        }
+        singleton(&mut deps.storage, CONFIG).save(&ConfigV1 {
+            pair_contract: config.pair_contract.clone(),
+            amm_settings: config.amm_settings.clone(),
+            lp_token_contract: config.lp_token_contract.clone(),
+            api_key: config.api_key.clone(),
+            authenticator: config.authenticator.clone(),
+            admin_auth: config.admin_auth.clone(),
+        })?;
+        set_contract_version(&mut deps.storage, 1)?;
+
+        migrate(deps.as_mut(), env, MigrateMsg {})?;
+
+        let migrated = config_r(deps.as_ref().storage).load()?;
+        assert_eq!(migrated.creation_policy, CreationPolicy::Open);
+        assert_eq!(migrated.config_timelock, 0);
+        assert_eq!(migrated, config);
+        Ok(())
+    }
+
+    #[test]
+    fn list_pairs_for_token_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&config))?;
+
+        let shd = TokenType::CustomToken {
+            contract_addr: Addr::unchecked("shd_addr"),
+            token_code_hash: "shd_hash".to_string(),
+        };
+        let amm_pairs: Vec<AMMPair> = (0..3)
+            .map(|i| AMMPair {
+                pair: TokenPair(
+                    shd.clone(),
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_addr_{}", i)),
+                        token_code_hash: format!("token_hash_{}", i),
+                    },
+                ),
+                address: Addr::unchecked(format!("pair_addr_{}", i)),
+                enabled: true,
+                code_hash: "".to_string(),
+            })
+            .collect();
+        execute(deps.as_mut(), env, admin, ExecuteMsg::AddAMMPairs { amm_pairs: amm_pairs.clone() })?;
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPairsForToken { token: shd.clone(), pagination: pagination(1, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListPairsForToken { amm_pairs: amm_pairs[1..].to_vec() });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListTokens { pagination: pagination(0, 2) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListTokens {
+            tokens: vec![shd, amm_pairs[0].pair.1.clone()],
+        });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsByAddress {
+                addresses: vec!["pair_addr_2".to_string(), "unknown".to_string()],
+            },
+        )?)?;
+        assert_eq!(response, QueryResponse::GetPairsByAddress { amm_pairs: vec![amm_pairs[2].clone()] });
+        Ok(())
+    }
+
+    #[test]
+    fn pair_fee_override_applies_to_pair_settings_ok() -> StdResult<()> {
+        let ref mut deps = mock_dependencies(&[]);
+        let config = mkconfig(0);
+        let env = mock_env();
+        let admin = MessageInfo {
+            sender: Addr::unchecked("admin"),
+            funds: vec![],
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), create_init_msg_from_config(&config))?;
+
+        let amm_pairs: Vec<AMMPair> = (0..2)
+            .map(|i| AMMPair {
+                pair: TokenPair(
+                    TokenType::NativeToken { denom: "uscrt".to_string() },
+                    TokenType::CustomToken {
+                        contract_addr: Addr::unchecked(format!("token_addr_{}", i)),
+                        token_code_hash: format!("token_hash_{}", i),
+                    },
+                ),
+                address: Addr::unchecked(format!("pair_addr_{}", i)),
+                enabled: true,
+                code_hash: "".to_string(),
+            })
+            .collect();
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::AddAMMPairs { amm_pairs: amm_pairs.clone() })?;
+
+        let fee = CustomFee {
+            lp_fee: Fee::new(10, 10000),
+            shade_dao_fee: Fee::new(1, 10000),
+        };
+        for amm_pair in &amm_pairs {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                admin.clone(),
+                ExecuteMsg::SetPairFee { pair: amm_pair.pair.clone(), fee: fee.clone() },
+            )?;
+        }
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::ClearPairFee { pair: amm_pairs[0].pair.clone() })?;
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListFeeOverrides { pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListFeeOverrides {
+            overrides: vec![PairFeeOverride { pair: amm_pairs[1].pair.clone(), fee: fee.clone() }],
+        });
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairSettings { pair: amm_pairs[1].pair.clone() },
+        )?)?;
+        match response {
+            QueryResponse::GetPairSettings { amm_settings, fee_override, .. } => {
+                assert_eq!(amm_settings.lp_fee, fee.lp_fee);
+                assert_eq!(amm_settings.shade_dao_fee, fee.shade_dao_fee);
+                assert_eq!(fee_override, Some(fee));
+            }
+            _ => panic!("Query Response does not match"),
+        }
+
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairSettings { pair: amm_pairs[0].pair.clone() },
+        )?)?;
+        match response {
+            QueryResponse::GetPairSettings { amm_settings, fee_override, .. } => {
+                assert_eq!(amm_settings.lp_fee, config.amm_settings.lp_fee);
+                assert_eq!(fee_override, None);
+            }
+            _ => panic!("Query Response does not match"),
+        }
+
+        // Removing a pair drops its override
+        execute(deps.as_mut(), env, admin, ExecuteMsg::RemoveAMMPairs { pairs: vec![Addr::unchecked("pair_addr_1")] })?;
+        let response: QueryResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListFeeOverrides { pagination: pagination(0, PAGINATION_LIMIT) },
+        )?)?;
+        assert_eq!(response, QueryResponse::ListFeeOverrides { overrides: vec![] });
+        Ok(())
     }
 }
 
@@ -225,6 +1162,7 @@ pub fn create_init_msg_from_config(config: &Config) -> InitMsg {
             address: Addr::unchecked("admin"),
             code_hash: "".to_string(),
         },
+        creation_policy: Some(config.creation_policy.clone()),
     }
 }
 
@@ -235,6 +1173,7 @@ pub fn create_query_response_from_config(config: &Config) -> QueryResponse {
         lp_token_contract: config.lp_token_contract.clone(),
         authenticator: None,
         admin_auth: config.admin_auth.clone(),
+        creation_policy: config.creation_policy.clone(),
     }
 }
 
@@ -266,15 +1205,33 @@ impl Querier for MockQuerier {
                 WasmQuery::Smart {
                     contract_addr,
                     code_hash: _,
-                    msg: _,
+                    msg,
                 } => match contract_addr.as_str() {
-                    "admin" => QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
-                        to_binary(&ValidateAdminPermissionResponse {
-                            has_permission: true,
+                    // Admin auth contracts grant permission to everyone except "user"
+                    admin_auth if admin_auth.starts_with("admin") => {
+                        let user = match from_binary(msg).unwrap() {
+                            AdminQueryMsg::ValidateAdminPermission { permission: _, user } => user,
+                            _ => unimplemented!(),
+                        };
+                        QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
+                            to_binary(&ValidateAdminPermissionResponse {
+                                has_permission: user != "user",
+                            })
+                            .unwrap(),
+                        ))
+                    }
+                    "live_pair" => QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
+                        to_binary(&mock_pair_response(from_binary(msg).unwrap())).unwrap(),
+                    )),
+                    _ => QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
+                        to_binary(&QueryAnswer::TokenInfo {
+                            name: "TOKEN".to_string(),
+                            symbol: "TKN".to_string(),
+                            decimals: 6,
+                            total_supply: None,
                         })
                         .unwrap(),
                     )),
-                    _ => unimplemented!(),
                 },
                 _ => unimplemented!(),
             },
@@ -283,6 +1240,46 @@ impl Querier for MockQuerier {
     }
 }
 
+fn mock_pair_response(msg: AMMPairQueryMsg) -> AMMPairQueryResponse {
+    let pair = TokenPair(
+        TokenType::NativeToken { denom: "uscrt".to_string() },
+        TokenType::NativeToken { denom: "uatom".to_string() },
+    );
+    match msg {
+        AMMPairQueryMsg::GetPairInfo {} => AMMPairQueryResponse::GetPairInfo {
+            liquidity_token: Contract {
+                address: Addr::unchecked("lp_token"),
+                code_hash: "".to_string(),
+            },
+            factory: None,
+            pair,
+            amount_0: Uint128::new(100),
+            amount_1: Uint128::new(200),
+            total_liquidity: Uint128::new(150),
+            contract_version: 1,
+            fee_info: FeeInfo {
+                shade_dao_address: Addr::unchecked("CALLBACKADDR"),
+                lp_fee: Fee::new(28, 10000),
+                shade_dao_fee: Fee::new(2, 10000),
+            },
+        },
+        AMMPairQueryMsg::GetConfig {} => AMMPairQueryResponse::GetConfig {
+            factory_contract: None,
+            lp_token: Contract {
+                address: Addr::unchecked("lp_token"),
+                code_hash: "".to_string(),
+            },
+            staking_contract: Some(Contract {
+                address: Addr::unchecked("staking"),
+                code_hash: "".to_string(),
+            }),
+            pair,
+            custom_fee: None,
+        },
+        _ => unimplemented!(),
+    }
+}
+
 fn mkconfig(id: u64) -> Config {
     Config::from_init_msg(InitMsg {
         pair_contract: ContractInstantiationInfo {
@@ -308,6 +1305,7 @@ fn mkconfig(id: u64) -> Config {
             address: Addr::unchecked("admin"),
             code_hash: "".to_string(),
         },
+        creation_policy: None,
     })
 }
 
@@ -21,7 +21,6 @@ fn factory_integration_tests() {
     use multi_test::amm_pairs::amm_pairs_lib::amm_pairs_lib::{amm_pair_contract_store_in};
     use multi_test::help_lib::integration_help_lib::{convert_to_contract_link, create_token_pair, mint_deposit_snip20, configure_block_send_init_funds, snip20_lp_token_contract_store, create_token_pair_with_native};
     use multi_test::staking::staking_lib::staking_lib::staking_contract_store_in;
-    use shadeswap_shared::Pagination;
     use shadeswap_shared::amm_pair::{AMMPair, AMMSettings};
     use shadeswap_shared::core::{TokenType, Fee};
     use shadeswap_shared::factory::ExecuteMsg;
@@ -66,7 +65,8 @@ fn factory_integration_tests() {
         prng_seed: to_binary(&"seed".to_string()).unwrap(),
         api_key: "api_key".to_string(),
         authenticator: Some(convert_to_contract_link(&auth_contract)),
-        admin_auth: convert_to_contract_link(&auth_contract)
+        admin_auth: convert_to_contract_link(&auth_contract),
+        creation_policy: None,
     };
     let factory_contract_id = router.store_code(contract_counter());
     let factory_contract = router
@@ -84,7 +84,7 @@ fn factory_integration_tests() {
     roll_blockchain(&mut router, 1).unwrap();
     let query: QueryResponse = router.query_test(factory_contract.clone(),to_binary(&QueryMsg::GetConfig { }).unwrap()).unwrap();
     match query {
-        QueryResponse::GetConfig { pair_contract: _, amm_settings, lp_token_contract: _, authenticator: _, admin_auth: _} => {
+        QueryResponse::GetConfig { pair_contract: _, amm_settings, lp_token_contract: _, authenticator: _, admin_auth: _, creation_policy: _} => {
             assert_eq!(amm_settings.lp_fee, shadeswap_shared::core::Fee { nom: 2, denom: 100 });
             assert_eq!(amm_settings.shade_dao_fee, shadeswap_shared::core::Fee { nom: 2, denom: 100 });
         },
@@ -102,12 +102,10 @@ fn factory_integration_tests() {
                 code_hash: staking_contract_info.code_hash.clone(), 
                 id: staking_contract_info.code_id
             },
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.clone(), 
                 token_code_hash: reward_contract.code_hash.clone() 
             },
-            valid_to: Uint128::new(30000000u128),
             custom_label: None
         }),
         None,
@@ -137,21 +135,18 @@ fn factory_integration_tests() {
     
     // LIST AMM PAIRS
     let list_amm_pairs = to_binary(&QueryMsg::ListAMMPairs { 
-        pagination: Pagination{
-            start: 0,
-            limit: 30,
-        }
+        start_after: None,
+        limit: Some(30),
+        order: None,
     }).unwrap();
 
     // ASSERT AMM PAIRS == 1
     let query_response: QueryResponse = router.query_test(factory_contract.clone(), list_amm_pairs.clone()).unwrap();
     match query_response{       
-        QueryResponse::ListAMMPairs { amm_pairs } => {
+        QueryResponse::ListAMMPairs { amm_pairs, .. } => {
            assert_eq!(amm_pairs.len(), 1);
         },
-        QueryResponse::GetConfig { pair_contract: _, amm_settings: _, lp_token_contract: _, authenticator: _ , admin_auth: _} => todo!(),
-        QueryResponse::GetAMMPairAddress { address: _ } => todo!(),
-        QueryResponse::AuthorizeApiKey { authorized: _ } => todo!(),        
+        _ => panic!("Query Response does not match"),
     };
     roll_blockchain(&mut router, 1).unwrap();
 
@@ -170,12 +165,10 @@ fn factory_integration_tests() {
                 code_hash: staking_contract_info.code_hash.clone(), 
                 id: staking_contract_info.code_id
             },
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.clone(), 
                 token_code_hash: reward_contract.code_hash.clone() 
             },
-            valid_to: Uint128::new(30000000u128),
             custom_label: None
         }),
         lp_token_decimals: 18u8,
@@ -194,7 +187,7 @@ fn factory_integration_tests() {
     // ASSERT AMM_PAIR == 2
     let query_response: QueryResponse = router.query_test(factory_contract.clone(), list_amm_pairs.clone()).unwrap();
     match query_response{       
-        QueryResponse::ListAMMPairs { amm_pairs } => {
+        QueryResponse::ListAMMPairs { amm_pairs, .. } => {
            assert_eq!(amm_pairs.len(), 2);
         },       
         _ => {}      
@@ -211,12 +204,10 @@ fn factory_integration_tests() {
                 code_hash: staking_contract_info.code_hash.clone(), 
                 id: staking_contract_info.code_id
             },
-            daily_reward_amount: Uint128::new(30000u128),
             reward_token: TokenType::CustomToken { 
                 contract_addr: reward_contract.address.clone(), 
                 token_code_hash: reward_contract.code_hash.clone() 
             },
-            valid_to: Uint128::new(30000000u128),
             custom_label: None
         }),
         lp_token_decimals: 18u8,
@@ -235,7 +226,7 @@ fn factory_integration_tests() {
     // ASSERT AMM_PAIR == 3
     let query_response: QueryResponse = router.query_test(factory_contract.clone(), list_amm_pairs.clone()).unwrap();
     match query_response{       
-        QueryResponse::ListAMMPairs { amm_pairs } => {
+        QueryResponse::ListAMMPairs { amm_pairs, .. } => {
             assert_eq!(amm_pairs.len(), 3);
         },       
         _ => {}      
@@ -292,7 +283,9 @@ fn factory_integration_tests() {
             shade_dao_address: convert_to_contract_link(&shade_dao_address_contract)
         }), 
         api_key: Some("pass_key".to_string()), 
-        admin_auth: Some(convert_to_contract_link(&auth_contract)) 
+        admin_auth: None,
+        creation_policy: None,
+        config_timelock: None,
     }; 
     let _ = router.execute_contract(
         owner_addr.to_owned(),
@@ -302,10 +295,20 @@ fn factory_integration_tests() {
     )
     .unwrap();  
 
+    // HAND OVER ADMIN AUTH
+    router.execute_contract(
+        owner_addr.to_owned(),
+        &factory_contract,
+        &ExecuteMsg::ProposeAdminAuth { admin_auth: convert_to_contract_link(&auth_contract) },
+        &[],
+    )
+    .unwrap();
+    router.execute_contract(owner_addr.to_owned(), &factory_contract, &ExecuteMsg::AcceptAdminAuth {}, &[]).unwrap();
+
     // ASSERT SETCONFIG CHANGES
     let query: QueryResponse = router.query_test(factory_contract.clone(),to_binary(&QueryMsg::GetConfig { }).unwrap()).unwrap();
     match query {
-        QueryResponse::GetConfig { pair_contract: _, amm_settings, lp_token_contract, authenticator: _, admin_auth: _} => {
+        QueryResponse::GetConfig { pair_contract: _, amm_settings, lp_token_contract, authenticator: _, admin_auth: _, creation_policy: _} => {
             assert_eq!(amm_settings.lp_fee, shadeswap_shared::core::Fee { nom: 5, denom: 100 });
             assert_eq!(amm_settings.shade_dao_fee, shadeswap_shared::core::Fee { nom: 10, denom: 100 });
             assert_eq!(lp_token_contract.code_hash,update_lp_token_info.code_hash);
@@ -8,8 +8,8 @@ use cosmwasm_std::{
 use crate::batch;
 use crate::msg::QueryWithPermit;
 use crate::msg::{
-    space_pad, ContractStatusLevel, ExecuteAnswer, ExecuteMsg, InstantiateMsg, QueryAnswer,
-    QueryMsg, ResponseStatus::Success,
+    space_pad, ContractStatusLevel, ExecuteAnswer, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    QueryAnswer, QueryMsg, ResponseStatus::Success,
 };
 use crate::receiver::Snip20ReceiveMsg;
 use crate::state::{
@@ -25,10 +25,12 @@ use secret_toolkit::crypto::sha_256;
 use crate::viewing_key_obj::ViewingKeyObj;
 use secret_toolkit::permit::{validate, Permit, RevokedPermits, TokenPermissions};
 use secret_toolkit::viewing_key::{ViewingKey, ViewingKeyStore};
+use shadeswap_shared::utils::migrate::{assert_can_migrate, set_contract_version};
 
 /// We make sure that responses from `handle` are padded to a multiple of this size.
 pub const RESPONSE_BLOCK_SIZE: usize = 256;
 pub const PREFIX_REVOKED_PERMITS: &str = "revoked_permits";
+pub const LP_TOKEN_CONTRACT_VERSION: u32 = 1;
 
 #[entry_point]
 pub fn instantiate(
@@ -109,11 +111,24 @@ pub fn instantiate(
     MintersStore::save(deps.storage, minters)?;
 
     ViewingKey::set_seed(deps.storage, &prng_seed_hashed);
+    set_contract_version(deps.storage, LP_TOKEN_CONTRACT_VERSION)?;
     let mut response = Response::new();   
     response.data = Some(env.contract.address.as_bytes().into());
     Ok(response)
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_can_migrate(deps.storage, LP_TOKEN_CONTRACT_VERSION)?;
+    // No stored state has changed between versions yet, so only the version is recorded
+    set_contract_version(deps.storage, LP_TOKEN_CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", LP_TOKEN_CONTRACT_VERSION.to_string()))
+}
+
 fn pad_response(response: StdResult<Response>) -> StdResult<Response> {
     response.map(|mut response| {
         response.data = response.data.map(|mut data| {
@@ -9,14 +9,14 @@ use shadeswap_shared::utils::{pad_query_result, pad_response_result};
 use shadeswap_shared::Contract;
 use shadeswap_shared::{
     core::{TokenAmount, TokenType},
-    router::{ExecuteMsg, InvokeMsg, QueryMsg},
+    router::{ExecuteMsg, Hop, InvokeMsg, QueryMsg},
     amm_pair::{ QueryMsgResponse as AMMPairQueryReponse}
 };
 
 use crate::{
     operations::{
-        next_swap, refresh_tokens,
-        swap_tokens_for_exact_tokens,
+        next_swap, refresh_tokens, set_config, set_wrapped_native_token, swap_tokens_cyclic,
+        swap_tokens_for_batch, swap_tokens_for_exact_tokens, wrapped_native_hop,
     },
     query,
     state::{config_r, config_w, Config},
@@ -27,6 +27,7 @@ use crate::{
 const BLOCK_SIZE: usize = 256;
 pub const SHADE_ROUTER_KEY: &str = "SHADE_ROUTER_KEY";
 pub const SWAP_REPLY_ID: u64 = 1u64;
+pub const MAX_BATCH_RECIPIENTS: usize = 50;
 
 #[entry_point]
 pub fn instantiate(
@@ -38,6 +39,10 @@ pub fn instantiate(
     config_w(deps.storage).save(&Config {
         viewing_key: SHADE_ROUTER_KEY.to_string(),
         admin_auth: msg.admin_auth,
+        wrapped_native_token: None,
+        max_referral_fee_bps: 0,
+        trusted_factories: vec![],
+        trusted_pairs: vec![],
     })?;
     Ok(Response::default())
 }
@@ -57,6 +62,8 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 expected_return,
                 path,
                 recipient,
+                max_price_impact_bps,
+                referral,
             } => {
                 if !offer.token.is_native_token() {
                     return Err(StdError::generic_err(
@@ -78,6 +85,60 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     &path,
                     sender,
                     checked_address,
+                    max_price_impact_bps,
+                    referral,
+                    response,
+                )?)
+            }
+            ExecuteMsg::SwapTokensForExactBatch {
+                offer,
+                expected_return,
+                path,
+                recipients,
+                max_price_impact_bps,
+            } => {
+                if !offer.token.is_native_token() {
+                    return Err(StdError::generic_err(
+                        "Sent a non-native token. Should use the receive interface in SNIP20.",
+                    ));
+                }
+                offer.assert_sent_native_token_balance(&info)?;
+                let sender = info.sender.clone();
+                let response = Response::new();
+                Ok(swap_tokens_for_batch(
+                    deps,
+                    env,
+                    offer,
+                    expected_return,
+                    &path,
+                    sender,
+                    recipients,
+                    max_price_impact_bps,
+                    response,
+                )?)
+            }
+            ExecuteMsg::CyclicSwap {
+                offer,
+                path,
+                min_profit,
+                max_price_impact_bps,
+            } => {
+                if !offer.token.is_native_token() {
+                    return Err(StdError::generic_err(
+                        "Sent a non-native token. Should use the receive interface in SNIP20.",
+                    ));
+                }
+                offer.assert_sent_native_token_balance(&info)?;
+                let sender = info.sender.clone();
+                let response = Response::new();
+                Ok(swap_tokens_cyclic(
+                    deps,
+                    env,
+                    offer,
+                    &path,
+                    sender,
+                    min_profit,
+                    max_price_impact_bps,
                     response,
                 )?)
             }
@@ -88,6 +149,39 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 let checked_token_addr = deps.api.addr_validate(&token_addr)?;
                 refresh_tokens(deps, env, checked_token_addr, token_code_hash)
             }
+            ExecuteMsg::SetWrappedNativeToken {
+                wrapped_native_token,
+            } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_wrapped_native_token(deps, env, wrapped_native_token)
+            }
+            ExecuteMsg::SetConfig {
+                admin_auth,
+                max_referral_fee_bps,
+                trusted_factories,
+                trusted_pairs,
+            } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_config(
+                    deps,
+                    admin_auth,
+                    max_referral_fee_bps,
+                    trusted_factories,
+                    trusted_pairs,
+                )
+            }
             ExecuteMsg::RecoverFunds {
                 token,
                 amount,
@@ -144,62 +238,66 @@ fn receiver_callback(
                     expected_return,
                     path,
                     recipient,
+                    max_price_impact_bps,
+                    referral,
                 } => {
-                    let pair_contract_config = query::pair_contract_config(
-                        &deps.querier,
-                        Contract {
-                            address: deps.api.addr_validate(&path[0].addr.to_string())?,
-                            code_hash: path[0].code_hash.clone(),
-                        },
-                    )?;
-
-                    match pair_contract_config {
-                        AMMPairQueryReponse::GetPairInfo {
-                            liquidity_token: _,
-                            factory: _,
-                            pair,
-                            amount_0: _,
-                            amount_1: _,
-                            total_liquidity: _,
-                            contract_version: _,
-                            fee_info: _
-                        } => {
-                            for token in pair.into_iter() {
-                                match token {
-                                    TokenType::CustomToken { contract_addr, .. } => {
-                                        if *contract_addr == info.sender {
-                                            let offer = TokenAmount {
-                                                token: token.clone(),
-                                                amount,
-                                            };
-
-                                            let checked_address = match recipient {
-                                                Some(x) => Some(deps.api.addr_validate(&x)?),
-                                                None => None,
-                                            };
+                    let offer = received_offer(deps.as_ref(), &info, &path, amount)?;
+                    let checked_address = match recipient {
+                        Some(x) => Some(deps.api.addr_validate(&x)?),
+                        None => None,
+                    };
 
-                                            let response = Response::new();
-                                            return Ok(swap_tokens_for_exact_tokens(
-                                                deps,
-                                                env,
-                                                offer,
-                                                expected_return,
-                                                &path,
-                                                from,
-                                                checked_address,
-                                                response,
-                                            )?);
-                                        }
-                                    }
-                                    _ => continue,
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                    return Err(StdError::generic_err(
-                        "No matching token in pair".to_string(),
-                    ));
+                    let response = Response::new();
+                    Ok(swap_tokens_for_exact_tokens(
+                        deps,
+                        env,
+                        offer,
+                        expected_return,
+                        &path,
+                        from,
+                        checked_address,
+                        max_price_impact_bps,
+                        referral,
+                        response,
+                    )?)
+                }
+                InvokeMsg::SwapTokensForExactBatch {
+                    expected_return,
+                    path,
+                    recipients,
+                    max_price_impact_bps,
+                } => {
+                    let offer = received_offer(deps.as_ref(), &info, &path, amount)?;
+                    let response = Response::new();
+                    Ok(swap_tokens_for_batch(
+                        deps,
+                        env,
+                        offer,
+                        expected_return,
+                        &path,
+                        from,
+                        recipients,
+                        max_price_impact_bps,
+                        response,
+                    )?)
+                }
+                InvokeMsg::CyclicSwap {
+                    path,
+                    min_profit,
+                    max_price_impact_bps,
+                } => {
+                    let offer = received_offer(deps.as_ref(), &info, &path, amount)?;
+                    let response = Response::new();
+                    Ok(swap_tokens_cyclic(
+                        deps,
+                        env,
+                        offer,
+                        &path,
+                        from,
+                        min_profit,
+                        max_price_impact_bps,
+                        response,
+                    )?)
                 }
             }
         } else {
@@ -209,6 +307,57 @@ fn receiver_callback(
     )
 }
 
+/// Resolve the token received through a SNIP20 send against the first hop of the path
+fn received_offer(
+    deps: Deps,
+    info: &MessageInfo,
+    path: &[Hop],
+    amount: Uint128,
+) -> StdResult<TokenAmount> {
+    if path.is_empty() {
+        return Err(StdError::generic_err("Path must contain at least one hop."));
+    }
+
+    // SNIP20 sent to unwrap as the first step of the path
+    let config = config_r(deps.storage).load()?;
+    if let Some(wrapped_native_token) = wrapped_native_hop(&config, &path[0]) {
+        if wrapped_native_token.token.address != info.sender {
+            return Err(StdError::generic_err(
+                "No matching token in pair".to_string(),
+            ));
+        }
+
+        return Ok(TokenAmount {
+            token: wrapped_native_token.custom_token(),
+            amount,
+        });
+    }
+
+    let pair_contract_config = query::pair_contract_config(
+        &deps.querier,
+        Contract {
+            address: deps.api.addr_validate(&path[0].addr.to_string())?,
+            code_hash: path[0].code_hash.clone(),
+        },
+    )?;
+
+    if let AMMPairQueryReponse::GetPairInfo { pair, .. } = pair_contract_config {
+        for token in pair.into_iter() {
+            if let TokenType::CustomToken { contract_addr, .. } = token {
+                if *contract_addr == info.sender {
+                    return Ok(TokenAmount {
+                        token: token.clone(),
+                        amount,
+                    });
+                }
+            }
+        }
+    }
+    Err(StdError::generic_err(
+        "No matching token in pair".to_string(),
+    ))
+}
+
 #[entry_point]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     pad_query_result(
@@ -216,8 +365,15 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             QueryMsg::SwapSimulation { offer, path, exclude_fee } => query::swap_simulation(deps, path, offer, exclude_fee),
             QueryMsg::GetConfig {} => {
                 let config = config_r(deps.storage).load()?;
-                return Ok(to_binary(&QueryMsgResponse::GetConfig { admin_auth: config.admin_auth })?)
+                return Ok(to_binary(&QueryMsgResponse::GetConfig {
+                    admin_auth: config.admin_auth,
+                    wrapped_native_token: config.wrapped_native_token,
+                    max_referral_fee_bps: config.max_referral_fee_bps,
+                    trusted_factories: config.trusted_factories,
+                    trusted_pairs: config.trusted_pairs,
+                })?)
             },
+            QueryMsg::GetReferralVolume { referrer } => query::referral_volume(deps, referrer),
         },
         BLOCK_SIZE,
     )
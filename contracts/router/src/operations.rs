@@ -1,25 +1,31 @@
 use cosmwasm_std::{
-    to_binary, Addr, Coin, CosmosMsg, DepsMut, Env, Response, StdError, StdResult, Storage, SubMsg,
-    Uint128, WasmMsg,
+    to_binary, Addr, Attribute, Coin, CosmosMsg, Deps, DepsMut, Env, Event, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use shadeswap_shared::{
-    core::{TokenAmount, TokenType},
+    core::{TokenAmount, TokenPair, TokenType},
     msg::amm_pair::{
         ExecuteMsg as AMMPairExecuteMsg, InvokeMsg as AMMPairInvokeMsg,
         QueryMsgResponse as AMMPairQueryReponse,
     },
-    router::{Hop, ExecuteMsgResponse},
+    router::{
+        BatchRecipient, ExecuteMsgResponse, Hop, Referral, ReferralVolume, WrappedNativeToken,
+    },
     snip20::{
         self,
-        helpers::{register_receive, set_viewing_key_msg},
+        helpers::{redeem_msg, register_receive, set_viewing_key_msg},
     },
+    utils::{calc::BASIS_POINTS, ExecuteCallback},
     Contract
 };
 
 use crate::{
-    contract::{SHADE_ROUTER_KEY, SWAP_REPLY_ID},
+    contract::{MAX_BATCH_RECIPIENTS, SHADE_ROUTER_KEY, SWAP_REPLY_ID},
     query,
-    state::{config_r, config_w, epheral_storage_r, epheral_storage_w, CurrentSwapInfo},
+    state::{
+        config_r, config_w, epheral_storage_r, epheral_storage_w, referral_stats_r,
+        referral_stats_w, Config, CurrentSwapInfo,
+    },
 };
 
 /// Set Viewing Key for Router & register pair token.
@@ -66,51 +72,62 @@ pub fn next_swap(deps: DepsMut, env: Env, mut response: Response) -> StdResult<R
         };
 
         if info.path.len() > (info.current_index + 1) as usize {
-            let next_pair_contract = query::pair_contract_config(
-                &deps.querier,
-                Contract {
-                    address: deps
-                        .api
-                        .addr_validate(&info.path[info.current_index as usize + 1].addr.clone())?,
-                    code_hash: info.path[info.current_index as usize + 1].code_hash.clone(),
-                },
+            let config = config_r(deps.storage).load()?;
+            info.current_index = info.current_index + 1;
+            let next_hop = info.path[info.current_index as usize].clone();
+            info.next_token_in = hop_token_out(deps.as_ref(), &config, &next_hop, &info.next_token_in)?;
+            epheral_storage_w(deps.storage).save(&info)?;
+            response = get_trade_with_callback(
+                env,
+                &config,
+                token_in,
+                next_hop,
+                info.max_price_impact_bps,
+                response,
             )?;
+            Ok(response)
+        } else {
+            let mut amount_out = token_in.amount;
+            if let Some(referral) = &info.referral {
+                let referral_fee = token_in
+                    .amount
+                    .multiply_ratio(referral.fee_bps, BASIS_POINTS);
+                amount_out = token_in.amount.checked_sub(referral_fee)?;
+                record_referral_volume(deps.storage, referral, &token_in, referral_fee)?;
+                if !referral_fee.is_zero() {
+                    response = response.add_message(token_in.token.create_send_msg(
+                        env.contract.address.to_string(),
+                        referral.address.clone(),
+                        referral_fee,
+                    )?);
+                }
+                response = response.add_attributes(vec![
+                    Attribute::new("referral_address", referral.address.clone()),
+                    Attribute::new("referral_fee_amount", referral_fee.to_string()),
+                ]);
+            }
 
-            match next_pair_contract {
-                AMMPairQueryReponse::GetPairInfo {
-                    liquidity_token: _,
-                    factory: _,
-                    pair,
-                    amount_0: _,
-                    amount_1: _,
-                    total_liquidity: _,
-                    contract_version: _,
-                    fee_info: _,
-                } => {
-                    info.current_index = info.current_index + 1;
-
-                    if pair.0 == info.next_token_in {
-                        info.next_token_in = pair.1;
-                    } else {
-                        info.next_token_in = pair.0;
-                    }
-                    epheral_storage_w(deps.storage).save(&info)?;
-                    response = get_trade_with_callback(
-                        env,
-                        token_in,
-                        info.path[(info.current_index) as usize].clone(),
-                        response,
-                    )?;
-                    Ok(response)
+            if let Some(min_profit) = info.min_profit {
+                if token_in.token != info.amount.token {
+                    return Err(StdError::generic_err(
+                        "Cyclic swap must end in the offered token.",
+                    ));
                 }
-                _ => Err(StdError::generic_err("Contract not found.")),
+                let profit = amount_out.checked_sub(info.amount.amount).unwrap_or_default();
+                if amount_out < info.amount.amount || profit < min_profit {
+                    return Err(StdError::generic_err(format!(
+                        "Cyclic swap profit below minimum. Input: {}, Output: {}, Min profit: {}",
+                        info.amount.amount, amount_out, min_profit
+                    )));
+                }
+                response = response.add_attribute("profit", profit.to_string());
             }
-        } else {
+
             if let Some(min_out) = info.amount_out_min {
-                if token_in.amount.lt(&min_out) {
+                if amount_out.lt(&min_out) {
                     return Err(StdError::generic_err(
                         "Operation fell short of expected_return. Actual: ".to_owned()
-                            + &token_in.amount.to_string().to_owned()
+                            + &amount_out.to_string().to_owned()
                             + ", Expected: "
                             + &min_out.to_string().to_owned(),
                     ));
@@ -118,14 +135,34 @@ pub fn next_swap(deps: DepsMut, env: Env, mut response: Response) -> StdResult<R
             }
 
             epheral_storage_w(deps.storage).remove();
-            response = response.add_messages(vec![token_in.token.create_send_msg(
-                env.contract.address.to_string(),
-                info.recipient.to_string(),
-                token_in.amount,
-            )?]).set_data(to_binary(&
+            response = match &info.recipients {
+                Some(recipients) => {
+                    let payouts = batch_payouts(amount_out, recipients)?;
+                    for (recipient, payout) in recipients.iter().zip(payouts) {
+                        if !payout.is_zero() {
+                            response = response.add_message(token_in.token.create_send_msg(
+                                env.contract.address.to_string(),
+                                recipient.address.clone(),
+                                payout,
+                            )?);
+                        }
+                        response = response.add_event(
+                            Event::new("batch_payout")
+                                .add_attribute("recipient", recipient.address.clone())
+                                .add_attribute("amount", payout.to_string()),
+                        );
+                    }
+                    response
+                }
+                None => response.add_messages(vec![token_in.token.create_send_msg(
+                    env.contract.address.to_string(),
+                    info.recipient.to_string(),
+                    amount_out,
+                )?]),
+            }.set_data(to_binary(&
                 ExecuteMsgResponse::SwapResult {
                     amount_in: info.amount.amount,
-                    amount_out: token_in.amount
+                    amount_out,
                 }
             )?);
 
@@ -147,51 +184,335 @@ pub fn swap_tokens_for_exact_tokens(
     path: &Vec<Hop>,
     sender: Addr,
     recipient: Option<Addr>,
+    max_price_impact_bps: Option<u64>,
+    referral: Option<Referral>,
     mut response: Response,
 ) -> StdResult<Response> {
     //Validates whether the amount received is greater then the amount_out_min
+    let config = config_r(deps.storage).load()?;
+    let referral = match referral {
+        Some(referral) => {
+            if referral.fee_bps > config.max_referral_fee_bps {
+                return Err(StdError::generic_err(format!(
+                    "Referral fee exceeds maximum ({} > {} bps).",
+                    referral.fee_bps, config.max_referral_fee_bps
+                )));
+            }
+            Some(Referral {
+                address: deps.api.addr_validate(&referral.address)?.to_string(),
+                fee_bps: referral.fee_bps,
+            })
+        }
+        None => None,
+    };
+    let next_token_in = hop_token_out(deps.as_ref(), &config, &path[0], &amount_in.token)?;
+
+    epheral_storage_w(deps.storage).save(&CurrentSwapInfo {
+        amount: amount_in.clone(),
+        amount_out_min: amount_out_min,
+        path: path.clone(),
+        recipient: recipient.unwrap_or(sender),
+        current_index: 0,
+        next_token_in: next_token_in,
+        max_price_impact_bps,
+        referral,
+        recipients: None,
+        min_profit: None,
+    })?;
+
+    response = get_trade_with_callback(
+        env,
+        &config,
+        amount_in,
+        path[0].clone(),
+        max_price_impact_bps,
+        response,
+    )?;
+
+    Ok(response)
+}
+
+/// Execute Swap for Exact Token paying the output out to several recipients
+pub fn swap_tokens_for_batch(
+    mut deps: DepsMut,
+    env: Env,
+    amount_in: TokenAmount,
+    amount_out_min: Option<Uint128>,
+    path: &Vec<Hop>,
+    sender: Addr,
+    recipients: Vec<BatchRecipient>,
+    max_price_impact_bps: Option<u64>,
+    response: Response,
+) -> StdResult<Response> {
+    if recipients.is_empty() || recipients.len() > MAX_BATCH_RECIPIENTS {
+        return Err(StdError::generic_err(format!(
+            "Batch must have between 1 and {} recipients.",
+            MAX_BATCH_RECIPIENTS
+        )));
+    }
+    let mut checked_recipients = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        if recipient.share.is_zero() {
+            return Err(StdError::generic_err(
+                "Recipient share must be greater than zero.",
+            ));
+        }
+        checked_recipients.push(BatchRecipient {
+            address: deps.api.addr_validate(&recipient.address)?.to_string(),
+            share: recipient.share,
+        });
+    }
+
+    let response = swap_tokens_for_exact_tokens(
+        deps.branch(),
+        env,
+        amount_in,
+        amount_out_min,
+        path,
+        sender,
+        None,
+        max_price_impact_bps,
+        None,
+        response,
+    )?;
+
+    let mut info = epheral_storage_r(deps.storage).load()?;
+    info.recipients = Some(checked_recipients);
+    epheral_storage_w(deps.storage).save(&info)?;
+
+    Ok(response)
+}
+
+/// Execute a swap along a path that starts and ends in the same token, paid back to the sender
+pub fn swap_tokens_cyclic(
+    mut deps: DepsMut,
+    env: Env,
+    amount_in: TokenAmount,
+    path: &Vec<Hop>,
+    sender: Addr,
+    min_profit: Uint128,
+    max_price_impact_bps: Option<u64>,
+    response: Response,
+) -> StdResult<Response> {
+    if path.len() < 2 {
+        return Err(StdError::generic_err(
+            "Cyclic swap path must contain at least two hops.",
+        ));
+    }
+
+    let response = swap_tokens_for_exact_tokens(
+        deps.branch(),
+        env,
+        amount_in,
+        None,
+        path,
+        sender,
+        None,
+        max_price_impact_bps,
+        None,
+        response,
+    )?;
+
+    let mut info = epheral_storage_r(deps.storage).load()?;
+    info.min_profit = Some(min_profit);
+    epheral_storage_w(deps.storage).save(&info)?;
+
+    Ok(response)
+}
+
+/// Split an amount pro rata to the recipients' shares, the rounding dust goes to the last recipient
+pub fn batch_payouts(amount: Uint128, recipients: &[BatchRecipient]) -> StdResult<Vec<Uint128>> {
+    let mut total_shares = Uint128::zero();
+    for recipient in recipients {
+        total_shares = total_shares.checked_add(recipient.share)?;
+    }
+
+    let mut payouts = Vec::with_capacity(recipients.len());
+    let mut remaining = amount;
+    for (index, recipient) in recipients.iter().enumerate() {
+        let payout = if index + 1 == recipients.len() {
+            remaining
+        } else {
+            amount.multiply_ratio(recipient.share, total_shares)
+        };
+        remaining = remaining.checked_sub(payout)?;
+        payouts.push(payout);
+    }
+    Ok(payouts)
+}
+
+/// Set the SNIP20 used for wrap and unwrap hops and register the router with it
+pub fn set_wrapped_native_token(
+    deps: DepsMut,
+    env: Env,
+    wrapped_native_token: Option<WrappedNativeToken>,
+) -> StdResult<Response> {
+    let mut config = config_r(deps.storage).load()?;
+    let mut messages = vec![];
+    if let Some(wrapped_native_token) = &wrapped_native_token {
+        register_pair_token(
+            &env,
+            &mut messages,
+            &wrapped_native_token.custom_token(),
+            config.viewing_key.clone(),
+        )?;
+    }
+    config.wrapped_native_token = wrapped_native_token;
+    config_w(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "set_wrapped_native_token"))
+}
+
+/// Update the router configuration
+pub fn set_config(
+    deps: DepsMut,
+    admin_auth: Option<Contract>,
+    max_referral_fee_bps: Option<u64>,
+    trusted_factories: Option<Vec<Contract>>,
+    trusted_pairs: Option<Vec<String>>,
+) -> StdResult<Response> {
+    let mut config = config_r(deps.storage).load()?;
+    if let Some(admin_auth) = admin_auth {
+        config.admin_auth = admin_auth;
+    }
+    if let Some(max_referral_fee_bps) = max_referral_fee_bps {
+        if max_referral_fee_bps > BASIS_POINTS {
+            return Err(StdError::generic_err(format!(
+                "Max referral fee can not exceed {} bps.",
+                BASIS_POINTS
+            )));
+        }
+        config.max_referral_fee_bps = max_referral_fee_bps;
+    }
+    if let Some(trusted_factories) = trusted_factories {
+        config.trusted_factories = trusted_factories;
+    }
+    if let Some(trusted_pairs) = trusted_pairs {
+        config.trusted_pairs = trusted_pairs
+            .iter()
+            .map(|pair| deps.api.addr_validate(pair))
+            .collect::<StdResult<Vec<Addr>>>()?;
+    }
+    config_w(deps.storage).save(&config)?;
+
+    Ok(Response::new().add_attribute("action", "set_config"))
+}
+
+/// Add the output of a referred swap to the referrer's volume
+fn record_referral_volume(
+    storage: &mut dyn Storage,
+    referral: &Referral,
+    token_out: &TokenAmount,
+    referral_fee: Uint128,
+) -> StdResult<()> {
+    let key = referral.address.as_bytes();
+    let mut stats = referral_stats_r(storage).may_load(key)?.unwrap_or_default();
+    stats.swap_count += 1;
+    match stats
+        .volumes
+        .iter_mut()
+        .find(|volume| volume.token == token_out.token)
+    {
+        Some(volume) => {
+            volume.volume = volume.volume.checked_add(token_out.amount)?;
+            volume.fee_amount = volume.fee_amount.checked_add(referral_fee)?;
+        }
+        None => stats.volumes.push(ReferralVolume {
+            token: token_out.token.clone(),
+            volume: token_out.amount,
+            fee_amount: referral_fee,
+        }),
+    }
+    referral_stats_w(storage).save(key, &stats)
+}
+
+/// Returns the wrapped native token when the hop is a wrap/unwrap step
+pub fn wrapped_native_hop<'a>(config: &'a Config, hop: &Hop) -> Option<&'a WrappedNativeToken> {
+    config
+        .wrapped_native_token
+        .as_ref()
+        .filter(|wrapped| wrapped.token.address.as_str() == hop.addr)
+}
+
+/// Token that comes out of a hop for the given token in
+pub fn hop_token_out(
+    deps: Deps,
+    config: &Config,
+    hop: &Hop,
+    token_in: &TokenType,
+) -> StdResult<TokenType> {
+    if let Some(wrapped_native_token) = wrapped_native_hop(config, hop) {
+        let native_token = wrapped_native_token.native_token();
+        let custom_token = wrapped_native_token.custom_token();
+        return if *token_in == native_token {
+            Ok(custom_token)
+        } else if *token_in == custom_token {
+            Ok(native_token)
+        } else {
+            Err(StdError::generic_err(
+                "Token can not be wrapped or unwrapped by this hop.",
+            ))
+        };
+    }
 
     let next_pair_contract = query::pair_contract_config(
         &deps.querier,
         Contract {
-            address: deps.api.addr_validate(&path[0].addr.clone())?,
-            code_hash: path[0].code_hash.clone(),
+            address: deps.api.addr_validate(&hop.addr)?,
+            code_hash: hop.code_hash.clone(),
         },
     )?;
 
     match next_pair_contract {
-        AMMPairQueryReponse::GetPairInfo {
-            liquidity_token: _,
-            factory: _,
-            pair,
-            amount_0: _,
-            amount_1: _,
-            total_liquidity: _,
-            contract_version: _,
-            fee_info: _,
-        } => {
-            let next_token_in;
-            if pair.0 == amount_in.token {
-                next_token_in = pair.1;
+        AMMPairQueryReponse::GetPairInfo { pair, factory, .. } => {
+            verify_trusted_pair(deps, config, hop, &pair, factory)?;
+            if pair.0 == *token_in {
+                Ok(pair.1)
             } else {
-                next_token_in = pair.0;
+                Ok(pair.0)
             }
+        }
+        _ => Err(StdError::generic_err("Pair Contract not found.")),
+    }
+}
 
-            epheral_storage_w(deps.storage).save(&CurrentSwapInfo {
-                amount: amount_in.clone(),
-                amount_out_min: amount_out_min,
-                path: path.clone(),
-                recipient: recipient.unwrap_or(sender),
-                current_index: 0,
-                next_token_in: next_token_in,
-            })?;
-
-            response = get_trade_with_callback(env, amount_in, path[0].clone(), response)?;
+/// Check that a pair hop was created by a trusted factory or is a trusted standalone pair.
+/// No hop is accepted until the admin configures either list.
+fn verify_trusted_pair(
+    deps: Deps,
+    config: &Config,
+    hop: &Hop,
+    pair: &TokenPair,
+    factory: Option<Contract>,
+) -> StdResult<()> {
+    if config.trusted_factories.is_empty() && config.trusted_pairs.is_empty() {
+        return Err(StdError::generic_err(
+            "No trusted factories or pairs are configured.",
+        ));
+    }
+    if config.trusted_pairs.iter().any(|trusted| trusted.as_str() == hop.addr) {
+        return Ok(());
+    }
 
-            Ok(response)
+    if let Some(factory) = factory {
+        if config
+            .trusted_factories
+            .iter()
+            .any(|trusted| trusted.address == factory.address)
+        {
+            let registered = query::factory_pair_address(&deps.querier, &factory, pair)?;
+            if registered.as_deref() == Some(hop.addr.as_str()) {
+                return Ok(());
+            }
         }
-        _ => Err(StdError::generic_err("Pair Contract not found.")),
     }
+
+    Err(StdError::generic_err(format!(
+        "Hop {} is not a pair of a trusted factory.",
+        hop.addr
+    )))
 }
 
 /// Update Viewing Key
@@ -205,10 +526,32 @@ pub fn update_viewing_key(storage: &mut dyn Storage, viewing_key: String) -> Std
 /// Get Trade from AMMPairs
 fn get_trade_with_callback(
     env: Env,
+    config: &Config,
     token_in: TokenAmount,
     hop: Hop,
+    max_price_impact_bps: Option<u64>,
     mut response: Response,
 ) -> StdResult<Response> {
+    if let Some(wrapped_native_token) = wrapped_native_hop(config, &hop) {
+        let msg = match &token_in.token {
+            TokenType::NativeToken { denom } => snip20::ExecuteMsg::Deposit { padding: None }
+                .to_cosmos_msg(
+                    &wrapped_native_token.token,
+                    vec![Coin {
+                        denom: denom.clone(),
+                        amount: token_in.amount,
+                    }],
+                )?,
+            TokenType::CustomToken { .. } => redeem_msg(
+                token_in.amount,
+                Some(wrapped_native_token.denom.clone()),
+                None,
+                &wrapped_native_token.token,
+            )?,
+        };
+        return Ok(response.add_submessage(SubMsg::reply_on_success(msg, SWAP_REPLY_ID)));
+    }
+
     match &token_in.token {
         TokenType::NativeToken { denom } => {
             let msg = to_binary(&AMMPairExecuteMsg::SwapTokens {
@@ -216,6 +559,7 @@ fn get_trade_with_callback(
                 to: None,
                 offer: token_in.clone(),
                 execute_arbitrage: None,
+                max_price_impact_bps,
             })?;
 
             response = response.add_submessage(SubMsg::reply_always(
@@ -242,6 +586,7 @@ fn get_trade_with_callback(
                     expected_return: None,
                     to: Some(env.contract.address.to_string()),
                     execute_arbitrage: None,
+                    max_price_impact_bps,
                 })?),
                 padding: None,
                 recipient_code_hash: None,
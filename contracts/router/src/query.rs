@@ -1,18 +1,25 @@
 use std::str::FromStr;
 
 use cosmwasm_std::{
-    to_binary, Binary, Deps, QuerierWrapper, QueryRequest, StdError, StdResult, Uint128, Uint256,
-    WasmQuery,
+    to_binary, Binary, Decimal, Deps, QuerierWrapper, QueryRequest, StdError, StdResult, Uint128,
+    Uint256, WasmQuery,
 };
 use shadeswap_shared::{
-    core::TokenAmount,
+    core::{TokenAmount, TokenPair},
+    factory::{QueryMsg as FactoryQueryMsg, QueryResponse as FactoryQueryResponse},
     msg::amm_pair::{
         QueryMsg as AMMPairQueryMsg, QueryMsgResponse as AMMPairQueryReponse, SwapResult,
     },
     router::{Hop, QueryMsgResponse},
+    utils::calc::BASIS_POINTS,
     Contract,
 };
 
+use crate::{
+    operations::{hop_token_out, wrapped_native_hop},
+    state::{config_r, referral_stats_r},
+};
+
 pub fn pair_contract_config(
     querier: &QuerierWrapper,
     pair_contract_address: Contract,
@@ -26,14 +33,45 @@ pub fn pair_contract_config(
     return Ok(result);
 }
 
+/// Address the factory registered for the pair, none when the factory does not know it
+pub fn factory_pair_address(
+    querier: &QuerierWrapper,
+    factory: &Contract,
+    pair: &TokenPair,
+) -> StdResult<Option<String>> {
+    let result: StdResult<FactoryQueryResponse> = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory.address.to_string(),
+        code_hash: factory.code_hash.clone(),
+        msg: to_binary(&FactoryQueryMsg::GetAMMPairAddress { pair: pair.clone() })?,
+    }));
+
+    match result {
+        Ok(FactoryQueryResponse::GetAMMPairAddress { address }) => Ok(Some(address)),
+        _ => Ok(None),
+    }
+}
+
 pub fn swap_simulation(deps: Deps, path: Vec<Hop>, offer: TokenAmount, exclude_fee: Option<bool>) -> StdResult<Binary> {
     let mut sum_total_fee_amount: Uint128 = Uint128::zero();
     let mut sum_lp_fee_amount: Uint128 = Uint128::zero();
     let mut sum_shade_dao_fee_amount: Uint128 = Uint128::zero();
+    let mut path_mid_price = Decimal::one();
+    // Share of the mid price return kept after every hop, in basis points
+    let mut retained_bps: u64 = BASIS_POINTS;
     let mut next_in = offer.clone();
     let querier = &deps.querier;
+    let config = config_r(deps.storage).load()?;
 
     for hop in path {
+        // Wrapping and unwrapping is 1:1 without fees
+        if wrapped_native_hop(&config, &hop).is_some() {
+            next_in = TokenAmount {
+                token: hop_token_out(deps, &config, &hop, &next_in.token)?,
+                amount: next_in.amount,
+            };
+            continue;
+        }
+
         let contract = Contract {
             address: deps.api.addr_validate(&hop.addr)?,
             code_hash: hop.code_hash,
@@ -72,6 +110,8 @@ pub fn swap_simulation(deps: Deps, path: Vec<Hop>, offer: TokenAmount, exclude_f
                         shade_dao_fee_amount,
                         result,
                         price: _,
+                        mid_price,
+                        price_impact_bps,
                     } => {
                         if pair.1 == next_in.token {
                             next_in = TokenAmount {
@@ -89,6 +129,10 @@ pub fn swap_simulation(deps: Deps, path: Vec<Hop>, offer: TokenAmount, exclude_f
                         sum_lp_fee_amount = lp_fee_amount.checked_add(sum_lp_fee_amount)?;
                         sum_shade_dao_fee_amount =
                             shade_dao_fee_amount.checked_add(sum_shade_dao_fee_amount)?;
+                        path_mid_price = path_mid_price * Decimal::from_str(&mid_price)?;
+                        retained_bps = retained_bps
+                            * (BASIS_POINTS - price_impact_bps.min(BASIS_POINTS))
+                            / BASIS_POINTS;
                     }
                     _ => return Err(StdError::generic_err("Failed to complete hop.")),
                 };
@@ -107,5 +151,18 @@ pub fn swap_simulation(deps: Deps, path: Vec<Hop>, offer: TokenAmount, exclude_f
         price: (Uint256::from_str(&next_in.amount.to_string())?
             / Uint256::from_str(&offer.amount.to_string())?)
         .to_string(),
+        mid_price: path_mid_price.to_string(),
+        price_impact_bps: BASIS_POINTS - retained_bps,
+    })
+}
+
+pub fn referral_volume(deps: Deps, referrer: String) -> StdResult<Binary> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let stats = referral_stats_r(deps.storage)
+        .may_load(referrer.as_bytes())?
+        .unwrap_or_default();
+    to_binary(&QueryMsgResponse::GetReferralVolume {
+        swap_count: stats.swap_count,
+        volumes: stats.volumes,
     })
 }
@@ -1,8 +1,12 @@
 use cosmwasm_std::Addr;
 use cosmwasm_std::Storage;
 use cosmwasm_std::Uint128;
+use cosmwasm_storage::Bucket;
+use cosmwasm_storage::ReadonlyBucket;
 use cosmwasm_storage::ReadonlySingleton;
 use cosmwasm_storage::Singleton;
+use cosmwasm_storage::bucket;
+use cosmwasm_storage::bucket_read;
 use cosmwasm_storage::singleton;
 use cosmwasm_storage::singleton_read;
 use schemars::JsonSchema;
@@ -11,16 +15,31 @@ use serde::{Deserialize, Serialize};
 use shadeswap_shared::Contract;
 use shadeswap_shared::core::TokenAmount;
 use shadeswap_shared::core::TokenType;
+use shadeswap_shared::router::BatchRecipient;
 use shadeswap_shared::router::Hop;
+use shadeswap_shared::router::Referral;
+use shadeswap_shared::router::ReferralVolume;
+use shadeswap_shared::router::WrappedNativeToken;
 
 pub static CONFIG: &[u8] = b"config";
 pub static ADDED_TOKEN_LIST: &[u8] = b"added_token_list";
 pub const EPHEMERAL_STORAGE_KEY: &[u8] = b"ephemeral_storage";
+pub static REFERRAL_STATS: &[u8] = b"referral_stats";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub viewing_key: String,
-    pub admin_auth: Contract
+    pub admin_auth: Contract,
+    pub wrapped_native_token: Option<WrappedNativeToken>,
+    pub max_referral_fee_bps: u64,
+    pub trusted_factories: Vec<Contract>,
+    pub trusted_pairs: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReferralStats {
+    pub swap_count: u64,
+    pub volumes: Vec<ReferralVolume>,
 }
 
 pub fn config_w(storage: &mut dyn Storage) -> Singleton<Config> {
@@ -40,6 +59,14 @@ pub fn added_tokens_r(storage: &dyn Storage) -> ReadonlySingleton<Config> {
     singleton_read(storage, ADDED_TOKEN_LIST)
 }
 
+pub fn referral_stats_w(storage: &mut dyn Storage) -> Bucket<ReferralStats> {
+    bucket(storage, REFERRAL_STATS)
+}
+
+pub fn referral_stats_r(storage: &dyn Storage) -> ReadonlyBucket<ReferralStats> {
+    bucket_read(storage, REFERRAL_STATS)
+}
+
 pub fn epheral_storage_w(storage: &mut dyn Storage) -> Singleton<CurrentSwapInfo> {
     singleton(storage, EPHEMERAL_STORAGE_KEY)
 }
@@ -57,4 +84,10 @@ pub struct CurrentSwapInfo {
     pub current_index: u32,
     //The next token that will be in the hop
     pub next_token_in: TokenType,
+    pub max_price_impact_bps: Option<u64>,
+    pub referral: Option<Referral>,
+    //Output is split between these instead of going to recipient
+    pub recipients: Option<Vec<BatchRecipient>>,
+    //Set for cyclic swaps that must end in the offered token
+    pub min_profit: Option<Uint128>,
 }
@@ -3,6 +3,8 @@ pub mod tests {
     use crate::contract::SWAP_REPLY_ID;
     use crate::contract::execute;
     use crate::contract::instantiate;
+    use crate::contract::query;
+    use crate::operations::next_swap;
     use crate::state::Config;
     use crate::state::CurrentSwapInfo;    
     use crate::state::config_r;
@@ -13,6 +15,7 @@ pub mod tests {
     use cosmwasm_std::Response;
     use cosmwasm_std::StdResult;
     use cosmwasm_std::SubMsg;    
+    use cosmwasm_std::from_binary;
     use cosmwasm_std::from_slice;
     use cosmwasm_std::testing::mock_env;
     use cosmwasm_std::testing::MockApi;
@@ -46,10 +49,18 @@ pub mod tests {
     use shadeswap_shared::core::Fee;
     use shadeswap_shared::core::TokenAmount;
     use shadeswap_shared::core::TokenType;
+    use shadeswap_shared::router::BatchRecipient;
     use shadeswap_shared::router::ExecuteMsg;
     use shadeswap_shared::router::Hop;
     use shadeswap_shared::router::InitMsg;
     use shadeswap_shared::router::InvokeMsg;
+    use shadeswap_shared::router::QueryMsg;
+    use shadeswap_shared::router::QueryMsgResponse;
+    use shadeswap_shared::router::Referral;
+    use shadeswap_shared::router::ReferralVolume;
+    use shadeswap_shared::router::WrappedNativeToken;
+    use shadeswap_shared::snip20;
+    use shadeswap_shared::utils::ExecuteCallback;
     
     use shadeswap_shared::snip20::manager::Balance;
 
@@ -92,6 +103,8 @@ pub mod tests {
                 expected_return: None,
                 path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
                 recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
             },
         )
         .unwrap();
@@ -144,6 +157,8 @@ pub mod tests {
                 expected_return: None,
                 path: vec![Hop{addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
                 recipient: Some("sender_addr".to_string()),
+                max_price_impact_bps: None,
+                referral: None,
             },
         )
         .unwrap();
@@ -196,6 +211,10 @@ pub mod tests {
             ],
             next_token_in: TokenType::CustomToken { contract_addr: Addr::unchecked("token_1"), token_code_hash: "".to_string() },
             recipient: Addr::unchecked("recipient".to_string()),
+            max_price_impact_bps: None,
+            referral: None,
+            recipients: None,
+            min_profit: None,
             current_index: 0,
         })?;
         
@@ -210,7 +229,9 @@ pub mod tests {
                     to_binary(&InvokeMsg::SwapTokensForExact {
                         expected_return: Some(Uint128::new(1000u128)),
                         path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
-                        recipient: None
+                        recipient: None,
+                        max_price_impact_bps: None,
+                        referral: None,
                     })
                     .unwrap(),
                 ),
@@ -255,6 +276,10 @@ pub mod tests {
                 Hop{ addr: PAIR_CONTRACT_2.to_string(), code_hash: "".to_string()},
             ],
             recipient: Addr::unchecked("recipient".to_string()),
+            max_price_impact_bps: None,
+            referral: None,
+            recipients: None,
+            min_profit: None,
             current_index: 0,
             next_token_in:  TokenType::NativeToken {
                 denom: "uscrt".into(),
@@ -275,6 +300,8 @@ pub mod tests {
                 expected_return: None,
                 path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
                 recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
             }
         )
         .unwrap();
@@ -289,6 +316,7 @@ pub mod tests {
                 amount: Uint128::new(10u128),
             },
             execute_arbitrage: None,
+            max_price_impact_bps: None,
         })?;
 
         assert_eq!(result.messages[0],SubMsg::reply_always(
@@ -331,6 +359,10 @@ pub mod tests {
                     Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()},
                 ],
                 recipient: Addr::unchecked("recipient".to_string()),
+                max_price_impact_bps: None,
+                referral: None,
+                recipients: None,
+                min_profit: None,
                 current_index: 0,
                 next_token_in: TokenType::NativeToken {
                     denom: "uscrt".into(),
@@ -352,6 +384,8 @@ pub mod tests {
                 expected_return: None,
                 path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
                 recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
             },
         )
         .unwrap();
@@ -366,6 +400,7 @@ pub mod tests {
                 amount: Uint128::new(10u128),
             },
             execute_arbitrage: None,
+            max_price_impact_bps: None,
         })?;
         assert_eq!(result.messages[0],SubMsg::reply_always(
             WasmMsg::Execute {
@@ -407,6 +442,10 @@ pub mod tests {
                     Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()},
                 ],
                 recipient: Addr::unchecked("recipient".to_string()),
+                max_price_impact_bps: None,
+                referral: None,
+                recipients: None,
+                min_profit: None,
                 current_index: 0,
                 next_token_in: TokenType::NativeToken {
                     denom: "uscrt".into(),
@@ -428,6 +467,8 @@ pub mod tests {
                 expected_return: None,
                 path: vec![Hop{ addr: "token_addr".to_string(), code_hash: "".to_string()}],
                 recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
             },
         );
 
@@ -438,10 +479,444 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn swap_native_with_wrap_hop_ok() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let wrapped_native_token = WrappedNativeToken {
+            token: Contract {
+                address: Addr::unchecked(CUSTOM_TOKEN_1),
+                code_hash: "hash".to_string(),
+            },
+            denom: "uscrt".to_string(),
+        };
+        let result = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetWrappedNativeToken {
+                wrapped_native_token: Some(wrapped_native_token.clone()),
+            },
+        )?;
+        assert_eq!(result.messages.len(), 2);
+
+        let result = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[Coin{ denom: "uscrt".to_string(), amount: Uint128::new(10u128) }]),
+            ExecuteMsg::SwapTokensForExact {
+                offer: TokenAmount {
+                    token: TokenType::NativeToken {
+                        denom: "uscrt".to_string(),
+                    },
+                    amount: Uint128::new(10u128),
+                },
+                expected_return: None,
+                path: vec![
+                    Hop{ addr: CUSTOM_TOKEN_1.to_string(), code_hash: "hash".to_string()},
+                    Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()},
+                ],
+                recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
+            },
+        )?;
+
+        assert_eq!(result.messages[0], SubMsg::reply_on_success(
+            snip20::ExecuteMsg::Deposit { padding: None }.to_cosmos_msg(
+                &wrapped_native_token.token,
+                vec![Coin { denom: "uscrt".to_string(), amount: Uint128::new(10u128) }],
+            )?,
+            SWAP_REPLY_ID,
+        ));
+        let info = epheral_storage_r(&deps.storage).load()?;
+        assert_eq!(info.next_token_in, wrapped_native_token.custom_token());
+        Ok(())
+    }
+
+    #[test]
+    fn swap_with_wrap_hop_wrong_token_fail() -> StdResult<()> {
+        let (_, mut deps) = init_helper();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetWrappedNativeToken {
+                wrapped_native_token: Some(WrappedNativeToken {
+                    token: Contract {
+                        address: Addr::unchecked(CUSTOM_TOKEN_1),
+                        code_hash: "hash".to_string(),
+                    },
+                    denom: "uscrt".to_string(),
+                }),
+            },
+        )?;
+
+        let result = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[Coin{ denom: "uatom".to_string(), amount: Uint128::new(10u128) }]),
+            ExecuteMsg::SwapTokensForExact {
+                offer: TokenAmount {
+                    token: TokenType::NativeToken {
+                        denom: "uatom".to_string(),
+                    },
+                    amount: Uint128::new(10u128),
+                },
+                expected_return: None,
+                path: vec![Hop{ addr: CUSTOM_TOKEN_1.to_string(), code_hash: "hash".to_string()}],
+                recipient: None,
+                max_price_impact_bps: None,
+                referral: None,
+            },
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("Token can not be wrapped or unwrapped by this hop.")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn swap_with_referral_fee_above_max_fail() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let result = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[Coin{ denom: "uscrt".to_string(), amount: Uint128::new(10u128) }]),
+            ExecuteMsg::SwapTokensForExact {
+                offer: TokenAmount {
+                    token: TokenType::NativeToken {
+                        denom: "uscrt".to_string(),
+                    },
+                    amount: Uint128::new(10u128),
+                },
+                expected_return: None,
+                path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
+                recipient: None,
+                max_price_impact_bps: None,
+                referral: Some(Referral { address: "referrer".to_string(), fee_bps: 50 }),
+            },
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("Referral fee exceeds maximum (50 > 0 bps).")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn last_swap_with_referral_pays_fee_ok() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetConfig {
+                admin_auth: None,
+                max_referral_fee_bps: Some(500),
+                trusted_factories: None,
+                trusted_pairs: None,
+            },
+        )?;
+
+        let token_out = TokenType::CustomToken {
+            contract_addr: Addr::unchecked(CUSTOM_TOKEN_1),
+            token_code_hash: "hash".to_string(),
+        };
+        epheral_storage_w(&mut deps.storage).save(&CurrentSwapInfo {
+            amount_out_min: Some(Uint128::new(95u128)),
+            amount: TokenAmount {
+                token: TokenType::NativeToken {
+                    denom: "uscrt".to_string(),
+                },
+                amount: Uint128::new(10u128),
+            },
+            path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
+            next_token_in: token_out.clone(),
+            recipient: Addr::unchecked("recipient".to_string()),
+            max_price_impact_bps: None,
+            referral: Some(Referral { address: "referrer".to_string(), fee_bps: 500 }),
+            recipients: None,
+            min_profit: None,
+            current_index: 0,
+        })?;
+
+        // Router holds 100 of the output token, 5% goes to the referrer
+        let result = next_swap(deps.as_mut(), mock_env(), Response::new())?;
+        assert_eq!(result.messages, vec![
+            SubMsg::new(token_out.create_send_msg("".to_string(), "referrer".to_string(), Uint128::new(5u128))?),
+            SubMsg::new(token_out.create_send_msg("".to_string(), "recipient".to_string(), Uint128::new(95u128))?),
+        ]);
+
+        let volume: QueryMsgResponse = from_binary(&query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetReferralVolume { referrer: "referrer".to_string() },
+        )?)?;
+        assert_eq!(volume, QueryMsgResponse::GetReferralVolume {
+            swap_count: 1,
+            volumes: vec![ReferralVolume {
+                token: token_out,
+                volume: Uint128::new(100u128),
+                fee_amount: Uint128::new(5u128),
+            }],
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn last_swap_with_batch_recipients_splits_output_ok() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let token_out = TokenType::CustomToken {
+            contract_addr: Addr::unchecked(CUSTOM_TOKEN_1),
+            token_code_hash: "hash".to_string(),
+        };
+        epheral_storage_w(&mut deps.storage).save(&CurrentSwapInfo {
+            amount_out_min: Some(Uint128::new(100u128)),
+            amount: TokenAmount {
+                token: TokenType::NativeToken {
+                    denom: "uscrt".to_string(),
+                },
+                amount: Uint128::new(10u128),
+            },
+            path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
+            next_token_in: token_out.clone(),
+            recipient: Addr::unchecked("admin".to_string()),
+            max_price_impact_bps: None,
+            referral: None,
+            recipients: Some(vec![
+                BatchRecipient { address: "payee_a".to_string(), share: Uint128::new(1u128) },
+                BatchRecipient { address: "payee_b".to_string(), share: Uint128::new(2u128) },
+            ]),
+            min_profit: None,
+            current_index: 0,
+        })?;
+
+        // Router holds 100 of the output token, the rounding dust goes to the last payee
+        let result = next_swap(deps.as_mut(), mock_env(), Response::new())?;
+        assert_eq!(result.messages, vec![
+            SubMsg::new(token_out.create_send_msg("".to_string(), "payee_a".to_string(), Uint128::new(33u128))?),
+            SubMsg::new(token_out.create_send_msg("".to_string(), "payee_b".to_string(), Uint128::new(67u128))?),
+        ]);
+        assert_eq!(result.events.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn last_cyclic_swap_asserts_min_profit() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let token = TokenType::CustomToken {
+            contract_addr: Addr::unchecked(CUSTOM_TOKEN_1),
+            token_code_hash: "hash".to_string(),
+        };
+        let swap_info = CurrentSwapInfo {
+            amount_out_min: None,
+            amount: TokenAmount {
+                token: token.clone(),
+                amount: Uint128::new(90u128),
+            },
+            path: vec![
+                Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()},
+                Hop{ addr: PAIR_CONTRACT_2.to_string(), code_hash: "".to_string()},
+            ],
+            next_token_in: token.clone(),
+            recipient: Addr::unchecked("bot".to_string()),
+            max_price_impact_bps: None,
+            referral: None,
+            recipients: None,
+            min_profit: Some(Uint128::new(20u128)),
+            current_index: 1,
+        };
+        epheral_storage_w(&mut deps.storage).save(&swap_info)?;
+
+        // Router holds 100 after the cycle, a profit of 10
+        let result = next_swap(deps.as_mut(), mock_env(), Response::new());
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("Cyclic swap profit below minimum. Input: 90, Output: 100, Min profit: 20")
+        );
+
+        epheral_storage_w(&mut deps.storage).save(&CurrentSwapInfo {
+            min_profit: Some(Uint128::new(10u128)),
+            ..swap_info
+        })?;
+        let result = next_swap(deps.as_mut(), mock_env(), Response::new())?;
+        assert_eq!(result.messages, vec![
+            SubMsg::new(token.create_send_msg("".to_string(), "bot".to_string(), Uint128::new(100u128))?),
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn swap_batch_without_recipients_fail() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let result = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[Coin{ denom: "uscrt".to_string(), amount: Uint128::new(10u128) }]),
+            ExecuteMsg::SwapTokensForExactBatch {
+                offer: TokenAmount {
+                    token: TokenType::NativeToken {
+                        denom: "uscrt".to_string(),
+                    },
+                    amount: Uint128::new(10u128),
+                },
+                expected_return: None,
+                path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
+                recipients: vec![],
+                max_price_impact_bps: None,
+            },
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("Batch must have between 1 and 50 recipients.")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn swap_through_untrusted_pair_fail() -> StdResult<()> {
+        let (init_result, mut deps) = init_helper();
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetConfig {
+                admin_auth: None,
+                max_referral_fee_bps: None,
+                trusted_factories: Some(vec![Contract {
+                    address: Addr::unchecked(FACTORY_ADDRESS),
+                    code_hash: "".to_string(),
+                }]),
+                trusted_pairs: Some(vec![]),
+            },
+        )?;
+
+        let swap_msg = ExecuteMsg::SwapTokensForExact {
+            offer: TokenAmount {
+                token: TokenType::NativeToken {
+                    denom: "uscrt".to_string(),
+                },
+                amount: Uint128::new(10u128),
+            },
+            expected_return: None,
+            path: vec![Hop{ addr: PAIR_CONTRACT_1.to_string(), code_hash: "".to_string()}],
+            recipient: None,
+            max_price_impact_bps: None,
+            referral: None,
+        };
+        let coins = [Coin{ denom: "uscrt".to_string(), amount: Uint128::new(10u128) }];
+
+        // The pair reports a factory that is not trusted
+        let result = execute(deps.as_mut(), mock_env(), mock_info("admin", &coins), swap_msg.clone());
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err(format!("Hop {} is not a pair of a trusted factory.", PAIR_CONTRACT_1))
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetConfig {
+                admin_auth: None,
+                max_referral_fee_bps: None,
+                trusted_factories: None,
+                trusted_pairs: Some(vec![PAIR_CONTRACT_1.to_string()]),
+            },
+        )?;
+        assert!(
+            execute(deps.as_mut(), mock_env(), mock_info("admin", &coins), swap_msg.clone()).is_ok()
+        );
+
+        // A pair list alone still restricts hops
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetConfig {
+                admin_auth: None,
+                max_referral_fee_bps: None,
+                trusted_factories: Some(vec![]),
+                trusted_pairs: Some(vec![PAIR_CONTRACT_2.to_string()]),
+            },
+        )?;
+        let result = execute(deps.as_mut(), mock_env(), mock_info("admin", &coins), swap_msg.clone());
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err(format!("Hop {} is not a pair of a trusted factory.", PAIR_CONTRACT_1))
+        );
+
+        // Without any trust source every pair hop is rejected
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::SetConfig {
+                admin_auth: None,
+                max_referral_fee_bps: None,
+                trusted_factories: Some(vec![]),
+                trusted_pairs: Some(vec![]),
+            },
+        )?;
+        let result = execute(deps.as_mut(), mock_env(), mock_info("admin", &coins), swap_msg);
+        assert_eq!(
+            result.unwrap_err(),
+            StdError::generic_err("No trusted factories or pairs are configured.")
+        );
+        Ok(())
+    }
+
     fn mkconfig(_env: Env, _id: u64) -> Config {
         Config{
             viewing_key: "SHADE_ROUTER_KEY".to_string(),
             admin_auth: Contract{address: Addr::unchecked("".to_string()), code_hash: "".to_string()},
+            wrapped_native_token: None,
+            max_referral_fee_bps: 0,
+            trusted_factories: vec![],
+            trusted_pairs: vec![],
         }       
     }
     fn mkdeps() -> OwnedDeps<impl Storage, impl Api, impl Querier> {
@@ -476,7 +951,23 @@ pub mod tests {
             },
         };
 
-        (instantiate(deps.as_mut(), env, mock_info("admin", &[]), init_msg), deps)
+        let result = instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), init_msg);
+        if result.is_ok() {
+            // Trust the mocked pairs, hops are rejected until a trust source is configured
+            execute(
+                deps.as_mut(),
+                env,
+                mock_info("admin", &[]),
+                ExecuteMsg::SetConfig {
+                    admin_auth: None,
+                    max_referral_fee_bps: None,
+                    trusted_factories: None,
+                    trusted_pairs: Some(vec![PAIR_CONTRACT_1.to_string(), PAIR_CONTRACT_2.to_string()]),
+                },
+            )
+            .unwrap();
+        }
+        (result, deps)
     }
 
     pub fn mock_dependencies(
@@ -528,6 +1019,7 @@ pub mod tests {
                                     },
                                     authenticator: None,
                                     admin_auth: Contract { address: Addr::unchecked("admin_auth".to_string()), code_hash: "".to_string() },
+                                    creation_policy: shadeswap_shared::msg::factory::CreationPolicy::AdminOnly,
                                 }).unwrap()))
                             }
                             "admin_auth" => {
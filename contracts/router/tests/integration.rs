@@ -111,11 +111,9 @@ pub fn router_integration_tests() {
         &create_staking_info_contract(
             staking_info.code_id, 
             &staking_info.code_hash, 
-            Uint128::new(30000u128), 
             TokenType::CustomToken { 
                 contract_addr: reward_contract.address.clone(), 
                 token_code_hash: reward_contract.code_hash.clone() },
-            Uint128::new(30000000000u128),
             None
         ),
         &router_contract, 
@@ -205,6 +203,8 @@ pub fn router_integration_tests() {
             shade_dao_fee_amount,
             result,
             price ,
+            mid_price: _,
+            price_impact_bps: _,
         } => {
         // Verify result not actual amount
            assert_ne!(total_fee_amount, Uint128::zero());
@@ -222,6 +222,7 @@ pub fn router_integration_tests() {
         expected_return: Some(Uint128::new(100u128)), 
         to: Some(staker_a_addr.to_string()),
         execute_arbitrage: None,
+        max_price_impact_bps: None,
     }).unwrap();
    
     let msg = snip20_reference_impl::msg::ExecuteMsg::Send {
@@ -247,7 +248,9 @@ pub fn router_integration_tests() {
         offer:offer.to_owned(),
         expected_return: Some(Uint128::new(1000u128)), 
         path: vec![Hop{addr: amm_pairs[0].address.to_string(), code_hash: amm_contract_info.code_hash.clone()}],
-        recipient: Some(owner_addr.to_string())
+        recipient: Some(owner_addr.to_string()),
+        max_price_impact_bps: None,
+        referral: None,
     };
 
     let _response = router.execute_contract(
@@ -276,11 +279,9 @@ pub fn router_integration_tests() {
         &create_staking_info_contract(
             staking_info.code_id, 
             &staking_info.code_hash, 
-            Uint128::new(30000u128), 
             TokenType::CustomToken { 
                 contract_addr: reward_contract.address.clone(), 
                 token_code_hash: reward_contract.code_hash.clone() },
-            Uint128::new(30000000000u128),
             None
         ),
         &router_contract, 
@@ -326,7 +327,9 @@ pub fn router_integration_tests() {
         offer:native_offer.to_owned(),
         expected_return: Some(Uint128::new(100u128)), 
         path: vec![Hop{addr: amm_pairs[1].address.to_string(), code_hash: amm_contract_info.code_hash.clone()}],
-        recipient:None
+        recipient:None,
+        max_price_impact_bps: None,
+        referral: None,
     };
     
     let _response = router.execute_contract(
@@ -1,27 +1,38 @@
 use cosmwasm_std::{
     entry_point, from_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Deps,
-    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsgResult, Uint128,
 };
 use shadeswap_shared::{
     core::{TokenType},
     query_auth::helpers::{authenticate_permit, PermitAuthentication},
     snip20::helpers::{send_msg, register_receive},
-    staking::{AuthQuery, ExecuteMsg, InitMsg, InvokeMsg, QueryData, QueryMsg},
-    utils::{pad_query_result, pad_response_result},
+    staking::{AuthQuery, ExecuteMsg, InitMsg, InvokeMsg, MigrateMsg, QueryData, QueryMsg},
+    utils::{
+        migrate::{assert_can_migrate, set_contract_version},
+        pad_query_result, pad_response_result,
+    },
     Contract, admin::helpers::{validate_admin, AdminPermissions},
 };
 
 use crate::{
     operations::{
-        claim_rewards, set_reward_token, stake,
-        unstake, update_authenticator, 
+        assert_not_emergency_mode, authenticate_viewing_key, claim_rewards, claim_unbonded,
+        create_receipt_token, create_staker_viewing_key, emergency_unstake, fund_rewards,
+        lock_stake, register_receipt_token, remove_reward_token, set_lock_config,
+        set_reward_funders, set_reward_recipient, set_reward_token, set_staker_viewing_key, stake,
+        stake_with_receipt, transfer_stake, unlock, unstake, unstake_receipt, update_authenticator,
     },
     query,
-    state::{config_r, config_w, prng_seed_w, Config, reward_token_list_w},
+    state::{
+        config_r, config_w, emergency_mode_w, prng_seed_w, Config, reward_token_list_w,
+        unbonding_period_w,
+    },
 };
 
 pub const BLOCK_SIZE: usize = 256;
 pub const SHADE_STAKING_VIEWKEY: &str = "SHADE_STAKING_VIEWKEY";
+pub const STAKING_CONTRACT_VERSION: u32 = 1;
+pub const INSTANTIATE_RECEIPT_TOKEN_REPLY_ID: u64 = 1u64;
 
 #[entry_point]
 pub fn instantiate(
@@ -32,14 +43,15 @@ pub fn instantiate(
 ) -> StdResult<Response> {
     let config = Config {
         amm_pair: info.sender.clone(),
-        daily_reward_amount: msg.daily_reward_amount,
         reward_token: msg.reward_token.to_owned(),
         lp_token: msg.lp_token.clone(),
         authenticator: msg.authenticator,
         admin_auth: msg.admin_auth,
+        reward_funders: None,
     };
     config_w(deps.storage).save(&config)?;
     prng_seed_w(deps.storage).save(&msg.prng_seed.as_slice().to_vec())?;
+    set_contract_version(deps.storage, STAKING_CONTRACT_VERSION)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
 
@@ -68,17 +80,29 @@ pub fn instantiate(
     let reward_token_list: Vec<String> = Vec::new();
     reward_token_list_w(deps.storage).save(&reward_token_list)?;
 
-    set_reward_token(deps, &env, msg.daily_reward_amount, msg.reward_token, msg.valid_to)?;
+    let reward_token_response = set_reward_token(deps, &env, msg.reward_token)?;
+    messages.extend(reward_token_response.messages.into_iter().map(|sub_msg| sub_msg.msg));
 
     let mut response = Response::new();
     response.data = Some(env.contract.address.as_bytes().into());
     Ok(response.add_messages(messages).add_attributes(vec![
         Attribute::new("staking_contract_addr", env.contract.address),
         Attribute::new("reward_token", reward_token_address.address.to_string()),
-        Attribute::new("daily_reward_amount", msg.daily_reward_amount),
     ]))
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let stored_version = assert_can_migrate(deps.storage, STAKING_CONTRACT_VERSION)?;
+    // No stored state has changed between versions yet, so only the version is recorded
+    set_contract_version(deps.storage, STAKING_CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", STAKING_CONTRACT_VERSION.to_string()))
+}
+
 #[entry_point]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     pad_response_result(
@@ -94,11 +118,23 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 let checked_from = deps.api.addr_validate(&from)?;
                 receiver_callback(deps, env, info, checked_from, amount, msg)
             },
-            ExecuteMsg::ClaimRewards {} => claim_rewards(deps, &info.sender, &env),
+            ExecuteMsg::ClaimRewards { recipient, tokens } => {
+                let checked_recipient = recipient
+                    .map(|recipient| deps.api.addr_validate(&recipient))
+                    .transpose()?;
+                claim_rewards(deps, &info.sender, &env, checked_recipient, tokens)
+            }
+            ExecuteMsg::SetRewardRecipient { recipient } => {
+                set_reward_recipient(deps, &info.sender, recipient)
+            }
             ExecuteMsg::Unstake {
                 amount,
                 remove_liquidity,
             } => unstake(deps, &env, &info.sender,  &info.sender, amount, remove_liquidity),
+            ExecuteMsg::Unlock {
+                position_id,
+                remove_liquidity,
+            } => unlock(deps, &env, &info.sender, position_id, remove_liquidity),
             ExecuteMsg::SetAuthenticator { authenticator } => {
                 let config = config_r(deps.storage).load()?;
                 validate_admin(
@@ -109,7 +145,10 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 )?;
                 update_authenticator(deps.storage, authenticator)
             }
-            ExecuteMsg::SetConfig { admin_auth } => {
+            ExecuteMsg::SetConfig {
+                admin_auth,
+                unbonding_period,
+            } => {
                 let mut config = config_r(deps.storage).load()?;
                 validate_admin(
                     &deps.querier,
@@ -120,12 +159,34 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 if let Some(admin_auth) = admin_auth {
                     config.admin_auth = admin_auth;
                 }
+                config_w(deps.storage).save(&config)?;
+                // Only applies to LP token unstaked from now on
+                if let Some(unbonding_period) = unbonding_period {
+                    unbonding_period_w(deps.storage).save(&unbonding_period)?;
+                }
                 Ok(Response::default())
             }
-            ExecuteMsg::SetRewardToken {
-                reward_token,
-                daily_reward_amount,
-                valid_to,
+            ExecuteMsg::ClaimUnbonded {} => claim_unbonded(deps, &env, &info.sender),
+            ExecuteMsg::EmergencyUnstake { amount } => {
+                emergency_unstake(deps, &env, &info.sender, amount)
+            }
+            ExecuteMsg::SetEmergencyMode { enabled } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                emergency_mode_w(deps.storage).save(&enabled)?;
+                Ok(Response::new().add_attributes(vec![
+                    Attribute::new("action", "set_emergency_mode"),
+                    Attribute::new("enabled", enabled.to_string()),
+                ]))
+            }
+            ExecuteMsg::CreateReceiptToken {
+                contract_info,
+                custom_label,
             } => {
                 let config = config_r(deps.storage).load()?;
                 validate_admin(
@@ -134,7 +195,58 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     &info.sender,
                     &config.admin_auth,
                 )?;
-                set_reward_token(deps, &env,  daily_reward_amount, reward_token ,valid_to)
+                create_receipt_token(deps, &env, contract_info, custom_label)
+            }
+            ExecuteMsg::CreateViewingKey { entropy } => {
+                create_staker_viewing_key(deps.storage, &env, &info, entropy)
+            }
+            ExecuteMsg::SetViewingKey { key } => {
+                set_staker_viewing_key(deps.storage, &info.sender, key)
+            }
+            ExecuteMsg::SetRewardToken { reward_token } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_reward_token(deps, &env, reward_token)
+            }
+            ExecuteMsg::RemoveRewardToken { token, refund_to } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                let checked_refund_to = deps.api.addr_validate(&refund_to)?;
+                remove_reward_token(deps, &env, token, &checked_refund_to)
+            }
+            ExecuteMsg::SetRewardFunders { funders } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_reward_funders(deps, funders)
+            }
+            ExecuteMsg::SetLockConfig {
+                tiers,
+                early_unlock_penalty,
+                penalty_recipient,
+            } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_lock_config(deps, tiers, early_unlock_penalty, penalty_recipient)
             }
             // This can be used by admins to recover any funds that were sent accidentally to staking contract.
             ExecuteMsg::RecoverFunds {
@@ -190,9 +302,14 @@ fn receiver_callback(
         StdError::generic_err("Receiver callback \"msg\" parameter cannot be empty.")
     })?;
 
+    let invoke_msg: InvokeMsg = from_binary(&msg)?;
+    // Receipt tokens stay redeemable in emergency mode
+    if !matches!(invoke_msg, InvokeMsg::UnstakeReceipt { .. }) {
+        assert_not_emergency_mode(deps.storage)?;
+    }
     let config = config_r(deps.storage).load()?;
     pad_response_result(
-        match from_binary(&msg)? {
+        match invoke_msg {
             InvokeMsg::Stake { from } => {
                 if config.lp_token.address != info.sender {
                     return Err(StdError::generic_err("Sender was not LP Token".to_string()));
@@ -208,6 +325,46 @@ fn receiver_callback(
                 let checked_for_addr = deps.api.addr_validate(&for_addr)?;
                 stake(deps, &env, &info, amount, &from, &checked_for_addr)
             }
+            InvokeMsg::FundRewards { duration } => {
+                fund_rewards(deps, &env, &info.sender, &from, amount, duration)
+            }
+            InvokeMsg::LockStake { duration } => {
+                if config.lp_token.address != info.sender {
+                    return Err(StdError::generic_err("Sender was not LP Token".to_string()));
+                }
+                lock_stake(deps, &env, &info, amount, &from, duration)
+            }
+            InvokeMsg::StakeWithReceipt {} => {
+                if config.lp_token.address != info.sender {
+                    return Err(StdError::generic_err("Sender was not LP Token".to_string()));
+                }
+                stake_with_receipt(deps, &env, &info, amount, &from)
+            }
+            InvokeMsg::TransferStake { recipient } => {
+                let checked_recipient = deps.api.addr_validate(&recipient)?;
+                transfer_stake(deps, &env, &info, &from, &checked_recipient, amount)
+            }
+            InvokeMsg::UnstakeReceipt { remove_liquidity } => {
+                unstake_receipt(deps, &env, &info, &from, amount, remove_liquidity)
+            }
+        },
+        BLOCK_SIZE,
+    )
+}
+
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    pad_response_result(
+        match (msg.id, msg.result) {
+            (INSTANTIATE_RECEIPT_TOKEN_REPLY_ID, SubMsgResult::Ok(s)) => match s.data {
+                Some(x) => {
+                    let contract_address =
+                        deps.api.addr_validate(&String::from_utf8(x.to_vec())?)?;
+                    register_receipt_token(deps.storage, &env, contract_address)
+                }
+                None => Err(StdError::generic_err("Unknown reply id")),
+            },
+            _ => Err(StdError::generic_err("Unknown reply id")),
         },
         BLOCK_SIZE,
     )
@@ -229,15 +386,31 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 
                 auth_queries(deps, env, query, res.sender)
             },
-            QueryMsg::GetRewardTokens {  } => query::reward_token_list(deps.storage),
+            QueryMsg::WithViewingKey {
+                address,
+                key,
+                query,
+            } => {
+                let checked_address = deps.api.addr_validate(&address)?;
+                authenticate_viewing_key(deps.storage, &checked_address, key)?;
+                auth_queries(deps, env, query, checked_address)
+            }
+            QueryMsg::GetRewardTokens { include_retired } => {
+                query::reward_token_list(deps.storage, include_retired.unwrap_or(false))
+            }
+            QueryMsg::GetRewardPrograms {} => query::reward_programs(deps, &env),
+            QueryMsg::GetLockConfig {} => query::lock_config(deps),
         },
         BLOCK_SIZE,
     )
 }
 
-pub fn auth_queries(deps: Deps, _env: Env, msg: AuthQuery, user: Addr) -> StdResult<Binary> {
+pub fn auth_queries(deps: Deps, env: Env, msg: AuthQuery, user: Addr) -> StdResult<Binary> {
     match msg {
         AuthQuery::GetClaimReward { time } => query::claim_reward_for_user(deps, user, time),
         AuthQuery::GetStakerLpTokenInfo {} => query::staking_stake_lp_token_info(deps, user),       
+        AuthQuery::GetLockPositions {} => query::lock_positions(deps, user),
+        AuthQuery::GetUnbondings {} => query::unbondings(deps, &env, user),
+        AuthQuery::GetRewardRecipient {} => query::reward_recipient(deps, user),
     }
 }
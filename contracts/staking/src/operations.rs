@@ -1,48 +1,35 @@
 use cosmwasm_std::{
-    to_binary, Addr, Attribute, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    to_binary, Addr, Attribute, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
-use shadeswap_shared::core::TokenType;
+use shadeswap_shared::core::{ContractInstantiationInfo, TokenType, ViewingKey, VIEWING_KEY_SIZE};
+use shadeswap_shared::lp_token::{InitConfig, InstantiateMsg as LpTokenInstantiateMsg};
 use shadeswap_shared::snip20;
-use shadeswap_shared::snip20::helpers::set_viewing_key_msg;
-use shadeswap_shared::staking::RewardTokenInfo;
+use shadeswap_shared::snip20::helpers::{
+    burn_msg, mint_msg, register_receive, set_viewing_key_msg, token_info,
+};
+use shadeswap_shared::staking::{
+    ExecuteMsgResponse, LockConfig, LockPosition, LockTier, RetiredRewardToken, RewardTokenInfo,
+    Unbonding,
+};
 use shadeswap_shared::utils::ExecuteCallback;
 use shadeswap_shared::{msg::amm_pair::InvokeMsg as AmmPairInvokeMsg, Contract};
-const SECONDS_IN_DAY: Uint128 = Uint128::new(24u128 * 60u128 * 60u128);
 pub const MAX_DECIMALS: Uint128 = Uint128::new(1_000_000_000_000_000_000);
+pub const SECONDS_IN_MONTH: u64 = 30 * 24 * 60 * 60;
 
-use crate::contract::SHADE_STAKING_VIEWKEY;
+use crate::contract::{INSTANTIATE_RECEIPT_TOKEN_REPLY_ID, SHADE_STAKING_VIEWKEY};
 use crate::state::{
-    claim_reward_info_r, claim_reward_info_w, config_r, config_w, proxy_staker_info_r,
-    proxy_staker_info_w, reward_token_list_r, reward_token_list_w, reward_token_r, reward_token_w,
-    stakers_r, stakers_w, total_staked_r, total_staked_w, ClaimRewardsInfo, ProxyStakingInfo,
-    StakingInfo,
+    claim_reward_info_r, claim_reward_info_w, config_r, config_w, emergency_mode_r, lock_config_r,
+    lock_config_w, lock_positions_r, lock_positions_w, prng_seed_r, proxy_staker_info_r,
+    proxy_staker_info_w, receipt_token_r, receipt_token_w, resumed_reward_per_token_r,
+    resumed_reward_per_token_w, retired_reward_tokens_r, retired_reward_tokens_w,
+    retired_rewards_settled_r, retired_rewards_settled_w, reward_recipients_r, reward_recipients_w,
+    reward_token_list_r, reward_token_list_w, reward_token_r, reward_token_retirements_r,
+    reward_token_retirements_w, reward_token_w, stakers_r, stakers_vk_r, stakers_vk_w, stakers_w,
+    total_boost_r, total_boost_w, total_staked_r, total_staked_w, unbonding_period_r, unbondings_r,
+    unbondings_w, ClaimRewardsInfo, Config, ProxyStakingInfo, ReceiptToken, StakingInfo,
 };
 
-/// Store init reward token with timestamp
-pub fn store_init_reward_token_and_timestamp(
-    storage: &mut dyn Storage,
-    reward_token: TokenType,
-    daily_emission_amount: Uint128,
-    current_timestamp: Uint128,
-) -> StdResult<()> {
-    // store reward token to the list
-    let mut reward_token_list: Vec<String> = Vec::new();
-    reward_token_list.push(reward_token.unique_key());
-    reward_token_list_w(storage).save(&reward_token_list)?;
-    reward_token_w(storage).save(
-        &reward_token.unique_key().as_bytes(),
-        &RewardTokenInfo {
-            reward_token: reward_token.to_owned(),
-            reward_rate: daily_emission_amount.checked_div(SECONDS_IN_DAY)?,
-            valid_to: current_timestamp,
-            reward_per_token_stored: Uint128::zero(),
-            last_update_time: current_timestamp,
-        },
-    )?;
-    Ok(())
-}
-
 /// Stake
 pub fn stake(
     deps: DepsMut,
@@ -89,6 +76,8 @@ pub fn stake(
                     &StakingInfo {
                         amount,
                         proxy_staked: amount,
+                        locked: Uint128::zero(),
+                        boost: Uint128::zero(),
                     },
                 )?;
             } else {
@@ -97,6 +86,8 @@ pub fn stake(
                     &StakingInfo {
                         amount,
                         proxy_staked: Uint128::zero(),
+                        locked: Uint128::zero(),
+                        boost: Uint128::zero(),
                     },
                 )?;
             }
@@ -127,18 +118,281 @@ pub fn stake(
     ]))
 }
 
+/// Stake and lock the amount for the lock tier matching duration
+pub fn lock_stake(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    amount: Uint128,
+    staker: &Addr,
+    duration: u64,
+) -> StdResult<Response> {
+    let lock_config = load_lock_config(deps.storage)?;
+    let tier = match lock_config.tiers.iter().find(|tier| tier.duration == duration) {
+        Some(tier) => tier.clone(),
+        None => {
+            return Err(StdError::generic_err(format!(
+                "No lock tier with a duration of {} seconds.",
+                duration
+            )))
+        }
+    };
+
+    let response = stake(deps.branch(), env, info, amount, staker, staker)?;
+
+    let weight = amount * tier.multiplier;
+    let boost = weight.checked_sub(amount)?;
+    let mut staker_info = stakers_r(deps.storage).load(staker.as_bytes())?;
+    staker_info.locked += amount;
+    staker_info.boost += boost;
+    stakers_w(deps.storage).save(staker.as_bytes(), &staker_info)?;
+    let total_boost = total_boost_r(deps.storage).may_load()?.unwrap_or_default();
+    total_boost_w(deps.storage).save(&(total_boost + boost))?;
+
+    let current_timestamp = env.block.time.seconds();
+    let mut locks = lock_positions_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .unwrap_or_default();
+    let position = LockPosition {
+        id: locks.next_id,
+        amount,
+        weight,
+        locked_at: current_timestamp,
+        unlock_at: current_timestamp + duration,
+    };
+    locks.next_id += 1;
+    locks.positions.push(position.clone());
+    lock_positions_w(deps.storage).save(staker.as_bytes(), &locks)?;
+
+    Ok(response.add_attributes(vec![
+        Attribute::new("lock_position_id", position.id.to_string()),
+        Attribute::new("weight", weight),
+        Attribute::new("unlock_at", position.unlock_at.to_string()),
+    ]))
+}
+
+/// Release a lock position, charging the early unlock penalty before unlock_at
+pub fn unlock(
+    deps: DepsMut,
+    env: &Env,
+    staker: &Addr,
+    position_id: u64,
+    remove_liquidity: Option<bool>,
+) -> StdResult<Response> {
+    let mut locks = lock_positions_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .unwrap_or_default();
+    let index = match locks.positions.iter().position(|p| p.id == position_id) {
+        Some(index) => index,
+        None => {
+            return Err(StdError::generic_err(format!(
+                "Lock position {} does not exist.",
+                position_id
+            )))
+        }
+    };
+
+    update_reward(staker, deps.storage, env)?;
+
+    let position = locks.positions.remove(index);
+    lock_positions_w(deps.storage).save(staker.as_bytes(), &locks)?;
+
+    let boost = position.weight.checked_sub(position.amount)?;
+    let mut staker_info = stakers_r(deps.storage).load(staker.as_bytes())?;
+    staker_info.amount = staker_info.amount.checked_sub(position.amount)?;
+    staker_info.locked = staker_info.locked.checked_sub(position.amount)?;
+    staker_info.boost = staker_info.boost.checked_sub(boost)?;
+    stakers_w(deps.storage).save(staker.as_bytes(), &staker_info)?;
+    let total_staked = total_staked_r(deps.storage).load()?;
+    total_staked_w(deps.storage).save(&total_staked.checked_sub(position.amount)?)?;
+    let total_boost = total_boost_r(deps.storage).may_load()?.unwrap_or_default();
+    total_boost_w(deps.storage).save(&total_boost.checked_sub(boost)?)?;
+
+    let recipient = reward_recipient(deps.storage, staker)?;
+    let mut response = get_rewards(deps.storage, staker, &recipient, None, env, &Response::new())?;
+
+    let config = config_r(deps.storage).load()?;
+    let lock_config = load_lock_config(deps.storage)?;
+    let current_timestamp = env.block.time.seconds();
+    let mut penalty = Uint128::zero();
+    if current_timestamp < position.unlock_at {
+        penalty = position.amount
+            * (lock_config.early_unlock_penalty
+                * Decimal::from_ratio(
+                    position.unlock_at - current_timestamp,
+                    position.unlock_at - position.locked_at,
+                ));
+    }
+    if !penalty.is_zero() {
+        match &lock_config.penalty_recipient {
+            Some(recipient) => {
+                response = response.add_message(
+                    snip20::ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount: penalty,
+                        memo: None,
+                        padding: None,
+                    }
+                    .to_cosmos_msg(&config.lp_token, vec![])?,
+                );
+            }
+            None => {
+                // Nobody is left to receive the penalty, so it is waived
+                if !distribute_penalty(deps.storage, &config.lp_token, penalty)? {
+                    penalty = Uint128::zero();
+                }
+            }
+        }
+    }
+
+    let amount = position.amount.checked_sub(penalty)?;
+    if !amount.is_zero() {
+        if let Some(msg) =
+            withdraw_lp_token(deps.storage, env, &config, staker, amount, remove_liquidity)?
+        {
+            response = response.add_message(msg);
+        }
+    }
+
+    Ok(response.add_attributes(vec![
+        Attribute::new("action", "unlock"),
+        Attribute::new("lock_position_id", position_id.to_string()),
+        Attribute::new("amount", amount),
+        Attribute::new("penalty", penalty),
+        Attribute::new("staker", staker.as_str()),
+    ]))
+}
+
+/// Add a penalty paid in LP token to the rewards of the remaining stakers.
+/// The LP token must be registered as a reward token with SetRewardToken.
+/// Returns false when there is no staker left to receive it.
+fn distribute_penalty(
+    storage: &mut dyn Storage,
+    lp_token: &Contract,
+    amount: Uint128,
+) -> StdResult<bool> {
+    let total_weight = total_weight(storage)?;
+    if total_weight.is_zero() {
+        return Ok(false);
+    }
+    let lp_reward_token = TokenType::CustomToken {
+        contract_addr: lp_token.address.to_owned(),
+        token_code_hash: lp_token.code_hash.to_owned(),
+    };
+    let key = lp_reward_token.unique_key();
+    let mut token_info = match reward_token_r(storage).may_load(key.as_bytes())? {
+        Some(token_info) => token_info,
+        None => {
+            return Err(StdError::generic_err(
+                "Register the LP token as a reward token or set a penalty recipient to unlock early.",
+            ))
+        }
+    };
+    token_info.reward_per_token_stored = token_info.reward_per_token_stored.checked_add(
+        amount
+            .checked_mul(MAX_DECIMALS)?
+            .checked_div(total_weight)?,
+    )?;
+    reward_token_w(storage).save(key.as_bytes(), &token_info)?;
+    Ok(true)
+}
+
+/// Lock tiers offered when none were configured by the admin
+pub fn default_lock_config() -> LockConfig {
+    LockConfig {
+        tiers: vec![
+            LockTier {
+                duration: SECONDS_IN_MONTH,
+                multiplier: Decimal::percent(110),
+            },
+            LockTier {
+                duration: 3 * SECONDS_IN_MONTH,
+                multiplier: Decimal::percent(125),
+            },
+            LockTier {
+                duration: 6 * SECONDS_IN_MONTH,
+                multiplier: Decimal::percent(150),
+            },
+            LockTier {
+                duration: 12 * SECONDS_IN_MONTH,
+                multiplier: Decimal::percent(200),
+            },
+        ],
+        early_unlock_penalty: Decimal::percent(50),
+        penalty_recipient: None,
+    }
+}
+
+pub fn load_lock_config(storage: &dyn Storage) -> StdResult<LockConfig> {
+    Ok(lock_config_r(storage)
+        .may_load()?
+        .unwrap_or_else(default_lock_config))
+}
+
+/// Replace the lock tiers and early unlock penalty, existing positions keep their weight
+pub fn set_lock_config(
+    deps: DepsMut,
+    tiers: Vec<LockTier>,
+    early_unlock_penalty: Decimal,
+    penalty_recipient: Option<String>,
+) -> StdResult<Response> {
+    for tier in &tiers {
+        if tier.duration == 0 {
+            return Err(StdError::generic_err(
+                "Lock duration must be greater than zero.",
+            ));
+        }
+        if tier.multiplier < Decimal::one() {
+            return Err(StdError::generic_err("Lock multiplier must be at least 1."));
+        }
+    }
+    if early_unlock_penalty > Decimal::one() {
+        return Err(StdError::generic_err(
+            "Early unlock penalty can not exceed 100%.",
+        ));
+    }
+    let penalty_recipient = match penalty_recipient {
+        Some(recipient) => Some(deps.api.addr_validate(&recipient)?),
+        None => None,
+    };
+    lock_config_w(deps.storage).save(&LockConfig {
+        tiers,
+        early_unlock_penalty,
+        penalty_recipient,
+    })?;
+    Ok(Response::new().add_attribute("action", "set_lock_config"))
+}
+
 /// Generate proxy Staking Key for Proxy Staker
 pub fn generate_proxy_staking_key(from: &Addr, for_addr: &Addr) -> Vec<u8> {
     [from.as_bytes(), for_addr.as_bytes()].concat()
 }
 
+/// Pay the claimer's accrued rewards to recipient, limited to tokens when given
 fn get_rewards(
     storage: &mut dyn Storage,
     claimer: &Addr,
+    recipient: &Addr,
+    tokens: Option<Vec<String>>,
     env: &Env,
     response: &Response,
 ) -> StdResult<Response> {
-    let reward_list = reward_token_list_r(storage).load()?;
+    let mut retired_tokens = retired_reward_tokens_r(storage)
+        .may_load()?
+        .unwrap_or_default();
+    let reward_list = match tokens {
+        Some(tokens) => tokens,
+        None => {
+            let mut reward_list = reward_token_list_r(storage).load()?;
+            reward_list.extend(
+                retired_tokens
+                    .iter()
+                    .filter(|retired| !retired.unclaimed.is_zero())
+                    .map(|retired| retired.reward_token.unique_key()),
+            );
+            reward_list
+        }
+    };
     let mut claim_response = response.clone();
     for addr in &reward_list {
         let key = get_user_claim_key(claimer.to_string(), addr.to_string());
@@ -155,7 +409,7 @@ fn get_rewards(
                     claim_response = claim_response
                         .add_message(claim_info.reward_token.create_send_msg(
                             env.contract.address.to_string(),
-                            claimer.to_string(),
+                            recipient.to_string(),
                             total,
                         )?)
                         .add_attributes(vec![
@@ -165,11 +419,20 @@ fn get_rewards(
                     let mut new_data = claim_info.clone();
                     new_data.rewards = Uint128::zero();
                     claim_reward_info_w(storage).save(key.as_bytes(), &new_data)?;
+                    if let Some(retired) = retired_tokens
+                        .iter_mut()
+                        .find(|retired| retired.reward_token.unique_key() == *addr)
+                    {
+                        retired.unclaimed = retired.unclaimed.checked_sub(total)?;
+                    }
                 }
             }
             None => (),
         }
     }
+    if !retired_tokens.is_empty() {
+        save_retired_reward_tokens(storage, retired_tokens)?;
+    }
     Ok(claim_response)
 }
 
@@ -178,92 +441,397 @@ pub fn claim_rewards(
     deps: DepsMut,
     claimer: &Addr,
     env: &Env,
+    recipient: Option<Addr>,
+    tokens: Option<Vec<TokenType>>,
 ) -> StdResult<Response> {
-    let receiver = claimer.clone();
-    update_reward(&receiver, deps.storage, &env)?;
+    let recipient = match recipient {
+        Some(recipient) => recipient,
+        None => reward_recipient(deps.storage, claimer)?,
+    };
+    let tokens = match tokens {
+        Some(tokens) => {
+            let mut keys: Vec<String> = vec![];
+            for token in tokens {
+                let key = token.unique_key();
+                if reward_token_r(deps.storage).may_load(key.as_bytes())?.is_none()
+                    && find_retired_reward_token(deps.storage, &key)?.is_none()
+                {
+                    return Err(StdError::generic_err(format!(
+                        "{} is not a reward token.",
+                        key
+                    )));
+                }
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+            Some(keys)
+        }
+        None => None,
+    };
+
+    update_reward(claimer, deps.storage, &env)?;
 
-    let response = get_rewards(deps.storage, claimer, env, &Response::new())?;
+    let response = get_rewards(
+        deps.storage,
+        claimer,
+        &recipient,
+        tokens,
+        env,
+        &Response::new(),
+    )?;
     Ok(response.add_attributes(vec![
         Attribute::new("action", "claim_rewards"),
         Attribute::new("caller", claimer.to_string()),
+        Attribute::new("recipient", recipient.to_string()),
+    ]))
+}
+
+/// Set where the staker's rewards are paid, None pays the staker
+pub fn set_reward_recipient(
+    deps: DepsMut,
+    staker: &Addr,
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    match recipient {
+        Some(recipient) => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            reward_recipients_w(deps.storage).save(staker.as_bytes(), &recipient)?;
+        }
+        None => reward_recipients_w(deps.storage).remove(staker.as_bytes()),
+    }
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "set_reward_recipient"),
+        Attribute::new("staker", staker.as_str()),
     ]))
 }
 
+pub fn reward_recipient(storage: &dyn Storage, staker: &Addr) -> StdResult<Addr> {
+    Ok(reward_recipients_r(storage)
+        .may_load(staker.as_bytes())?
+        .unwrap_or_else(|| staker.clone()))
+}
+
+/// Register a reward token, it only emits once funded
 pub fn set_reward_token(
     deps: DepsMut,
     env: &Env,
-    daily_reward_amount: Uint128,
     reward_token: TokenType,
-    valid_to: Uint128,
 ) -> StdResult<Response> {
-    let current_timestamp = Uint128::new((env.block.time.seconds()) as u128);
-    update_reward(&env.contract.address, deps.storage, &env)?;
+    let reward_token_contract = match reward_token.clone() {
+        TokenType::CustomToken {
+            contract_addr,
+            token_code_hash,
+        } => Contract {
+            address: contract_addr,
+            code_hash: token_code_hash,
+        },
+        TokenType::NativeToken { denom: _ } => {
+            return Err(StdError::generic_err(
+                "Invalid Token Type for Reward Token".to_string(),
+            ))
+        }
+    };
+    if reward_token_r(deps.storage)
+        .may_load(reward_token.unique_key().as_bytes())?
+        .is_some()
+    {
+        return Err(StdError::generic_err(format!(
+            "{} is already a reward token.",
+            reward_token.unique_key()
+        )));
+    }
+    // Claims of a retired token are settled against its final reward per token,
+    // so it can only come back once every staker is settled
+    if let Some(retired) = find_retired_reward_token(deps.storage, &reward_token.unique_key())? {
+        if !retired.unsettled_weight.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "{} was retired as a reward token and is not settled yet.",
+                reward_token.unique_key()
+            )));
+        }
+        // Its unclaimed rewards are claimed as a registered token again
+        let mut retired_tokens = retired_reward_tokens_r(deps.storage).load()?;
+        let key = reward_token.unique_key();
+        retired_tokens.retain(|retired| retired.reward_token.unique_key() != key);
+        retired_reward_tokens_w(deps.storage).save(&retired_tokens)?;
+    }
+    // Settled stakers are paid up to the final reward per token of its last retirement
+    let reward_per_token_stored = resumed_reward_per_token_r(deps.storage)
+        .may_load(reward_token.unique_key().as_bytes())?
+        .unwrap_or_default();
+
+    let current_timestamp = Uint128::new(env.block.time.seconds() as u128);
+    let mut reward_token_list = reward_token_list_r(deps.storage).load()?;
+    reward_token_list.push(reward_token.unique_key());
+    reward_token_list_w(deps.storage).save(&reward_token_list)?;
+    reward_token_w(deps.storage).save(
+        reward_token.unique_key().as_bytes(),
+        &RewardTokenInfo {
+            reward_token: reward_token.to_owned(),
+            reward_rate: Uint128::zero(),
+            valid_to: current_timestamp,
+            reward_per_token_stored,
+            last_update_time: current_timestamp,
+        },
+    )?;
 
-    let token_info_option =
-        reward_token_w(deps.storage).may_load(reward_token.unique_key().as_bytes())?;
-    let mut token_info: RewardTokenInfo;
+    Ok(Response::new()
+        .add_message(set_viewing_key_msg(
+            SHADE_STAKING_VIEWKEY.to_string(),
+            None,
+            &reward_token_contract,
+        )?)
+        // FundRewards arrives through the token's Send
+        .add_message(register_receive(
+            env.contract.code_hash.clone(),
+            None,
+            &reward_token_contract,
+        )?)
+        .add_attributes(vec![
+            Attribute::new("action", "set_reward_token"),
+            Attribute::new("reward_token", reward_token.unique_key()),
+        ]))
+}
 
-    let mut messages = vec![];
+/// Stop emitting a reward token. Accrued rewards stay claimable and are settled
+/// lazily, the unemitted budget is refunded to refund_to.
+pub fn remove_reward_token(
+    deps: DepsMut,
+    env: &Env,
+    token: TokenType,
+    refund_to: &Addr,
+) -> StdResult<Response> {
+    let key = token.unique_key();
+    if reward_token_r(deps.storage).may_load(key.as_bytes())?.is_none() {
+        return Err(StdError::generic_err(format!(
+            "{} is not a reward token.",
+            key
+        )));
+    }
 
-    match token_info_option {
-        None => {
-            match reward_token.clone() {
-                TokenType::CustomToken {
-                    contract_addr,
-                    token_code_hash,
-                } => {
-                    messages.push(set_viewing_key_msg(
-                        SHADE_STAKING_VIEWKEY.to_string(),
-                        None,
-                        &Contract {
-                            address: contract_addr.clone(),
-                            code_hash: token_code_hash.to_string(),
-                        },
-                    )?);
-                }
-                TokenType::NativeToken { denom: _ } => (),
-            }
+    // Settle emission up to now before the token leaves the reward list
+    update_reward(&env.contract.address, deps.storage, env)?;
 
-            let mut reward_token_list = reward_token_list_w(deps.storage).load()?;
-            reward_token_list.push(reward_token.unique_key());
-            reward_token_list_w(deps.storage).save(&reward_token_list)?;
-            token_info = RewardTokenInfo {
-                reward_token: reward_token.to_owned(),
-                reward_rate: daily_reward_amount
-                    .checked_mul(MAX_DECIMALS)?
-                    .checked_div(SECONDS_IN_DAY)?,
-                valid_to: current_timestamp,
-                reward_per_token_stored: Uint128::zero(),
-                last_update_time: current_timestamp,
-            };
-            reward_token_w(deps.storage)
-                .save(&reward_token.unique_key().as_bytes(), &token_info)?;
+    let current_timestamp = Uint128::new(env.block.time.seconds() as u128);
+    let token_info = reward_token_r(deps.storage).load(key.as_bytes())?;
+    let leftover = if current_timestamp < token_info.valid_to {
+        (token_info.valid_to - current_timestamp)
+            .checked_mul(token_info.reward_rate)?
+            .checked_div(MAX_DECIMALS)?
+    } else {
+        Uint128::zero()
+    };
+
+    let mut reward_token_list = reward_token_list_r(deps.storage).load()?;
+    reward_token_list.retain(|reward_token| *reward_token != key);
+    reward_token_list_w(deps.storage).save(&reward_token_list)?;
+    reward_token_w(deps.storage).remove(key.as_bytes());
+
+    let retirement = current_retirement(deps.storage, &key)? + 1;
+    reward_token_retirements_w(deps.storage).save(key.as_bytes(), &retirement)?;
+    resumed_reward_per_token_w(deps.storage)
+        .save(key.as_bytes(), &token_info.reward_per_token_stored)?;
+
+    let mut retired_tokens = retired_reward_tokens_r(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    retired_tokens.push(RetiredRewardToken {
+        reward_token: token_info.reward_token.clone(),
+        reward_per_token: token_info.reward_per_token_stored,
+        retired_at: env.block.time.seconds(),
+        unsettled_weight: total_weight(deps.storage)?,
+        unclaimed: Uint128::zero(),
+    });
+    save_retired_reward_tokens(deps.storage, retired_tokens)?;
+
+    let mut response = Response::new();
+    if !leftover.is_zero() {
+        response = response.add_message(token_info.reward_token.create_send_msg(
+            env.contract.address.to_string(),
+            refund_to.to_string(),
+            leftover,
+        )?);
+    }
+    Ok(response.add_attributes(vec![
+        Attribute::new("action", "remove_reward_token"),
+        Attribute::new("reward_token", key),
+        Attribute::new("refund", leftover),
+    ]))
+}
+
+/// Save the retired tokens, dropping those fully settled and claimed
+fn save_retired_reward_tokens(
+    storage: &mut dyn Storage,
+    mut retired_tokens: Vec<RetiredRewardToken>,
+) -> StdResult<()> {
+    retired_tokens
+        .retain(|retired| !retired.unsettled_weight.is_zero() || !retired.unclaimed.is_zero());
+    retired_reward_tokens_w(storage).save(&retired_tokens)
+}
+
+fn current_retirement(storage: &dyn Storage, reward_token_key: &str) -> StdResult<u64> {
+    Ok(reward_token_retirements_r(storage)
+        .may_load(reward_token_key.as_bytes())?
+        .unwrap_or_default())
+}
+
+/// Whether the staker's rewards of the token's current retirement are settled
+pub fn retired_rewards_settled(
+    storage: &dyn Storage,
+    staker: &Addr,
+    reward_token_key: &str,
+) -> StdResult<bool> {
+    let key = get_user_claim_key(staker.to_string(), reward_token_key.to_string());
+    Ok(retired_rewards_settled_r(storage).may_load(key.as_bytes())?
+        == Some(current_retirement(storage, reward_token_key)?))
+}
+
+fn find_retired_reward_token(
+    storage: &dyn Storage,
+    key: &str,
+) -> StdResult<Option<RetiredRewardToken>> {
+    Ok(retired_reward_tokens_r(storage)
+        .may_load()?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|retired| retired.reward_token.unique_key() == key))
+}
+
+/// Settle the staker's rewards of retired tokens once, with the weight that earned them.
+/// A retired token leaves this loop when all the weight it was shared by is settled.
+fn settle_retired_rewards(storage: &mut dyn Storage, address: &Addr) -> StdResult<()> {
+    let mut retired_tokens = match retired_reward_tokens_r(storage).may_load()? {
+        Some(retired_tokens) => retired_tokens,
+        None => return Ok(()),
+    };
+    let staker_info = stakers_r(storage).may_load(address.as_bytes())?;
+    let mut changed = false;
+    for retired in retired_tokens
+        .iter_mut()
+        .filter(|retired| !retired.unsettled_weight.is_zero())
+    {
+        let token_key = retired.reward_token.unique_key();
+        if retired_rewards_settled(storage, address, &token_key)? {
+            continue;
         }
-        Some(ti) => {
-            token_info = ti;
-            if current_timestamp >= valid_to {
-                token_info.reward_rate = daily_reward_amount
-                    .checked_mul(MAX_DECIMALS)?
-                    .checked_div(SECONDS_IN_DAY)?;
-            } else {
-                token_info.reward_rate = daily_reward_amount
-                    .checked_mul(MAX_DECIMALS)?
-                    .checked_div(SECONDS_IN_DAY)?
-            }
+        let key = get_user_claim_key(address.to_string(), token_key.clone());
+        retired_rewards_settled_w(storage)
+            .save(key.as_bytes(), &current_retirement(storage, &token_key)?)?;
+        changed = true;
+
+        let staker_info = match &staker_info {
+            Some(staker_info) => staker_info,
+            None => continue,
+        };
+        let claim_info = claim_reward_info_r(storage)
+            .may_load(key.as_bytes())?
+            .unwrap_or(ClaimRewardsInfo {
+                rewards: Uint128::zero(),
+                reward_token: retired.reward_token.clone(),
+                reward_token_per_token_paid: Uint128::zero(),
+            });
+        let rewards = earned(
+            staker_info.weight(),
+            retired.reward_per_token,
+            claim_info.reward_token_per_token_paid,
+            claim_info.rewards,
+        )?;
+        claim_reward_info_w(storage).save(
+            key.as_bytes(),
+            &ClaimRewardsInfo {
+                rewards,
+                reward_token: retired.reward_token.clone(),
+                reward_token_per_token_paid: retired.reward_per_token,
+            },
+        )?;
+        retired.unsettled_weight = retired
+            .unsettled_weight
+            .saturating_sub(staker_info.weight());
+        retired.unclaimed = retired.unclaimed.checked_add(rewards)?;
+    }
+    if changed {
+        save_retired_reward_tokens(storage, retired_tokens)?;
+    }
+    Ok(())
+}
+
+/// Fund the reward program of a reward token. The rate is derived from the funded
+/// amount plus the budget left over from the running program.
+pub fn fund_rewards(
+    deps: DepsMut,
+    env: &Env,
+    token: &Addr,
+    funder: &Addr,
+    amount: Uint128,
+    duration: u64,
+) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    if let Some(funders) = &config.reward_funders {
+        if !funders.contains(funder) {
+            return Err(StdError::generic_err(format!(
+                "{} is not allowed to fund rewards.",
+                funder
+            )));
         }
     }
+    if duration == 0 {
+        return Err(StdError::generic_err(
+            "Reward duration must be greater than zero.",
+        ));
+    }
+    if reward_token_r(deps.storage).may_load(token.as_bytes())?.is_none() {
+        return Err(StdError::generic_err(format!(
+            "{} is not a reward token.",
+            token
+        )));
+    }
 
+    // Settle the running program before its rate changes
+    update_reward(&env.contract.address, deps.storage, env)?;
+
+    let current_timestamp = Uint128::new(env.block.time.seconds() as u128);
+    let mut token_info = reward_token_r(deps.storage).load(token.as_bytes())?;
+    let leftover = if current_timestamp < token_info.valid_to {
+        (token_info.valid_to - current_timestamp).checked_mul(token_info.reward_rate)?
+    } else {
+        Uint128::zero()
+    };
+    let duration = Uint128::from(duration);
+    token_info.reward_rate = amount
+        .checked_mul(MAX_DECIMALS)?
+        .checked_add(leftover)?
+        .checked_div(duration)?;
+    token_info.valid_to = current_timestamp.checked_add(duration)?;
     token_info.last_update_time = current_timestamp;
-    token_info.valid_to = valid_to;
+    reward_token_w(deps.storage).save(token.as_bytes(), &token_info)?;
 
-    reward_token_w(deps.storage).save(reward_token.unique_key().as_bytes(), &token_info)?;
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        Attribute::new("action", "set_reward_token"),
-        Attribute::new("daily_reward_amount", daily_reward_amount.to_string()),
-        Attribute::new("valid_to", valid_to.to_string()),
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "fund_rewards"),
+        Attribute::new("reward_token", token.to_string()),
+        Attribute::new("amount", amount),
+        Attribute::new("valid_to", token_info.valid_to),
     ]))
 }
 
+/// Restrict who can fund reward programs
+pub fn set_reward_funders(
+    deps: DepsMut,
+    funders: Option<Vec<String>>,
+) -> StdResult<Response> {
+    let mut config = config_r(deps.storage).load()?;
+    config.reward_funders = funders
+        .map(|funders| {
+            funders
+                .iter()
+                .map(|funder| deps.api.addr_validate(funder))
+                .collect::<StdResult<Vec<Addr>>>()
+        })
+        .transpose()?;
+    config_w(deps.storage).save(&config)?;
+    Ok(Response::new().add_attribute("action", "set_reward_funders"))
+}
+
 /// Return List of Reward Tokens
 pub fn get_reward_tokens_info(storage: &dyn Storage) -> StdResult<Vec<RewardTokenInfo>> {
     let mut list_token: Vec<RewardTokenInfo> = Vec::new();
@@ -287,6 +855,47 @@ pub fn update_authenticator(
     Ok(Response::default())
 }
 
+/// Generate a viewing key for the staker from the contract's prng seed
+pub fn create_staker_viewing_key(
+    storage: &mut dyn Storage,
+    env: &Env,
+    info: &MessageInfo,
+    entropy: String,
+) -> StdResult<Response> {
+    let prng_seed = prng_seed_r(storage).load()?;
+    let key = ViewingKey::new(env, info, &prng_seed, entropy.as_bytes());
+    stakers_vk_w(storage).save(info.sender.as_bytes(), &key.to_hashed())?;
+    Ok(Response::new().set_data(to_binary(&ExecuteMsgResponse::CreateViewingKey {
+        key: key.to_string(),
+    })?))
+}
+
+pub fn set_staker_viewing_key(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    key: String,
+) -> StdResult<Response> {
+    stakers_vk_w(storage).save(staker.as_bytes(), &ViewingKey(key).to_hashed())?;
+    Ok(Response::new().add_attribute("action", "set_viewing_key"))
+}
+
+/// Addresses without a viewing key are checked against an empty hash, so all failures look alike
+pub fn authenticate_viewing_key(
+    storage: &dyn Storage,
+    address: &Addr,
+    key: String,
+) -> StdResult<()> {
+    let hashed_key = stakers_vk_r(storage)
+        .may_load(address.as_bytes())?
+        .unwrap_or([0u8; VIEWING_KEY_SIZE]);
+    if !ViewingKey(key).check_viewing_key(&hashed_key) {
+        return Err(StdError::generic_err(
+            "Wrong viewing key for this address or viewing key not set",
+        ));
+    }
+    Ok(())
+}
+
 /// Unstake Amount
 pub fn unstake(
     deps: DepsMut,
@@ -301,50 +910,41 @@ pub fn unstake(
 
     if from_address == for_address {
         if let Some(mut staker_info) = stakers_r(deps.storage).may_load(for_address.as_bytes())? {
-            if amount > (staker_info.amount - staker_info.proxy_staked) {
+            let unstakable = staker_info
+                .amount
+                .checked_sub(staker_info.proxy_staked)
+                .and_then(|free| free.checked_sub(staker_info.locked))
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            if amount > unstakable {
                 return Err(StdError::generic_err(
                     "Unstaking Amount is higher then actual staking amount".to_string(),
                 ));
             }
 
-            let mut response = get_rewards(deps.storage, for_address, env, &Response::new())?;
+            let recipient = reward_recipient(deps.storage, for_address)?;
+            let mut response = get_rewards(
+                deps.storage,
+                for_address,
+                &recipient,
+                None,
+                env,
+                &Response::new(),
+            )?;
 
             staker_info.amount = staker_info.amount - amount;
             stakers_w(deps.storage).save(for_address.as_bytes(), &staker_info)?;
 
             // send back amount of lp token to pair contract to send pair token back with burn
             let config = config_r(deps.storage).load()?;
-
-            if let Some(true) = remove_liquidity {
-                // SEND LP Token back to Pair Contract With Remove Liquidity
-                let remove_liquidity_msg = to_binary(&AmmPairInvokeMsg::RemoveLiquidity {
-                    from: Some(for_address.to_string()),
-                    single_sided_withdraw_type: None,
-                    single_sided_expected_return: None,
-                })?;
-
-                let cosmos_msg = snip20::ExecuteMsg::Send {
-                    recipient: config.amm_pair.to_string(),
-                    recipient_code_hash: None,
-                    amount,
-                    msg: Some(remove_liquidity_msg.clone()),
-                    memo: None,
-                    padding: None,
-                }
-                .to_cosmos_msg(&config.lp_token, vec![])?;
-
-                response = response.add_message(cosmos_msg);
-            } else {
-                // SEND LP Token back to Staker And User Will Manually Remove Liquidity
-                let cosmos_msg = snip20::ExecuteMsg::Transfer {
-                    recipient: for_address.to_string(),
-                    amount,
-                    memo: None,
-                    padding: None,
-                }
-                .to_cosmos_msg(&config.lp_token, vec![])?;
-
-                response = response.add_message(cosmos_msg);
+            if let Some(msg) = withdraw_lp_token(
+                deps.storage,
+                env,
+                &config,
+                for_address,
+                amount,
+                remove_liquidity,
+            )? {
+                response = response.add_message(msg);
             }
             let mut total_stake_amount = total_staked_w(deps.storage).load()?;
             total_stake_amount -= amount;
@@ -388,15 +988,11 @@ pub fn unstake(
             // send back amount of lp token to pair contract to send pair token back with burn
             let config = config_r(deps.storage).load()?;
 
-            let cosmos_msg = snip20::ExecuteMsg::Transfer {
-                recipient: from_address.to_string(),
-                amount,
-                memo: None,
-                padding: None,
+            if let Some(cosmos_msg) =
+                withdraw_lp_token(deps.storage, env, &config, from_address, amount, None)?
+            {
+                messages.push(cosmos_msg);
             }
-            .to_cosmos_msg(&config.lp_token, vec![])?;
-
-            messages.push(cosmos_msg);
 
             let mut total_stake_amount = total_staked_w(deps.storage).load()?;
             total_stake_amount -= amount;
@@ -414,6 +1010,470 @@ pub fn unstake(
     }
 }
 
+/// Unstake without paying rewards, so a broken reward token can not trap LP token.
+/// Reward state is only checkpointed in storage and the staker's pending rewards are forfeited.
+pub fn emergency_unstake(
+    deps: DepsMut,
+    env: &Env,
+    staker: &Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let emergency_mode = emergency_mode_r(deps.storage).may_load()?.unwrap_or_default();
+    let mut staker_info = match stakers_r(deps.storage).may_load(staker.as_bytes())? {
+        Some(staker_info) => staker_info,
+        None => {
+            return Err(StdError::generic_err(
+                "Staking information does not exist".to_string(),
+            ))
+        }
+    };
+
+    update_reward(staker, deps.storage, env)?;
+
+    // Lock positions are dissolved without penalty in emergency mode
+    if emergency_mode {
+        let mut locks = lock_positions_r(deps.storage)
+            .may_load(staker.as_bytes())?
+            .unwrap_or_default();
+        if !locks.positions.is_empty() {
+            let total_boost = total_boost_r(deps.storage).may_load()?.unwrap_or_default();
+            total_boost_w(deps.storage).save(&total_boost.checked_sub(staker_info.boost)?)?;
+            staker_info.locked = Uint128::zero();
+            staker_info.boost = Uint128::zero();
+            locks.positions.clear();
+            lock_positions_w(deps.storage).save(staker.as_bytes(), &locks)?;
+        }
+    }
+
+    let unstakable = staker_info
+        .amount
+        .checked_sub(staker_info.proxy_staked)
+        .and_then(|free| free.checked_sub(staker_info.locked))
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    if amount > unstakable {
+        return Err(StdError::generic_err(
+            "Unstaking Amount is higher then actual staking amount".to_string(),
+        ));
+    }
+
+    let forfeited = forfeit_rewards(deps.storage, staker)?;
+
+    staker_info.amount = staker_info.amount.checked_sub(amount)?;
+    stakers_w(deps.storage).save(staker.as_bytes(), &staker_info)?;
+    let total_staked = total_staked_r(deps.storage).load()?;
+    total_staked_w(deps.storage).save(&total_staked.checked_sub(amount)?)?;
+
+    let config = config_r(deps.storage).load()?;
+    let mut response = Response::new();
+    if emergency_mode {
+        response = response.add_message(lp_token_return_msg(&config, staker, amount, None)?);
+    } else if let Some(msg) = withdraw_lp_token(deps.storage, env, &config, staker, amount, None)? {
+        response = response.add_message(msg);
+    }
+
+    let mut attributes = vec![
+        Attribute::new("action", "emergency_unstake"),
+        Attribute::new("amount", amount),
+        Attribute::new("staker", staker.as_str()),
+    ];
+    for (reward_token, rewards) in forfeited {
+        attributes.push(Attribute::new("forfeited_reward_token", reward_token));
+        attributes.push(Attribute::new("forfeited_amount", rewards));
+    }
+    Ok(response.add_attributes(attributes))
+}
+
+/// Drop the staker's settled rewards of every reward token, retired ones included
+fn forfeit_rewards(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let mut retired_tokens = retired_reward_tokens_r(storage)
+        .may_load()?
+        .unwrap_or_default();
+    let mut reward_list = reward_token_list_r(storage).load()?;
+    reward_list.extend(
+        retired_tokens
+            .iter()
+            .map(|retired| retired.reward_token.unique_key()),
+    );
+
+    let mut forfeited = vec![];
+    for addr in &reward_list {
+        let key = get_user_claim_key(staker.to_string(), addr.to_string());
+        if let Some(mut claim_info) = claim_reward_info_r(storage).may_load(key.as_bytes())? {
+            if claim_info.rewards.is_zero() {
+                continue;
+            }
+            if let Some(retired) = retired_tokens
+                .iter_mut()
+                .find(|retired| retired.reward_token.unique_key() == *addr)
+            {
+                retired.unclaimed = retired.unclaimed.checked_sub(claim_info.rewards)?;
+            }
+            forfeited.push((addr.to_string(), claim_info.rewards));
+            claim_info.rewards = Uint128::zero();
+            claim_reward_info_w(storage).save(key.as_bytes(), &claim_info)?;
+        }
+    }
+    if !retired_tokens.is_empty() {
+        save_retired_reward_tokens(storage, retired_tokens)?;
+    }
+    Ok(forfeited)
+}
+
+/// Emergency mode stops deposits and reward funding
+pub fn assert_not_emergency_mode(storage: &dyn Storage) -> StdResult<()> {
+    if emergency_mode_r(storage).may_load()?.unwrap_or_default() {
+        return Err(StdError::generic_err("Staking is in emergency mode."));
+    }
+    Ok(())
+}
+
+/// Instantiate the receipt token from the LP token code, the reply stores its address
+pub fn create_receipt_token(
+    deps: DepsMut,
+    env: &Env,
+    contract_info: ContractInstantiationInfo,
+    custom_label: Option<String>,
+) -> StdResult<Response> {
+    if receipt_token_r(deps.storage).may_load()?.is_some() {
+        return Err(StdError::generic_err("Receipt token already exists."));
+    }
+    let config = config_r(deps.storage).load()?;
+    let lp_token_info = token_info(&deps.querier, &config.lp_token)?;
+    receipt_token_w(deps.storage).save(&ReceiptToken {
+        code_hash: contract_info.code_hash.clone(),
+        address: None,
+    })?;
+
+    let init_msg = LpTokenInstantiateMsg {
+        name: format!("Staked {}", lp_token_info.name),
+        admin: Some(env.contract.address.to_string()),
+        symbol: "STKLP".to_string(),
+        decimals: lp_token_info.decimals,
+        initial_balances: None,
+        prng_seed: Binary::from(prng_seed_r(deps.storage).load()?),
+        config: Some(InitConfig {
+            public_total_supply: Some(true),
+            enable_deposit: Some(false),
+            enable_redeem: Some(false),
+            enable_mint: Some(true),
+            enable_burn: Some(true),
+        }),
+    };
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id: contract_info.id,
+                label: custom_label.unwrap_or(format!(
+                    "ShadeSwap-Staking-Receipt-Token-{}",
+                    &env.contract.address
+                )),
+                msg: to_binary(&init_msg)?,
+                code_hash: contract_info.code_hash,
+                funds: vec![],
+            }),
+            INSTANTIATE_RECEIPT_TOKEN_REPLY_ID,
+        ))
+        .add_attribute("action", "create_receipt_token"))
+}
+
+pub fn register_receipt_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: Addr,
+) -> StdResult<Response> {
+    let mut stored_receipt_token = receipt_token_r(storage).load()?;
+    if stored_receipt_token.address.is_some() {
+        return Err(StdError::generic_err("Receipt token already exists."));
+    }
+    let receipt_token = Contract {
+        address: address.clone(),
+        code_hash: stored_receipt_token.code_hash.clone(),
+    };
+    stored_receipt_token.address = Some(address);
+    receipt_token_w(storage).save(&stored_receipt_token)?;
+    Ok(Response::new()
+        .add_message(register_receive(
+            env.contract.code_hash.clone(),
+            None,
+            &receipt_token,
+        )?)
+        .add_attribute("receipt_token", receipt_token.address.as_str()))
+}
+
+fn load_receipt_token(storage: &dyn Storage) -> StdResult<Contract> {
+    receipt_token_r(storage)
+        .may_load()?
+        .and_then(|receipt_token| receipt_token.contract())
+        .ok_or_else(|| StdError::generic_err("Receipt token does not exist."))
+}
+
+fn assert_receipt_token(storage: &dyn Storage, info: &MessageInfo) -> StdResult<Contract> {
+    let receipt_token = load_receipt_token(storage)?;
+    if receipt_token.address != info.sender {
+        return Err(StdError::generic_err("Sender was not the receipt token."));
+    }
+    Ok(receipt_token)
+}
+
+/// Account holding the stake behind receipt tokens. Receipts are bearer claims on its
+/// stake and rewards, so whoever sends them to staking redeems them.
+pub const RECEIPT_POOL: &str = "receipt_pool";
+
+fn receipt_pool() -> Addr {
+    Addr::unchecked(RECEIPT_POOL)
+}
+
+/// Stake into the receipt pool and mint receipt tokens 1:1 for the staked amount
+pub fn stake_with_receipt(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    amount: Uint128,
+    staker: &Addr,
+) -> StdResult<Response> {
+    let receipt_token = load_receipt_token(deps.storage)?;
+    let pool = receipt_pool();
+    let response = stake(deps.branch(), env, info, amount, &pool, &pool)?;
+
+    Ok(response
+        .add_message(mint_msg(staker.clone(), amount, None, None, &receipt_token)?)
+        .add_attribute("receipt_minted", amount))
+}
+
+/// Move amount of the receipt pool's stake and the same share of the pool's rewards
+/// to holder, rewards of both are checkpointed first
+fn redeem_receipts(
+    storage: &mut dyn Storage,
+    env: &Env,
+    holder: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let pool = receipt_pool();
+    update_reward(&pool, storage, env)?;
+    update_reward(holder, storage, env)?;
+
+    let mut pool_info = match stakers_r(storage).may_load(pool.as_bytes())? {
+        Some(pool_info) if pool_info.amount >= amount => pool_info,
+        _ => {
+            return Err(StdError::generic_err(
+                "Receipt amount is higher than the receipted stake.",
+            ))
+        }
+    };
+
+    // Retired tokens keep their unclaimed total, the rewards only change owner
+    let mut reward_list = reward_token_list_r(storage).load()?;
+    reward_list.extend(
+        retired_reward_tokens_r(storage)
+            .may_load()?
+            .unwrap_or_default()
+            .iter()
+            .map(|retired| retired.reward_token.unique_key()),
+    );
+    for addr in &reward_list {
+        let pool_key = get_user_claim_key(pool.to_string(), addr.to_string());
+        let mut pool_claim = match claim_reward_info_r(storage).may_load(pool_key.as_bytes())? {
+            Some(pool_claim) => pool_claim,
+            None => continue,
+        };
+        let share = pool_claim.rewards.multiply_ratio(amount, pool_info.amount);
+        if share.is_zero() {
+            continue;
+        }
+        pool_claim.rewards = pool_claim.rewards.checked_sub(share)?;
+        claim_reward_info_w(storage).save(pool_key.as_bytes(), &pool_claim)?;
+
+        let holder_key = get_user_claim_key(holder.to_string(), addr.to_string());
+        let mut holder_claim = claim_reward_info_r(storage)
+            .may_load(holder_key.as_bytes())?
+            .unwrap_or(ClaimRewardsInfo {
+                rewards: Uint128::zero(),
+                reward_token: pool_claim.reward_token.clone(),
+                reward_token_per_token_paid: pool_claim.reward_token_per_token_paid,
+            });
+        holder_claim.rewards = holder_claim.rewards.checked_add(share)?;
+        claim_reward_info_w(storage).save(holder_key.as_bytes(), &holder_claim)?;
+    }
+
+    pool_info.amount = pool_info.amount.checked_sub(amount)?;
+    stakers_w(storage).save(pool.as_bytes(), &pool_info)?;
+
+    let holder_info = match stakers_r(storage).may_load(holder.as_bytes())? {
+        Some(mut holder_info) => {
+            holder_info.amount = holder_info.amount.checked_add(amount)?;
+            holder_info
+        }
+        None => StakingInfo {
+            amount,
+            proxy_staked: Uint128::zero(),
+            locked: Uint128::zero(),
+            boost: Uint128::zero(),
+        },
+    };
+    stakers_w(storage).save(holder.as_bytes(), &holder_info)?;
+    Ok(())
+}
+
+/// Burn receipt tokens sent with TransferStake and move their stake and rewards out of
+/// the receipt pool to recipient as plain stake
+pub fn transfer_stake(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    owner: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let receipt_token = assert_receipt_token(deps.storage, info)?;
+    if *recipient == env.contract.address {
+        return Err(StdError::generic_err("Stake can not be transferred to staking."));
+    }
+    redeem_receipts(deps.storage, env, recipient, amount)?;
+
+    Ok(Response::new()
+        .add_message(burn_msg(amount, None, None, &receipt_token)?)
+        .add_attributes(vec![
+            Attribute::new("action", "transfer_stake"),
+            Attribute::new("amount", amount),
+            Attribute::new("owner", owner.as_str()),
+            Attribute::new("recipient", recipient.as_str()),
+        ]))
+}
+
+/// Burn receipt tokens sent with UnstakeReceipt and unstake their stake to the sender,
+/// paying it the receipts' share of the pool's rewards with its own.
+/// In emergency mode the sender's rewards are forfeited as with EmergencyUnstake.
+pub fn unstake_receipt(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    holder: &Addr,
+    amount: Uint128,
+    remove_liquidity: Option<bool>,
+) -> StdResult<Response> {
+    let receipt_token = assert_receipt_token(deps.storage, info)?;
+    redeem_receipts(deps.storage, env, holder, amount)?;
+
+    let response = if emergency_mode_r(deps.storage).may_load()?.unwrap_or_default() {
+        emergency_unstake(deps.branch(), env, holder, amount)?
+    } else {
+        unstake(deps.branch(), env, holder, holder, amount, remove_liquidity)?
+    };
+    Ok(response.add_message(burn_msg(amount, None, None, &receipt_token)?))
+}
+
+/// Return LP token now, or queue it for ClaimUnbonded when an unbonding period is set
+fn withdraw_lp_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    staker: &Addr,
+    amount: Uint128,
+    remove_liquidity: Option<bool>,
+) -> StdResult<Option<CosmosMsg>> {
+    let unbonding_period = unbonding_period_r(storage).may_load()?.unwrap_or_default();
+    if unbonding_period == 0 {
+        return Ok(Some(lp_token_return_msg(
+            config,
+            staker,
+            amount,
+            remove_liquidity,
+        )?));
+    }
+    let mut unbondings = unbondings_r(storage)
+        .may_load(staker.as_bytes())?
+        .unwrap_or_default();
+    unbondings.push(Unbonding {
+        amount,
+        release_at: env.block.time.seconds() + unbonding_period,
+        remove_liquidity: remove_liquidity == Some(true),
+    });
+    unbondings_w(storage).save(staker.as_bytes(), &unbondings)?;
+    Ok(None)
+}
+
+/// Release the unbondings of the staker whose unbonding period has passed
+pub fn claim_unbonded(deps: DepsMut, env: &Env, staker: &Addr) -> StdResult<Response> {
+    let current_timestamp = env.block.time.seconds();
+    let (matured, pending): (Vec<Unbonding>, Vec<Unbonding>) = unbondings_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|unbonding| unbonding.release_at <= current_timestamp);
+    if matured.is_empty() {
+        return Err(StdError::generic_err("No unbonded LP token to claim."));
+    }
+    unbondings_w(deps.storage).save(staker.as_bytes(), &pending)?;
+
+    let mut amount = Uint128::zero();
+    let mut remove_liquidity_amount = Uint128::zero();
+    for unbonding in &matured {
+        if unbonding.remove_liquidity {
+            remove_liquidity_amount += unbonding.amount;
+        } else {
+            amount += unbonding.amount;
+        }
+    }
+
+    let config = config_r(deps.storage).load()?;
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        response = response.add_message(lp_token_return_msg(&config, staker, amount, None)?);
+    }
+    if !remove_liquidity_amount.is_zero() {
+        response = response.add_message(lp_token_return_msg(
+            &config,
+            staker,
+            remove_liquidity_amount,
+            Some(true),
+        )?);
+    }
+    Ok(response.add_attributes(vec![
+        Attribute::new("action", "claim_unbonded"),
+        Attribute::new("amount", amount + remove_liquidity_amount),
+        Attribute::new("staker", staker.as_str()),
+    ]))
+}
+
+/// Return LP token to the staker, or through the pair contract to remove liquidity
+fn lp_token_return_msg(
+    config: &Config,
+    staker: &Addr,
+    amount: Uint128,
+    remove_liquidity: Option<bool>,
+) -> StdResult<CosmosMsg> {
+    if let Some(true) = remove_liquidity {
+        // SEND LP Token back to Pair Contract With Remove Liquidity
+        let remove_liquidity_msg = to_binary(&AmmPairInvokeMsg::RemoveLiquidity {
+            from: Some(staker.to_string()),
+            single_sided_withdraw_type: None,
+            single_sided_expected_return: None,
+        })?;
+
+        snip20::ExecuteMsg::Send {
+            recipient: config.amm_pair.to_string(),
+            recipient_code_hash: None,
+            amount,
+            msg: Some(remove_liquidity_msg),
+            memo: None,
+            padding: None,
+        }
+        .to_cosmos_msg(&config.lp_token, vec![])
+    } else {
+        // SEND LP Token back to Staker And User Will Manually Remove Liquidity
+        snip20::ExecuteMsg::Transfer {
+            recipient: staker.to_string(),
+            amount,
+            memo: None,
+            padding: None,
+        }
+        .to_cosmos_msg(&config.lp_token, vec![])
+    }
+}
+
 pub fn create_send_msg(
     recipient: String,
     amount: Uint128,
@@ -490,15 +1550,13 @@ pub fn update_reward(
 ) -> StdResult<()> {
     let current_timestamp = Uint128::new(env.block.time.seconds() as u128);
     let reward_list = reward_token_list_r(storage).load()?;
-    let total_staked = match total_staked_r(storage).may_load()? {
-        Some(s) => s,
-        None => Uint128::zero(),
-    };
+    let total_weight = total_weight(storage)?;
+    let is_staker = stakers_r(storage).may_load(address.as_bytes())?.is_some();
     for addr in &reward_list {
         // load total reward token
         if let Some(mut reward_token_info) = reward_token_w(storage).may_load(addr.as_bytes())? {
             let reward_per_token =
-                reward_per_token(current_timestamp, &reward_token_info, total_staked)?;
+                reward_per_token(current_timestamp, &reward_token_info, total_weight)?;
             reward_token_info.reward_per_token_stored = reward_per_token;
             reward_token_info.last_update_time =
                 last_time_reward_applicable(current_timestamp, reward_token_info.valid_to)?;
@@ -508,14 +1566,21 @@ pub fn update_reward(
                     .may_load(get_user_claim_key(address.to_string(), addr.to_string()).as_bytes())?
                     .is_none()
                 {
+                    // Existing stakers earn a token added after they staked from its first reward,
+                    // a re-added token resumes from its last retirement
+                    let reward_token_per_token_paid = if is_staker {
+                        resumed_reward_per_token_r(storage)
+                            .may_load(addr.as_bytes())?
+                            .unwrap_or_default()
+                    } else {
+                        reward_token_info.reward_per_token_stored
+                    };
                     claim_reward_info_w(storage).save(
                         get_user_claim_key(address.to_string(), addr.to_string()).as_bytes(),
                         &ClaimRewardsInfo {
                             rewards: Uint128::zero(),
                             reward_token: reward_token_info.clone().reward_token,
-                            reward_token_per_token_paid: reward_token_info
-                                .clone()
-                                .reward_per_token_stored,
+                            reward_token_per_token_paid,
                         },
                     )?;
                 };
@@ -530,7 +1595,7 @@ pub fn update_reward(
                         get_user_claim_key(address.to_string(), addr.to_string()).as_bytes(),
                     )?;
                     let new_amount = earned(
-                        staker_info.amount,
+                        staker_info.weight(),
                         reward_per_token,
                         claim_reward_info.reward_token_per_token_paid,
                         claim_reward_info.rewards,
@@ -548,9 +1613,19 @@ pub fn update_reward(
             }
         }
     }
+    if address.to_string() != env.contract.address.to_string() {
+        settle_retired_rewards(storage, address)?;
+    }
     Ok(())
 }
 
+/// Reward weight of all stakers, staked LP token plus lock boosts
+pub fn total_weight(storage: &dyn Storage) -> StdResult<Uint128> {
+    let total_staked = total_staked_r(storage).may_load()?.unwrap_or_default();
+    let total_boost = total_boost_r(storage).may_load()?.unwrap_or_default();
+    Ok(total_staked + total_boost)
+}
+
 pub fn calculate_staker_shares(storage: &dyn Storage, amount: Uint128) -> StdResult<Decimal> {
     let total_staking_amount: Uint128 = match total_staked_r(storage).may_load()? {
         Some(staking_amount) => staking_amount,
@@ -1,11 +1,15 @@
-use crate::operations::{earned, get_reward_tokens_info, get_user_claim_key, reward_per_token, MAX_DECIMALS};
+use crate::operations::{
+    earned, get_reward_tokens_info, get_user_claim_key, load_lock_config, retired_rewards_settled,
+    reward_per_token, total_weight, MAX_DECIMALS,
+};
 use crate::state::{
-    claim_reward_info_r, config_r, reward_token_list_r, reward_token_r, stakers_r,
-    total_staked_r,
+    claim_reward_info_r, config_r, emergency_mode_r, lock_positions_r, receipt_token_r,
+    retired_reward_tokens_r, reward_token_list_r, reward_token_r,
+    stakers_r, total_staked_r, unbonding_period_r, unbondings_r,
 };
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, Env, StdError, StdResult, Storage, Uint128};
 use shadeswap_shared::core::TokenType;
-use shadeswap_shared::staking::{ClaimableInfo, QueryResponse, RewardTokenInfo};
+use shadeswap_shared::staking::{ClaimableInfo, QueryResponse, RewardProgram, RewardTokenInfo};
 use shadeswap_shared::utils::asset::Contract;
 
 pub fn config(deps: Deps) -> StdResult<Binary> {
@@ -27,6 +31,11 @@ pub fn config(deps: Deps) -> StdResult<Binary> {
             total_staked_lp_token: total_staked_r(deps.storage)
                 .may_load()?
                 .map_or_else(|| Uint128::zero(), |v| v),
+            unbonding_period: unbonding_period_r(deps.storage).may_load()?.unwrap_or_default(),
+            emergency_mode: emergency_mode_r(deps.storage).may_load()?.unwrap_or_default(),
+            receipt_token: receipt_token_r(deps.storage)
+                .may_load()?
+                .and_then(|receipt_token| receipt_token.contract()),
         };
         return to_binary(&response);
     } else {
@@ -40,10 +49,7 @@ pub fn claim_reward_for_user(deps: Deps, staker: Addr, time: Uint128) -> StdResu
 
     let reward_list = reward_token_list_r(deps.storage).load()?;
     let staker_info_option = stakers_r(deps.storage).may_load(staker.as_bytes())?;
-    let total_staked = match total_staked_r(deps.storage).may_load()? {
-        Some(s) => s,
-        None => Uint128::zero(),
-    };
+    let total_weight = total_weight(deps.storage)?;
 
     match staker_info_option {
         Some(staker_info) => {
@@ -60,8 +66,8 @@ pub fn claim_reward_for_user(deps: Deps, staker: Addr, time: Uint128) -> StdResu
                         result_list.push(ClaimableInfo {
                             token_address: claim_info.reward_token.unique_key(),
                             amount: earned(
-                                staker_info.amount,
-                                reward_per_token(time, &reward_token_info, total_staked)?,
+                                staker_info.weight(),
+                                reward_per_token(time, &reward_token_info, total_weight)?,
                                 claim_info.reward_token_per_token_paid,
                                 claim_info.rewards,
                             )?
@@ -70,6 +76,41 @@ pub fn claim_reward_for_user(deps: Deps, staker: Addr, time: Uint128) -> StdResu
                     None => (),
                 }
             }
+            // Retired tokens stop at their final reward per token
+            for retired in retired_reward_tokens_r(deps.storage)
+                .may_load()?
+                .unwrap_or_default()
+            {
+                let key = get_user_claim_key(staker.to_string(), retired.reward_token.unique_key());
+                let claim_info = claim_reward_info_r(deps.storage).may_load(key.as_bytes())?;
+                let settled = retired_rewards_settled(
+                    deps.storage,
+                    &staker,
+                    &retired.reward_token.unique_key(),
+                )?;
+                let amount = match claim_info {
+                    Some(claim_info) if settled => claim_info.rewards,
+                    Some(claim_info) => earned(
+                        staker_info.weight(),
+                        retired.reward_per_token,
+                        claim_info.reward_token_per_token_paid,
+                        claim_info.rewards,
+                    )?,
+                    None if settled => Uint128::zero(),
+                    None => earned(
+                        staker_info.weight(),
+                        retired.reward_per_token,
+                        Uint128::zero(),
+                        Uint128::zero(),
+                    )?,
+                };
+                if !amount.is_zero() {
+                    result_list.push(ClaimableInfo {
+                        token_address: retired.reward_token.unique_key(),
+                        amount,
+                    });
+                }
+            }
         }
         None => (),
     }
@@ -92,7 +133,7 @@ pub fn staking_stake_lp_token_info(deps: Deps, staker: Addr) -> StdResult<Binary
     to_binary(&response_msg)
 }
 
-pub fn reward_token_list(storage: &dyn Storage) -> StdResult<Binary> {
+pub fn reward_token_list(storage: &dyn Storage, include_retired: bool) -> StdResult<Binary> {
     let list: Vec<RewardTokenInfo> = get_reward_tokens_info(storage)?;
     let mut response: Vec<RewardTokenInfo> = vec![];
     for i in list.iter() {
@@ -100,5 +141,77 @@ pub fn reward_token_list(storage: &dyn Storage) -> StdResult<Binary> {
         reward_token.reward_rate = i.reward_rate / MAX_DECIMALS;
         response.push(reward_token);
     }
-    to_binary(&QueryResponse::GetRewardTokens { tokens: response })
+    let retired_tokens = if include_retired {
+        retired_reward_tokens_r(storage).may_load()?.unwrap_or_default()
+    } else {
+        vec![]
+    };
+    to_binary(&QueryResponse::GetRewardTokens {
+        tokens: response,
+        retired_tokens,
+    })
+}
+
+pub fn reward_programs(deps: Deps, env: &Env) -> StdResult<Binary> {
+    let current_timestamp = Uint128::new(env.block.time.seconds() as u128);
+    let mut programs: Vec<RewardProgram> = vec![];
+    for reward_token in get_reward_tokens_info(deps.storage)? {
+        let remaining_budget = if current_timestamp < reward_token.valid_to {
+            (reward_token.valid_to - current_timestamp)
+                .checked_mul(reward_token.reward_rate)?
+                .checked_div(MAX_DECIMALS)?
+        } else {
+            Uint128::zero()
+        };
+        programs.push(RewardProgram {
+            reward_token: reward_token.reward_token,
+            reward_rate: reward_token.reward_rate / MAX_DECIMALS,
+            remaining_budget,
+            ends_at: reward_token.valid_to,
+        });
+    }
+    to_binary(&QueryResponse::GetRewardPrograms {
+        programs,
+        funders: config_r(deps.storage).load()?.reward_funders,
+    })
+}
+
+pub fn lock_config(deps: Deps) -> StdResult<Binary> {
+    to_binary(&QueryResponse::GetLockConfig {
+        lock_config: load_lock_config(deps.storage)?,
+    })
+}
+
+pub fn lock_positions(deps: Deps, staker: Addr) -> StdResult<Binary> {
+    let positions = lock_positions_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .map_or_else(|| vec![], |v| v.positions);
+    let weight = stakers_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .map_or_else(|| Uint128::zero(), |v| v.weight());
+    to_binary(&QueryResponse::GetLockPositions {
+        positions,
+        weight,
+        total_weight: total_weight(deps.storage)?,
+    })
+}
+
+pub fn unbondings(deps: Deps, env: &Env, staker: Addr) -> StdResult<Binary> {
+    let unbondings = unbondings_r(deps.storage)
+        .may_load(staker.as_bytes())?
+        .map_or_else(|| vec![], |v| v);
+    let claimable = unbondings
+        .iter()
+        .filter(|unbonding| unbonding.release_at <= env.block.time.seconds())
+        .fold(Uint128::zero(), |total, unbonding| total + unbonding.amount);
+    to_binary(&QueryResponse::GetUnbondings {
+        unbondings,
+        claimable,
+    })
+}
+
+pub fn reward_recipient(deps: Deps, staker: Addr) -> StdResult<Binary> {
+    to_binary(&QueryResponse::GetRewardRecipient {
+        recipient: crate::operations::reward_recipient(deps.storage, &staker)?,
+    })
 }
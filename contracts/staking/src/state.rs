@@ -1,7 +1,7 @@
 use cosmwasm_std::{Addr, Uint128, Storage};
 use cosmwasm_storage::{singleton, Singleton, ReadonlySingleton, singleton_read, bucket_read, bucket, ReadonlyBucket, Bucket};
 use serde::{Serialize, Deserialize};
-use shadeswap_shared::{core::{TokenType, ViewingKey}, Contract, staking::RewardTokenInfo};
+use shadeswap_shared::{core::{TokenType, VIEWING_KEY_SIZE}, Contract, staking::{LockConfig, LockPosition, RetiredRewardToken, RewardTokenInfo, Unbonding}};
 
 pub static CONFIG: &[u8] = b"CONFIG";
 pub static STAKERS: &[u8] = b"LIST_STAKERS";
@@ -17,21 +17,52 @@ pub static REWARD_TOKEN_INFO: &[u8] = b"REWARD_TOKEN_INFO";
 pub static REWARD_TOKEN_LIST: &[u8] = b"REWARD_TOKEN_LIST";
 pub static PROXY_STAKE: &[u8] = b"PROXY_STAKE";
 pub static REWARD_TOKEN_PER_TOKEN_PAID: &[u8] = b"REWARD_TOKEN_PER_TOKEN_PAID";
+pub static LOCK_CONFIG: &[u8] = b"LOCK_CONFIG";
+pub static LOCK_POSITIONS: &[u8] = b"LOCK_POSITIONS";
+pub static TOTAL_BOOST: &[u8] = b"TOTAL_BOOST";
+pub static UNBONDING_PERIOD: &[u8] = b"UNBONDING_PERIOD";
+pub static UNBONDINGS: &[u8] = b"UNBONDINGS";
+pub static REWARD_RECIPIENTS: &[u8] = b"REWARD_RECIPIENTS";
+pub static RETIRED_REWARD_TOKENS: &[u8] = b"RETIRED_REWARD_TOKENS";
+pub static RETIRED_REWARDS_SETTLED: &[u8] = b"RETIRED_REWARDS_SETTLED";
+pub static EMERGENCY_MODE: &[u8] = b"EMERGENCY_MODE";
+pub static RECEIPT_TOKEN: &[u8] = b"RECEIPT_TOKEN";
+pub static REWARD_TOKEN_RETIREMENTS: &[u8] = b"REWARD_TOKEN_RETIREMENTS";
+pub static RESUMED_REWARD_PER_TOKEN: &[u8] = b"RESUMED_REWARD_PER_TOKEN";
 
 #[derive(Serialize, Deserialize,  PartialEq, Debug)]
 pub struct Config {
     pub amm_pair: Addr,
-    pub daily_reward_amount: Uint128,
     pub reward_token: TokenType,
     pub lp_token: Contract,
     pub authenticator: Option<Contract>,
-    pub admin_auth: Contract
+    pub admin_auth: Contract,
+    // Addresses allowed to fund rewards, None lets anyone fund
+    pub reward_funders: Option<Vec<Addr>>,
 }
 
 #[derive(Serialize, Deserialize,  PartialEq, Debug)]
 pub struct StakingInfo{
     pub amount: Uint128,
-    pub proxy_staked: Uint128
+    pub proxy_staked: Uint128,
+    // Part of amount held in lock positions
+    #[serde(default)]
+    pub locked: Uint128,
+    // Extra reward weight granted by lock multipliers
+    #[serde(default)]
+    pub boost: Uint128
+}
+
+impl StakingInfo {
+    pub fn weight(&self) -> Uint128 {
+        self.amount + self.boost
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct StakerLocks{
+    pub next_id: u64,
+    pub positions: Vec<LockPosition>
 }
 
 #[derive(Serialize, Deserialize,  PartialEq, Debug)]
@@ -51,6 +82,22 @@ pub struct ClaimRewardsInfo{
     pub reward_token_per_token_paid: Uint128
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ReceiptToken{
+    pub code_hash: String,
+    // None until the instantiate reply arrives
+    pub address: Option<Addr>
+}
+
+impl ReceiptToken {
+    pub fn contract(&self) -> Option<Contract> {
+        self.address.as_ref().map(|address| Contract {
+            address: address.clone(),
+            code_hash: self.code_hash.clone(),
+        })
+    }
+}
+
 pub fn config_w(storage: &mut dyn Storage) -> Singleton<Config> {
     singleton(storage, CONFIG)
 }
@@ -83,11 +130,12 @@ pub fn claim_reward_info_r(storage: &dyn Storage) -> ReadonlyBucket<ClaimRewards
     bucket_read(storage, CLAIM_REWARDS)
 }
 
-pub fn stakers_vk_w(storage: &mut dyn Storage) -> Bucket<ViewingKey> {
+// Hashed viewing keys, the keys themselves are never stored
+pub fn stakers_vk_w(storage: &mut dyn Storage) -> Bucket<[u8; VIEWING_KEY_SIZE]> {
     bucket(storage, STAKER_VK)
 }
 
-pub fn stakers_vk_r(storage: &dyn Storage) -> ReadonlyBucket<ViewingKey> {
+pub fn stakers_vk_r(storage: &dyn Storage) -> ReadonlyBucket<[u8; VIEWING_KEY_SIZE]> {
     bucket_read(storage, STAKER_VK)
 }
 
@@ -122,3 +170,102 @@ pub fn reward_token_list_w(storage: &mut dyn Storage) -> Singleton<Vec<String>>
 pub fn reward_token_list_r(storage: &dyn Storage) -> ReadonlySingleton<Vec<String>> {
     singleton_read(storage, REWARD_TOKEN_LIST)
 }
+
+pub fn lock_config_w(storage: &mut dyn Storage) -> Singleton<LockConfig> {
+    singleton(storage, LOCK_CONFIG)
+}
+
+pub fn lock_config_r(storage: &dyn Storage) -> ReadonlySingleton<LockConfig> {
+    singleton_read(storage, LOCK_CONFIG)
+}
+
+pub fn lock_positions_w(storage: &mut dyn Storage) -> Bucket<StakerLocks> {
+    bucket(storage, LOCK_POSITIONS)
+}
+
+pub fn lock_positions_r(storage: &dyn Storage) -> ReadonlyBucket<StakerLocks> {
+    bucket_read(storage, LOCK_POSITIONS)
+}
+
+pub fn total_boost_w(storage: &mut dyn Storage) -> Singleton<Uint128> {
+    singleton(storage, TOTAL_BOOST)
+}
+
+pub fn total_boost_r(storage: &dyn Storage) -> ReadonlySingleton<Uint128> {
+    singleton_read(storage, TOTAL_BOOST)
+}
+
+pub fn unbonding_period_w(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, UNBONDING_PERIOD)
+}
+
+pub fn unbonding_period_r(storage: &dyn Storage) -> ReadonlySingleton<u64> {
+    singleton_read(storage, UNBONDING_PERIOD)
+}
+
+pub fn unbondings_w(storage: &mut dyn Storage) -> Bucket<Vec<Unbonding>> {
+    bucket(storage, UNBONDINGS)
+}
+
+pub fn unbondings_r(storage: &dyn Storage) -> ReadonlyBucket<Vec<Unbonding>> {
+    bucket_read(storage, UNBONDINGS)
+}
+
+pub fn reward_recipients_w(storage: &mut dyn Storage) -> Bucket<Addr> {
+    bucket(storage, REWARD_RECIPIENTS)
+}
+
+pub fn reward_recipients_r(storage: &dyn Storage) -> ReadonlyBucket<Addr> {
+    bucket_read(storage, REWARD_RECIPIENTS)
+}
+
+pub fn retired_reward_tokens_w(storage: &mut dyn Storage) -> Singleton<Vec<RetiredRewardToken>> {
+    singleton(storage, RETIRED_REWARD_TOKENS)
+}
+
+pub fn retired_reward_tokens_r(storage: &dyn Storage) -> ReadonlySingleton<Vec<RetiredRewardToken>> {
+    singleton_read(storage, RETIRED_REWARD_TOKENS)
+}
+
+// Keyed by user claim key, the retirement of the token the staker's rewards were settled for
+pub fn retired_rewards_settled_w(storage: &mut dyn Storage) -> Bucket<u64> {
+    bucket(storage, RETIRED_REWARDS_SETTLED)
+}
+
+pub fn retired_rewards_settled_r(storage: &dyn Storage) -> ReadonlyBucket<u64> {
+    bucket_read(storage, RETIRED_REWARDS_SETTLED)
+}
+
+// Keyed by reward token, how many times the token was removed
+pub fn reward_token_retirements_w(storage: &mut dyn Storage) -> Bucket<u64> {
+    bucket(storage, REWARD_TOKEN_RETIREMENTS)
+}
+
+pub fn reward_token_retirements_r(storage: &dyn Storage) -> ReadonlyBucket<u64> {
+    bucket_read(storage, REWARD_TOKEN_RETIREMENTS)
+}
+
+// Keyed by reward token, the final reward per token of its last retirement
+pub fn resumed_reward_per_token_w(storage: &mut dyn Storage) -> Bucket<Uint128> {
+    bucket(storage, RESUMED_REWARD_PER_TOKEN)
+}
+
+pub fn resumed_reward_per_token_r(storage: &dyn Storage) -> ReadonlyBucket<Uint128> {
+    bucket_read(storage, RESUMED_REWARD_PER_TOKEN)
+}
+
+pub fn emergency_mode_w(storage: &mut dyn Storage) -> Singleton<bool> {
+    singleton(storage, EMERGENCY_MODE)
+}
+
+pub fn emergency_mode_r(storage: &dyn Storage) -> ReadonlySingleton<bool> {
+    singleton_read(storage, EMERGENCY_MODE)
+}
+
+pub fn receipt_token_w(storage: &mut dyn Storage) -> Singleton<ReceiptToken> {
+    singleton(storage, RECEIPT_TOKEN)
+}
+
+pub fn receipt_token_r(storage: &dyn Storage) -> ReadonlySingleton<ReceiptToken> {
+    singleton_read(storage, RECEIPT_TOKEN)
+}
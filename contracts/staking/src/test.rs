@@ -7,6 +7,7 @@ pub const STAKER_A: &str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czcmy2";
 pub const STAKER_B: &str = "secret1pf42ypa2awg0pxkx8lfyyrjvm28vq0qpffa8qx";
 pub const STAKER_C: &str = "secret1nulgwu6es24us9urgyvms7y02txyg0s02msgzw";
 pub const SENDER: &str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czcmt2";
+pub const RECEIPT_TOKEN: &str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czcmr3";
 
 #[cfg(test)]
 pub mod tests {
@@ -18,21 +19,28 @@ pub mod tests {
     };
     use shadeswap_shared::{
         query_auth::PermitData,
-        staking::{AuthQuery, ExecuteMsg, QueryData, QueryMsg, QueryResponse, RewardTokenInfo},
-        utils::asset::Contract,
+        staking::{
+            AuthQuery, ExecuteMsg, ExecuteMsgResponse, InvokeMsg, QueryData, QueryMsg,
+            QueryResponse, RewardTokenInfo,
+        },
     };
 
     use super::*;
     use crate::{
         contract::{auth_queries, execute, query},
         operations::{
-            calculate_staker_shares, claim_rewards, earned, generate_proxy_staking_key,
-            get_user_claim_key, reward_per_token, set_reward_token, stake, unstake, update_reward,
+            calculate_staker_shares, claim_rewards, claim_unbonded, earned, emergency_unstake,
+            fund_rewards, generate_proxy_staking_key, get_user_claim_key, lock_stake,
+            register_receipt_token, remove_reward_token, reward_per_token, set_reward_token, stake,
+            total_weight, unlock, unstake, update_reward, MAX_DECIMALS, RECEIPT_POOL,
+            SECONDS_IN_MONTH,
         },
         query::{self},
         state::{
-            claim_reward_info_r, config_w, proxy_staker_info_r, reward_token_list_r,
-            reward_token_r, stakers_r, total_staked_r, total_staked_w, ClaimRewardsInfo, Config,
+            claim_reward_info_r, config_r, config_w, emergency_mode_w, proxy_staker_info_r,
+            receipt_token_w, retired_reward_tokens_r, reward_token_list_r, reward_token_r,
+            stakers_r, total_staked_r, total_staked_w, unbonding_period_w, ClaimRewardsInfo,
+            Config, ReceiptToken,
         },
         test::test_help_lib::{
             make_init_config, make_reward_token_contract, mock_custom_env, mock_dependencies,
@@ -42,7 +50,8 @@ pub mod tests {
     use cosmwasm_std::{
         from_binary,
         testing::{mock_info, MockApi, MockStorage},
-        to_binary, Addr, Binary, Decimal, MessageInfo, StdError, StdResult, Timestamp, Uint128,
+        to_binary, Addr, Attribute, Binary, Decimal, Env, MessageInfo, Response, StdError,
+        StdResult, Timestamp, Uint128,
     };
 
     use shadeswap_shared::utils::testing::assert_error;
@@ -60,7 +69,7 @@ pub mod tests {
         let mut deps = mock_dependencies(&[]);
         let env = mock_custom_env(CONTRACT_ADDRESS, 1571797523, 1524);
         let config: Config = make_init_config(deps.as_mut(), &env, Uint128::from(100u128))?;
-        assert_eq!(config.daily_reward_amount, Uint128::from(100u128));
+        assert_eq!(config.reward_funders, None);
         assert_eq!(
             config.reward_token,
             TokenType::CustomToken {
@@ -101,6 +110,19 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn assert_init_reward_token_starts_unfunded() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1571797523, 1524);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        let reward_token_info =
+            reward_token_r(deps.as_ref().storage).load(reward_token().unique_key().as_bytes())?;
+        // Nothing is emitted until a FundRewards Send backs it
+        assert_eq!(reward_token_info.reward_rate, Uint128::zero());
+        assert_eq!(reward_token_info.valid_to, Uint128::new(1524u128));
+        Ok(())
+    }
+
     #[test]
     fn assert_get_reward_token_list_success() -> StdResult<()> {
         let mut deps = mock_dependencies(&[]);
@@ -109,18 +131,18 @@ pub mod tests {
         set_reward_token(
             deps.as_mut(),
             &env,
-            Uint128::new(100000000000u128),
             TokenType::CustomToken {
                 contract_addr: Addr::unchecked("REWARD_TOKEN_A".to_string()),
                 token_code_hash: "".to_string(),
             },
-            Uint128::new(30000000000000u128),
         )?;
-        let auth_query = shadeswap_shared::staking::QueryMsg::GetRewardTokens {};
+        let auth_query = shadeswap_shared::staking::QueryMsg::GetRewardTokens {
+            include_retired: None,
+        };
         let raw_response = query(deps.as_ref(), env, auth_query)?;
         let query_response: QueryResponse = from_binary(&raw_response)?;
         match query_response {
-            QueryResponse::GetRewardTokens { tokens } => {
+            QueryResponse::GetRewardTokens { tokens, .. } => {
                 assert_eq!(tokens.len(), 2);
             }
             _ => todo!(),
@@ -706,11 +728,15 @@ pub mod tests {
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env_b,
+            None,
+            None,
         )?;
         claim_rewards(
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_B)?,
             &env_b,
+            None,
+            None,
         )?;
 
         let claim_reward_info_a: ClaimRewardsInfo = claim_reward_info_r(deps.as_mut().storage)
@@ -749,11 +775,15 @@ pub mod tests {
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env_b,
+            None,
+            None,
         )?;
         claim_rewards(
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_B)?,
             &env_b,
+            None,
+            None,
         )?;
 
         let claim_reward_info_a: ClaimRewardsInfo = claim_reward_info_r(deps.as_mut().storage)
@@ -1062,12 +1092,16 @@ pub mod tests {
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env,
+            None,
+            None,
         )?;
 
         claim_rewards(
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env,
+            None,
+            None,
         )?;
         let claim_reward_info_a = claim_reward_info_r(deps.as_mut().storage).load(
             get_user_claim_key(
@@ -1130,12 +1164,10 @@ pub mod tests {
         set_reward_token(
             deps.as_mut(),
             &mock_custom_env(CONTRACT_ADDRESS, 15834, 17000000),
-            Uint128::new(300000u128),
             TokenType::CustomToken {
                 contract_addr: Addr::unchecked(CONTRACT_ADDRESS),
                 token_code_hash: "".to_string(),
             },
-            Uint128::new(19000000u128),
         )?;
 
         env.block.time = Timestamp::from_seconds(1700000u64);
@@ -1144,11 +1176,15 @@ pub mod tests {
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env,
+            None,
+            None,
         )?;
         claim_rewards(
             deps.as_mut(),
             &deps_owned.as_mut().api.addr_validate(STAKER_A)?,
             &env,
+            None,
+            None,
         )?;
         let claim_reward_info_a = claim_reward_info_r(deps.as_mut().storage).load(
             get_user_claim_key(
@@ -1175,9 +1211,9 @@ pub mod tests {
     }
 
     /**
-     * 3000000 seconds pass
+     * 10416666 funded over 3000000 seconds
      * Ratio is 0.6/0.4
-     * 0.6 * 3000000 * 3 = 5400000
+     * 0.6 * 10416666 = 6249999
      */
     #[test]
     fn assert_claim_reward_no_change_last_time_reward_info() -> StdResult<()> {
@@ -1187,7 +1223,7 @@ pub mod tests {
         let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
         let staker_a = &deps_owned.as_mut().api.addr_validate(STAKER_A)?;
         let staker_b = deps_owned.as_mut().api.addr_validate(STAKER_B)?;
-        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::new(300000u128))?;
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
         let _stake_a = stake(
             deps.as_mut(),
             &env,
@@ -1204,19 +1240,21 @@ pub mod tests {
             &staker_b,
             &staker_b,
         )?;
-        set_reward_token(
+        fund_rewards(
             deps.as_mut(),
-            &mock_custom_env(CONTRACT_ADDRESS, 15834, 17000000),
-            Uint128::new(300000u128),
-            reward_token(),
-            Uint128::new(19000000u128),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(10416666u128),
+            3000000u64,
         )?;
-        let current_timestamp = Uint128::from(21000000u128);
 
         claim_rewards(
             deps.as_mut(),
             &staker_a,
             &mock_custom_env(&CONTRACT_ADDRESS, 1600, 21000000),
+            None,
+            None,
         )?;
         let claim_reward_info_a = claim_reward_info_r(deps.as_mut().storage).load(
             get_user_claim_key(
@@ -1242,6 +1280,755 @@ pub mod tests {
         assert_eq!(claim_reward_info_b.rewards, Uint128::new(6249999u128));
         Ok(())
     }
+
+    #[test]
+    fn assert_fund_rewards_rolls_over_leftover_budget() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(1000u128),
+            1000u64,
+        )?;
+        // half of the first program is left when it is topped up
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16000500);
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(1500u128),
+            1000u64,
+        )?;
+        let reward_token_info = reward_token_r(deps.as_mut().storage)
+            .load(REWARD_TOKEN.as_bytes())?;
+        assert_eq!(reward_token_info.reward_rate, Uint128::new(2 * 10u128.pow(18)));
+        assert_eq!(reward_token_info.valid_to, Uint128::new(16001500u128));
+
+        let raw_response = query(deps.as_ref(), env.clone(), QueryMsg::GetRewardPrograms {})?;
+        let query_response: QueryResponse = from_binary(&raw_response)?;
+        match query_response {
+            QueryResponse::GetRewardPrograms { programs, funders } => {
+                assert_eq!(programs.len(), 1);
+                assert_eq!(programs[0].reward_rate, Uint128::new(2u128));
+                assert_eq!(programs[0].remaining_budget, Uint128::new(2000u128));
+                assert_eq!(funders, None);
+            }
+            _ => panic!("Query Response does not match"),
+        };
+
+        let mut config = config_r(deps.as_mut().storage).load()?;
+        config.reward_funders = Some(vec![Addr::unchecked(SENDER)]);
+        config_w(deps.as_mut().storage).save(&config)?;
+        assert_error(
+            fund_rewards(
+                deps.as_mut(),
+                &env,
+                &Addr::unchecked(REWARD_TOKEN),
+                &Addr::unchecked(STAKER_A),
+                Uint128::new(1000u128),
+                1000u64,
+            ),
+            format!("{} is not allowed to fund rewards.", STAKER_A),
+        );
+        assert_error(
+            fund_rewards(
+                deps.as_mut(),
+                &env,
+                &Addr::unchecked(REWARD_TOKEN),
+                &Addr::unchecked(SENDER),
+                Uint128::new(1000u128),
+                0u64,
+            ),
+            "Reward duration must be greater than zero.".to_string(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn assert_lock_stake_boosts_weight_and_penalises_early_unlock() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let staker_b = Addr::unchecked(STAKER_B);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(1000u128),
+            &staker_a,
+            &staker_a,
+        )?;
+        assert_error(
+            lock_stake(
+                deps.as_mut(),
+                &env,
+                &mock_info_lp_token,
+                Uint128::new(1000u128),
+                &staker_b,
+                5u64,
+            ),
+            "No lock tier with a duration of 5 seconds.".to_string(),
+        );
+        // one month lock has a 1.1 multiplier
+        lock_stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(1000u128),
+            &staker_b,
+            SECONDS_IN_MONTH,
+        )?;
+        assert_eq!(total_weight(&deps.storage)?, Uint128::new(2100u128));
+        assert_eq!(
+            stakers_r(&deps.storage).load(staker_b.as_bytes())?.weight(),
+            Uint128::new(1100u128)
+        );
+        assert_error(
+            unstake(deps.as_mut(), &env, &staker_b, &staker_b, Uint128::new(1u128), None),
+            "Unstaking Amount is higher then actual staking amount".to_string(),
+        );
+
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(2100u128),
+            1000u64,
+        )?;
+
+        // half way through the lock, half of the 50% penalty is charged
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16000000 + SECONDS_IN_MONTH / 2);
+        assert_error(
+            unlock(deps.as_mut(), &env, &staker_b, 1u64, None),
+            "Lock position 1 does not exist.".to_string(),
+        );
+        // without a penalty recipient the LP token has to be a registered reward token
+        assert_error(
+            unlock(deps.as_mut(), &env, &staker_b, 0u64, None),
+            "Register the LP token as a reward token or set a penalty recipient to unlock early."
+                .to_string(),
+        );
+        set_reward_token(
+            deps.as_mut(),
+            &env,
+            TokenType::CustomToken {
+                contract_addr: Addr::unchecked(LP_TOKEN),
+                token_code_hash: "".to_string(),
+            },
+        )?;
+        let response = unlock(deps.as_mut(), &env, &staker_b, 0u64, None)?;
+        let penalty = response
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key == "penalty")
+            .unwrap();
+        assert_eq!(penalty.value, "250");
+        let claim_reward_info_b = claim_reward_info_r(&deps.storage).load(
+            get_user_claim_key(staker_b.to_string(), REWARD_TOKEN.to_string()).as_bytes(),
+        )?;
+        assert_eq!(claim_reward_info_b.rewards, Uint128::zero());
+        assert_eq!(total_weight(&deps.storage)?, Uint128::new(1000u128));
+
+        // the penalty is paid to the remaining staker as LP token rewards
+        update_reward(&staker_a, deps.as_mut().storage, &env)?;
+        let claim_reward_info_a = claim_reward_info_r(&deps.storage).load(
+            get_user_claim_key(staker_a.to_string(), REWARD_TOKEN.to_string()).as_bytes(),
+        )?;
+        assert_eq!(claim_reward_info_a.rewards, Uint128::new(1000u128));
+        let claim_lp_reward_info_a = claim_reward_info_r(&deps.storage).load(
+            get_user_claim_key(staker_a.to_string(), LP_TOKEN.to_string()).as_bytes(),
+        )?;
+        assert_eq!(claim_lp_reward_info_a.rewards, Uint128::new(250u128));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_unstake_waits_for_unbonding_period() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        unbonding_period_w(deps.as_mut().storage).save(&1000u64)?;
+        stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(1000u128),
+            &staker_a,
+            &staker_a,
+        )?;
+
+        let response = unstake(
+            deps.as_mut(),
+            &env,
+            &staker_a,
+            &staker_a,
+            Uint128::new(400u128),
+            None,
+        )?;
+        assert_eq!(response.messages.len(), 0);
+        assert_eq!(total_staked_r(&deps.storage).load()?, Uint128::new(600u128));
+        assert_error(
+            claim_unbonded(deps.as_mut(), &env, &staker_a),
+            "No unbonded LP token to claim.".to_string(),
+        );
+
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1700, 16001000);
+        let raw_response = auth_queries(
+            deps.as_ref(),
+            env.clone(),
+            AuthQuery::GetUnbondings {},
+            staker_a.clone(),
+        )?;
+        let query_response: QueryResponse = from_binary(&raw_response)?;
+        match query_response {
+            QueryResponse::GetUnbondings {
+                unbondings,
+                claimable,
+            } => {
+                assert_eq!(unbondings.len(), 1);
+                assert_eq!(unbondings[0].release_at, 16001000u64);
+                assert_eq!(claimable, Uint128::new(400u128));
+            }
+            _ => panic!("Query Response does not match"),
+        };
+        let response = claim_unbonded(deps.as_mut(), &env, &staker_a)?;
+        assert_eq!(response.messages.len(), 1);
+        assert_error(
+            claim_unbonded(deps.as_mut(), &env, &staker_a),
+            "No unbonded LP token to claim.".to_string(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn assert_claim_rewards_pays_reward_recipient() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(1000u128),
+            &staker_a,
+            &staker_a,
+        )?;
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(10000u128),
+            1000u64,
+        )?;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(STAKER_A, &[]),
+            ExecuteMsg::SetRewardRecipient {
+                recipient: Some(STAKER_C.to_string()),
+            },
+        )?;
+        let query_response: QueryResponse = from_binary(&auth_queries(
+            deps.as_ref(),
+            env.clone(),
+            AuthQuery::GetRewardRecipient {},
+            staker_a.clone(),
+        )?)?;
+        assert_eq!(
+            query_response,
+            QueryResponse::GetRewardRecipient {
+                recipient: Addr::unchecked(STAKER_C)
+            }
+        );
+
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16000500);
+        assert_error(
+            claim_rewards(
+                deps.as_mut(),
+                &staker_a,
+                &env,
+                None,
+                Some(vec![TokenType::NativeToken {
+                    denom: "uscrt".to_string(),
+                }]),
+            ),
+            "uscrt is not a reward token.".to_string(),
+        );
+        let response = claim_rewards(deps.as_mut(), &staker_a, &env, None, None)?;
+        assert_eq!(response.messages.len(), 1);
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("recipient", STAKER_C)));
+
+        // An explicit recipient overrides the preference, also for a subset of tokens
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1700, 16001000);
+        let response = claim_rewards(
+            deps.as_mut(),
+            &staker_a,
+            &env,
+            Some(Addr::unchecked(STAKER_B)),
+            Some(vec![reward_token()]),
+        )?;
+        assert_eq!(response.messages.len(), 1);
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("recipient", STAKER_B)));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_removed_reward_token_settles_then_leaves_reward_loop() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let staker_b = Addr::unchecked(STAKER_B);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::zero())?;
+        for staker in [&staker_a, &staker_b] {
+            stake(
+                deps.as_mut(),
+                &env,
+                &mock_info_lp_token,
+                Uint128::new(1000u128),
+                staker,
+                staker,
+            )?;
+        }
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(10000u128),
+            1000u64,
+        )?;
+
+        // Half of the program is left and refunded
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16000500);
+        let response = remove_reward_token(
+            deps.as_mut(),
+            &env,
+            reward_token(),
+            &Addr::unchecked(STAKER_B),
+        )?;
+        assert_eq!(response.messages.len(), 1);
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("refund", Uint128::new(5000u128))));
+        assert!(reward_token_list_r(&deps.storage).load()?.is_empty());
+        assert_error(
+            set_reward_token(deps.as_mut(), &env, reward_token()),
+            format!("{} was retired as a reward token and is not settled yet.", REWARD_TOKEN),
+        );
+
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1700, 16000800);
+        let response = claim_rewards(deps.as_mut(), &staker_a, &env, None, None)?;
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("claim_amount", Uint128::new(2500u128))));
+        let retired = retired_reward_tokens_r(&deps.storage).load()?;
+        assert_eq!(retired[0].unsettled_weight, Uint128::new(1000u128));
+        assert_eq!(retired[0].unclaimed, Uint128::zero());
+
+        // Unstaking settles and pays staker b, the token is then dropped from the retired list
+        unstake(
+            deps.as_mut(),
+            &env,
+            &staker_b,
+            &staker_b,
+            Uint128::new(1000u128),
+            None,
+        )?;
+        assert!(retired_reward_tokens_r(&deps.storage).load()?.is_empty());
+
+        let raw_response = query(
+            deps.as_ref(),
+            env.clone(),
+            shadeswap_shared::staking::QueryMsg::GetRewardTokens {
+                include_retired: Some(true),
+            },
+        )?;
+        match from_binary(&raw_response)? {
+            QueryResponse::GetRewardTokens {
+                tokens,
+                retired_tokens,
+            } => {
+                assert!(tokens.is_empty());
+                assert!(retired_tokens.is_empty());
+            }
+            _ => panic!("Query Response does not match"),
+        };
+
+        // Registered again it resumes from its final reward per token
+        set_reward_token(deps.as_mut(), &env, reward_token())?;
+        assert_eq!(
+            reward_token_r(&deps.storage)
+                .load(reward_token().unique_key().as_bytes())?
+                .reward_per_token_stored,
+            Uint128::new(5000u128) * MAX_DECIMALS / Uint128::new(2000u128)
+        );
+        fund_rewards(
+            deps.as_mut(),
+            &env,
+            &Addr::unchecked(REWARD_TOKEN),
+            &Addr::unchecked(SENDER),
+            Uint128::new(1000u128),
+            1000u64,
+        )?;
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1800, 16001800);
+        let response = claim_rewards(deps.as_mut(), &staker_a, &env, None, None)?;
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("claim_amount", Uint128::new(1000u128))));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_emergency_unstake_forfeits_rewards_and_returns_locked_lp_token() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let mock_info_lp_token = mock_info(LP_TOKEN, &[]);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::new(100000u128))?;
+        unbonding_period_w(deps.as_mut().storage).save(&1000u64)?;
+        stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(1000u128),
+            &staker_a,
+            &staker_a,
+        )?;
+        lock_stake(
+            deps.as_mut(),
+            &env,
+            &mock_info_lp_token,
+            Uint128::new(500u128),
+            &staker_a,
+            SECONDS_IN_MONTH,
+        )?;
+
+        // Locked LP token stays locked and the unbonding period applies outside emergency mode
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16001000);
+        assert_error(
+            emergency_unstake(deps.as_mut(), &env, &staker_a, Uint128::new(1500u128)),
+            "Unstaking Amount is higher then actual staking amount".to_string(),
+        );
+        let response = emergency_unstake(deps.as_mut(), &env, &staker_a, Uint128::new(400u128))?;
+        assert_eq!(response.messages.len(), 0);
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("forfeited_reward_token", REWARD_TOKEN)));
+        let claim_info = claim_reward_info_r(&deps.storage).load(
+            get_user_claim_key(STAKER_A.to_string(), reward_token().unique_key()).as_bytes(),
+        )?;
+        assert_eq!(claim_info.rewards, Uint128::zero());
+
+        emergency_mode_w(deps.as_mut().storage).save(&true)?;
+        assert_error(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(LP_TOKEN, &[]),
+                ExecuteMsg::Receive {
+                    from: STAKER_A.to_string(),
+                    msg: Some(to_binary(&InvokeMsg::Stake {
+                        from: STAKER_A.to_string(),
+                    })?),
+                    amount: Uint128::new(100u128),
+                },
+            ),
+            "Staking is in emergency mode.".to_string(),
+        );
+
+        let response = emergency_unstake(deps.as_mut(), &env, &staker_a, Uint128::new(1100u128))?;
+        assert_eq!(response.messages.len(), 1);
+        let staker_info = stakers_r(&deps.storage).load(staker_a.as_bytes())?;
+        assert_eq!(staker_info.amount, Uint128::zero());
+        assert_eq!(staker_info.locked, Uint128::zero());
+        assert_eq!(total_weight(&deps.storage)?, Uint128::zero());
+        Ok(())
+    }
+
+    fn receipt_invoke(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        sender: &str,
+        from: &str,
+        amount: u128,
+        msg: InvokeMsg,
+    ) -> StdResult<Response> {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(sender, &[]),
+            ExecuteMsg::Receive {
+                from: from.to_string(),
+                msg: Some(to_binary(&msg)?),
+                amount: Uint128::new(amount),
+            },
+        )
+    }
+
+    #[test]
+    fn assert_receipt_tokens_redeem_pooled_stake_for_any_holder() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let staker_b = Addr::unchecked(STAKER_B);
+        let staker_c = Addr::unchecked(STAKER_C);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::new(100000u128))?;
+
+        assert_error(
+            receipt_invoke(
+                &mut deps,
+                &env,
+                LP_TOKEN,
+                STAKER_A,
+                1000,
+                InvokeMsg::StakeWithReceipt {},
+            ),
+            "Receipt token does not exist.".to_string(),
+        );
+        receipt_token_w(deps.as_mut().storage).save(&ReceiptToken {
+            code_hash: "".to_string(),
+            address: None,
+        })?;
+        assert_error(
+            receipt_invoke(
+                &mut deps,
+                &env,
+                LP_TOKEN,
+                STAKER_A,
+                1000,
+                InvokeMsg::StakeWithReceipt {},
+            ),
+            "Receipt token does not exist.".to_string(),
+        );
+        register_receipt_token(deps.as_mut().storage, &env, Addr::unchecked(RECEIPT_TOKEN))?;
+        let response = receipt_invoke(
+            &mut deps,
+            &env,
+            LP_TOKEN,
+            STAKER_A,
+            1000,
+            InvokeMsg::StakeWithReceipt {},
+        )?;
+        assert_eq!(response.messages.len(), 1);
+
+        // Receipted stake is held by the receipt pool, not by the staker
+        let pool = Addr::unchecked(RECEIPT_POOL);
+        assert!(stakers_r(&deps.storage)
+            .may_load(staker_a.as_bytes())?
+            .is_none());
+        assert_eq!(
+            stakers_r(&deps.storage).load(pool.as_bytes())?.amount,
+            Uint128::new(1000u128)
+        );
+        let transfer = InvokeMsg::TransferStake {
+            recipient: STAKER_C.to_string(),
+        };
+        assert_error(
+            receipt_invoke(&mut deps, &env, LP_TOKEN, STAKER_B, 100, transfer.clone()),
+            "Sender was not the receipt token.".to_string(),
+        );
+
+        // Staker A transfers the receipt tokens to staker B with a plain Transfer, which staking
+        // never sees. Staker B redeems them for the LP token and their share of the pool's rewards.
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1600, 16001000);
+        update_reward(&pool, deps.as_mut().storage, &env)?;
+        let pool_claim_key =
+            get_user_claim_key(RECEIPT_POOL.to_string(), reward_token().unique_key());
+        let pool_rewards = claim_reward_info_r(&deps.storage)
+            .load(pool_claim_key.as_bytes())?
+            .rewards;
+        assert!(pool_rewards > Uint128::zero());
+        let response = receipt_invoke(
+            &mut deps,
+            &env,
+            RECEIPT_TOKEN,
+            STAKER_B,
+            400,
+            InvokeMsg::UnstakeReceipt {
+                remove_liquidity: Some(false),
+            },
+        )?;
+        assert_eq!(response.messages.len(), 3);
+        let share = pool_rewards.multiply_ratio(400u128, 1000u128);
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("claim_amount", share)));
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("staker", STAKER_B)));
+        let pool_claim = claim_reward_info_r(&deps.storage).load(pool_claim_key.as_bytes())?;
+        assert_eq!(pool_claim.rewards, pool_rewards - share);
+        assert_eq!(
+            stakers_r(&deps.storage).load(pool.as_bytes())?.amount,
+            Uint128::new(600u128)
+        );
+        assert_eq!(
+            stakers_r(&deps.storage).load(staker_b.as_bytes())?.amount,
+            Uint128::zero()
+        );
+        assert!(stakers_r(&deps.storage)
+            .may_load(staker_a.as_bytes())?
+            .is_none());
+        assert_eq!(total_staked_r(&deps.storage).load()?, Uint128::new(600u128));
+
+        assert_error(
+            receipt_invoke(
+                &mut deps,
+                &env,
+                RECEIPT_TOKEN,
+                STAKER_A,
+                601,
+                InvokeMsg::UnstakeReceipt {
+                    remove_liquidity: None,
+                },
+            ),
+            "Receipt amount is higher than the receipted stake.".to_string(),
+        );
+
+        // TransferStake burns receipt tokens into plain stake of the recipient
+        let response = receipt_invoke(&mut deps, &env, RECEIPT_TOKEN, STAKER_B, 100, transfer)?;
+        assert_eq!(response.messages.len(), 1);
+        assert_eq!(
+            stakers_r(&deps.storage).load(pool.as_bytes())?.amount,
+            Uint128::new(500u128)
+        );
+        assert_eq!(
+            stakers_r(&deps.storage).load(staker_c.as_bytes())?.amount,
+            Uint128::new(100u128)
+        );
+        unstake(
+            deps.as_mut(),
+            &env,
+            &staker_c,
+            &staker_c,
+            Uint128::new(100u128),
+            None,
+        )?;
+        assert_eq!(total_staked_r(&deps.storage).load()?, Uint128::new(500u128));
+
+        // In emergency mode receipt tokens still redeem, without rewards
+        emergency_mode_w(deps.as_mut().storage).save(&true)?;
+        assert_error(
+            receipt_invoke(
+                &mut deps,
+                &env,
+                LP_TOKEN,
+                STAKER_A,
+                1000,
+                InvokeMsg::StakeWithReceipt {},
+            ),
+            "Staking is in emergency mode.".to_string(),
+        );
+        let response = receipt_invoke(
+            &mut deps,
+            &env,
+            RECEIPT_TOKEN,
+            STAKER_B,
+            300,
+            InvokeMsg::UnstakeReceipt {
+                remove_liquidity: None,
+            },
+        )?;
+        assert!(response
+            .attributes
+            .contains(&Attribute::new("action", "emergency_unstake")));
+        assert_eq!(
+            stakers_r(&deps.storage).load(pool.as_bytes())?.amount,
+            Uint128::new(200u128)
+        );
+        assert_eq!(
+            stakers_r(&deps.storage).load(staker_b.as_bytes())?.amount,
+            Uint128::zero()
+        );
+        assert_eq!(total_staked_r(&deps.storage).load()?, Uint128::new(200u128));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_viewing_key_authenticates_auth_queries() -> StdResult<()> {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_custom_env(CONTRACT_ADDRESS, 1500, 16000000);
+        let staker_a = Addr::unchecked(STAKER_A);
+        let _config: Config = make_init_config(deps.as_mut(), &env, Uint128::new(100000u128))?;
+        stake(
+            deps.as_mut(),
+            &env,
+            &mock_info(LP_TOKEN, &[]),
+            Uint128::new(1000u128),
+            &staker_a,
+            &staker_a,
+        )?;
+
+        let with_viewing_key = |address: &str, key: &str| QueryMsg::WithViewingKey {
+            address: address.to_string(),
+            key: key.to_string(),
+            query: AuthQuery::GetStakerLpTokenInfo {},
+        };
+        let error = "Wrong viewing key for this address or viewing key not set".to_string();
+        assert_eq!(
+            query(deps.as_ref(), env.clone(), with_viewing_key(STAKER_A, "")).unwrap_err(),
+            StdError::generic_err(error.clone())
+        );
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(STAKER_A, &[]),
+            ExecuteMsg::SetViewingKey {
+                key: "password".to_string(),
+            },
+        )?;
+        let raw_response =
+            query(deps.as_ref(), env.clone(), with_viewing_key(STAKER_A, "password"))?;
+        let query_response: QueryResponse = from_binary(&raw_response)?;
+        match query_response {
+            QueryResponse::GetStakerLpTokenInfo {
+                staked_lp_token,
+                total_staked_lp_token: _,
+            } => assert_eq!(staked_lp_token, Uint128::new(1000u128)),
+            _ => panic!("Query Response does not match"),
+        }
+        assert_eq!(
+            query(deps.as_ref(), env.clone(), with_viewing_key(STAKER_B, "password"))
+                .unwrap_err(),
+            StdError::generic_err(error.clone())
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(STAKER_A, &[]),
+            ExecuteMsg::CreateViewingKey {
+                entropy: "entropy".to_string(),
+            },
+        )?;
+        let ExecuteMsgResponse::CreateViewingKey { key } = from_binary(&response.data.unwrap())?;
+        assert_eq!(
+            query(deps.as_ref(), env.clone(), with_viewing_key(STAKER_A, "password"))
+                .unwrap_err(),
+            StdError::generic_err(error)
+        );
+        assert!(query(deps.as_ref(), env, with_viewing_key(STAKER_A, &key)).is_ok());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1264,6 +2051,7 @@ pub mod test_help_lib {
 
     use crate::{
         contract::instantiate,
+        operations::fund_rewards,
         state::{config_r, config_w, Config},
         test::tests::reward_token,
     };
@@ -1283,7 +2071,6 @@ pub mod test_help_lib {
     ) -> StdResult<Config> {
         let info = mock_info(SENDER, &[]);
         let msg = InitMsg {
-            daily_reward_amount: daily_reward_amount.clone(),
             reward_token: reward_token(),
             pair_contract: Contract {
                 address: deps.api.addr_validate(CONTRACT_ADDRESS)?,
@@ -1299,7 +2086,6 @@ pub mod test_help_lib {
                 address: Addr::unchecked("admin"),
                 code_hash: "".to_string(),
             },
-            valid_to: Uint128::new(3747905010000u128),
         };
         assert!(instantiate(deps.branch(), env.clone(), info.clone(), msg).is_ok());
         let mut config = config_r(deps.storage).load()?;
@@ -1308,6 +2094,17 @@ pub mod test_help_lib {
             code_hash: "".to_string(),
         };
         config_w(deps.storage).save(&config)?;
+        // Fund a long running program paying out daily_reward_amount per day
+        if !daily_reward_amount.is_zero() {
+            fund_rewards(
+                deps.branch(),
+                env,
+                &Addr::unchecked(REWARD_TOKEN),
+                &Addr::unchecked(SENDER),
+                daily_reward_amount * Uint128::new(36500u128),
+                86400u64 * 36500u64,
+            )?;
+        }
         Ok(config)
     }
 
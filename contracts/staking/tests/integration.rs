@@ -1,7 +1,7 @@
 use cosmwasm_std::{to_binary, Addr, ContractInfo, Empty};
 use multi_test::help_lib::integration_help_lib::mk_address;
 use secret_multi_test::{App, Contract, ContractWrapper, Executor};
-use shadeswap_shared::msg::staking::{ExecuteMsg, InitMsg, QueryResponse};
+use shadeswap_shared::msg::staking::{ExecuteMsg, InitMsg, InvokeMsg, QueryResponse};
 use staking::contract::{execute, instantiate, query};
 
 use shadeswap_shared::utils::asset::Contract as AuthContract;
@@ -15,7 +15,7 @@ use multi_test::admin::admin_help::init_admin_contract;
 use multi_test::help_lib::integration_help_lib::send_snip20_to_stake;
 use multi_test::help_lib::integration_help_lib::{
     convert_to_contract_link, get_current_block_time, mint_deposit_snip20, roll_blockchain,
-    send_snip20_to_proxy_stake, set_viewing_key, store_init_auth_contract,
+    send_snip20_to_proxy_stake, send_snip20_with_msg, set_viewing_key, store_init_auth_contract,
 };
 use multi_test::help_lib::integration_help_lib::{generate_snip20_contract, snip_20_balance_query};
 use multi_test::util_addr::util_addr::{OWNER, OWNER_PUB_KEY, PUB_KEY_STAKER_A, STAKER_A};
@@ -53,7 +53,6 @@ pub fn set_up_tests(
     let lp_token_contract =
         generate_snip20_contract(router, "LPT".to_string(), "LPT".to_string(), 18).unwrap();
     let init_msg = InitMsg {
-        daily_reward_amount: Uint128::new(300000u128),
         reward_token: TokenType::CustomToken {
             contract_addr: reward_contract.address.to_owned(),
             token_code_hash: reward_contract.code_hash.to_owned(),
@@ -72,7 +71,6 @@ pub fn set_up_tests(
             code_hash: auth_contract.code_hash.to_owned(),
         }),
         admin_auth: convert_to_contract_link(&admin_contract),
-        valid_to: Uint128::new(3747905010000u128),
     };
 
     let staking_contract = router
@@ -117,6 +115,9 @@ pub fn staking_integration_tests() {
             amm_pair: _,
             admin_auth: _,
             total_staked_lp_token,
+            unbonding_period: _,
+            emergency_mode: _,
+            receipt_token: _,
         } => {
             assert_eq!(
                 reward_token.address.to_string(),
@@ -154,10 +155,23 @@ pub fn staking_integration_tests() {
     mint_deposit_snip20(
         &mut router,
         &reward_contract,
-        &staking_contract.address,
-        Uint128::new(100000000),
+        &owner_addr,
+        Uint128::new(300000u128 * 36500u128),
         &owner_addr,
     );
+    // FUND 300000 REWARD TOKEN PER DAY
+    send_snip20_with_msg(
+        &mut router,
+        &reward_contract,
+        &staking_contract,
+        Uint128::new(300000u128 * 36500u128),
+        &owner_addr,
+        &to_binary(&InvokeMsg::FundRewards {
+            duration: 86400u64 * 36500u64,
+        })
+        .unwrap(),
+    )
+    .unwrap();
     // STAKE LP TOKEN
     send_snip20_to_stake(
         &mut router,
@@ -195,7 +209,10 @@ pub fn staking_integration_tests() {
     assert_eq!(balances.0, 1);
     assert_eq!(balances.1, Uint128::new(17361));
 
-    let msg = ExecuteMsg::ClaimRewards {};
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        tokens: None,
+    };
     router
         .execute_contract(
             owner_addr.to_owned(),
@@ -227,8 +244,6 @@ pub fn staking_integration_tests() {
             contract_addr: reward_contract_b.address.to_owned(),
             token_code_hash: reward_contract_b.code_hash.to_owned(),
         },
-        daily_reward_amount: Uint128::new(600000u128),
-        valid_to: Uint128::new(3747905010000u128),
     };
 
     let _ = router
@@ -242,10 +257,23 @@ pub fn staking_integration_tests() {
     mint_deposit_snip20(
         &mut router,
         &reward_contract_b,
-        &staking_contract.address,
-        Uint128::new(100000),
+        &owner_addr,
+        Uint128::new(600000u128 * 36500u128),
         &owner_addr,
     );
+    // FUND 600000 REWARD TOKEN B PER DAY
+    send_snip20_with_msg(
+        &mut router,
+        &reward_contract_b,
+        &staking_contract,
+        Uint128::new(600000u128 * 36500u128),
+        &owner_addr,
+        &to_binary(&InvokeMsg::FundRewards {
+            duration: 86400u64 * 36500u64,
+        })
+        .unwrap(),
+    )
+    .unwrap();
     // This will move time forwards 2500
     roll_blockchain(&mut router, 500).unwrap();
 
@@ -262,7 +290,10 @@ pub fn staking_integration_tests() {
     assert_eq!(balances.0, 1);
     assert_eq!(balances.1, Uint128::new(8680u128));
 
-    let msg = ExecuteMsg::ClaimRewards {};
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        tokens: None,
+    };
     router
         .execute_contract(
             Addr::unchecked(OWNER.to_owned()),
@@ -585,24 +616,26 @@ pub fn staking_integration_tests() {
         assert_eq!(balances.2, Uint128::new(118633u128));
     }
 
-    // CHANGE EXISTING REWARD TOKEN
-    let set_reward_msg = ExecuteMsg::SetRewardToken {
-        reward_token: TokenType::CustomToken {
-            contract_addr: reward_contract_b.address.to_owned(),
-            token_code_hash: reward_contract_b.code_hash.to_owned(),
-        },
-        daily_reward_amount: Uint128::new(500000u128),
-        valid_to: Uint128::new(3747905010000u128),
-    };
-
-    let _ = router
-        .execute_contract(
-            owner_addr.to_owned(),
-            &staking_contract,
-            &set_reward_msg,
-            &[],
-        )
-        .unwrap();
+    // TOP UP EXISTING REWARD TOKEN, LEFTOVER IS ROLLED INTO THE NEW RATE
+    mint_deposit_snip20(
+        &mut router,
+        &reward_contract_b,
+        &owner_addr,
+        Uint128::new(500000u128 * 36500u128),
+        &owner_addr,
+    );
+    send_snip20_with_msg(
+        &mut router,
+        &reward_contract_b,
+        &staking_contract,
+        Uint128::new(500000u128 * 36500u128),
+        &owner_addr,
+        &to_binary(&InvokeMsg::FundRewards {
+            duration: 86400u64 * 36500u64,
+        })
+        .unwrap(),
+    )
+    .unwrap();
 
     // Increment time by 5000
     roll_blockchain(&mut router, 1000).unwrap();
@@ -619,7 +652,7 @@ pub fn staking_integration_tests() {
         .unwrap();
         assert_eq!(balances.0, 2);
         assert_eq!(balances.1, Uint128::new(8680u128));
-        assert_eq!(balances.2, Uint128::new(15914u128));
+        assert_eq!(balances.2, Uint128::new(24594u128));
     }
 
     // Assert staker A balance
@@ -634,7 +667,7 @@ pub fn staking_integration_tests() {
         .unwrap();
         assert_eq!(balances.0, 2);
         assert_eq!(balances.1, Uint128::new(72337u128));
-        assert_eq!(balances.2, Uint128::new(140335u128));
+        assert_eq!(balances.2, Uint128::new(166376u128));
     }
 }
 
@@ -0,0 +1,216 @@
+use cosmwasm_std::{
+    entry_point, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo,
+    QueryRequest, Response, StdError, StdResult, Uint128, WasmQuery,
+};
+use shadeswap_shared::{
+    admin::helpers::{validate_admin, AdminPermissions},
+    amm_pair::{QueryMsg as AMMPairQueryMsg, QueryMsgResponse as AMMPairQueryResponse},
+    core::TokenType,
+    query_auth::helpers::{authenticate_permit, PermitAuthentication},
+    snip20::helpers::{register_receive, set_viewing_key_msg},
+    utils::{pad_query_result, pad_response_result},
+    vault::{AuthQuery, ExecuteMsg, InitMsg, InvokeMsg, QueryData, QueryMsg},
+    Contract,
+};
+
+use crate::{
+    operations::{
+        assert_no_unbonding_period, assert_vault_callback, claim_withdrawals, deposit, harvest,
+        provide_liquidity, restake, set_harvest_bounty, set_max_liquidity_slippage,
+        set_reference_price, set_reward_routes, swap_rewards, validate_harvest_bounty,
+        validate_max_liquidity_slippage, validate_reference_price, withdraw,
+    },
+    query,
+    state::{config_r, config_w, Config},
+};
+
+pub const BLOCK_SIZE: usize = 256;
+pub const SHADE_VAULT_VIEWKEY: &str = "SHADE_VAULT_VIEWKEY";
+
+#[entry_point]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InitMsg,
+) -> StdResult<Response> {
+    validate_harvest_bounty(msg.harvest_bounty_bps)?;
+    validate_max_liquidity_slippage(msg.max_liquidity_slippage_bps)?;
+    validate_reference_price(msg.reference_price)?;
+    assert_no_unbonding_period(deps.as_ref(), &msg.staking_contract)?;
+    let pair_info: AMMPairQueryResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: msg.pair_contract.address.to_string(),
+            code_hash: msg.pair_contract.code_hash.clone(),
+            msg: to_binary(&AMMPairQueryMsg::GetPairInfo {})?,
+        }))?;
+    let (lp_token, pair) = match pair_info {
+        AMMPairQueryResponse::GetPairInfo {
+            liquidity_token,
+            pair,
+            ..
+        } => (liquidity_token, pair),
+        _ => return Err(StdError::generic_err("Invalid pair info response")),
+    };
+
+    // Viewing keys let the vault read the balances it compounds
+    let mut messages = vec![
+        register_receive(env.contract.code_hash.clone(), None, &lp_token)?,
+        set_viewing_key_msg(SHADE_VAULT_VIEWKEY.to_string(), None, &lp_token)?,
+    ];
+    for token in pair.into_iter() {
+        if let TokenType::CustomToken {
+            contract_addr,
+            token_code_hash,
+        } = token
+        {
+            messages.push(set_viewing_key_msg(
+                SHADE_VAULT_VIEWKEY.to_string(),
+                None,
+                &Contract {
+                    address: contract_addr.clone(),
+                    code_hash: token_code_hash.clone(),
+                },
+            )?);
+        }
+    }
+
+    config_w(deps.storage).save(&Config {
+        pair_contract: msg.pair_contract,
+        pair,
+        lp_token,
+        staking_contract: msg.staking_contract,
+        router: msg.router,
+        harvest_bounty_bps: msg.harvest_bounty_bps,
+        max_liquidity_slippage_bps: msg.max_liquidity_slippage_bps,
+        reference_price: msg.reference_price,
+        reward_routes: vec![],
+        authenticator: msg.authenticator,
+        admin_auth: msg.admin_auth,
+    })?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("vault_contract_addr", env.contract.address))
+}
+
+#[entry_point]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    pad_response_result(
+        match msg {
+            ExecuteMsg::Receive {
+                from, amount, msg, ..
+            } => {
+                let checked_from = deps.api.addr_validate(&from)?;
+                receiver_callback(deps, env, info, checked_from, amount, msg)
+            }
+            ExecuteMsg::Withdraw { shares } => withdraw(deps, &env, &info.sender, shares),
+            ExecuteMsg::ClaimWithdrawals {} => claim_withdrawals(deps, &env, &info.sender),
+            ExecuteMsg::Harvest {} => harvest(deps, &env, &info.sender),
+            ExecuteMsg::SetRewardRoutes { routes } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_reward_routes(deps, routes)
+            }
+            ExecuteMsg::SetHarvestBounty { harvest_bounty_bps } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_harvest_bounty(deps, harvest_bounty_bps)
+            }
+            ExecuteMsg::SetMaxLiquiditySlippage {
+                max_liquidity_slippage_bps,
+            } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_max_liquidity_slippage(deps, max_liquidity_slippage_bps)
+            }
+            ExecuteMsg::SetReferencePrice { reference_price } => {
+                let config = config_r(deps.storage).load()?;
+                validate_admin(
+                    &deps.querier,
+                    AdminPermissions::ShadeSwapAdmin,
+                    &info.sender,
+                    &config.admin_auth,
+                )?;
+                set_reference_price(deps, reference_price)
+            }
+            ExecuteMsg::SwapRewards { harvester } => {
+                assert_vault_callback(&env, &info)?;
+                let checked_harvester = deps.api.addr_validate(&harvester)?;
+                swap_rewards(deps, &env, &checked_harvester)
+            }
+            ExecuteMsg::ProvideLiquidity {} => {
+                assert_vault_callback(&env, &info)?;
+                provide_liquidity(deps, &env)
+            }
+            ExecuteMsg::Restake {} => {
+                assert_vault_callback(&env, &info)?;
+                restake(deps, &env)
+            }
+        },
+        BLOCK_SIZE,
+    )
+}
+
+fn receiver_callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from: Addr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> StdResult<Response> {
+    let msg = msg.ok_or_else(|| {
+        StdError::generic_err("Receiver callback \"msg\" parameter cannot be empty.")
+    })?;
+
+    pad_response_result(
+        match from_binary(&msg)? {
+            InvokeMsg::Deposit {} => deposit(deps, &env, &info, &from, amount),
+        },
+        BLOCK_SIZE,
+    )
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    pad_query_result(
+        match msg {
+            QueryMsg::GetConfig {} => query::config(deps),
+            QueryMsg::GetVaultInfo {} => query::vault_info(deps),
+            QueryMsg::WithPermit { permit, query } => {
+                let config = config_r(deps.storage).load()?;
+                let res: PermitAuthentication<QueryData> =
+                    authenticate_permit(deps, permit, &deps.querier, config.authenticator)?;
+
+                if res.revoked {
+                    return Err(StdError::generic_err("Permit has been revoked".to_string()));
+                }
+
+                auth_queries(deps, env, query, res.sender)
+            }
+        },
+        BLOCK_SIZE,
+    )
+}
+
+pub fn auth_queries(deps: Deps, _env: Env, msg: AuthQuery, user: Addr) -> StdResult<Binary> {
+    match msg {
+        AuthQuery::GetShares {} => query::shares(deps, user),
+    }
+}
@@ -0,0 +1,5 @@
+pub mod contract;
+pub mod state;
+pub mod operations;
+pub mod query;
+#[cfg(test)] mod test;
@@ -0,0 +1,524 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{
+    to_binary, Addr, Attribute, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128, Uint256,
+};
+use shadeswap_shared::{
+    amm_pair::ExecuteMsg as AMMPairExecuteMsg,
+    core::{TokenPairAmount, TokenType},
+    router::InvokeMsg as RouterInvokeMsg,
+    snip20::{
+        self,
+        helpers::{balance_query, increase_allowance_msg},
+    },
+    staking::{ExecuteMsg as StakingExecuteMsg, InvokeMsg as StakingInvokeMsg},
+    utils::{calc::sqrt, ExecuteCallback},
+    vault::{ExecuteMsg, PendingWithdrawal, RewardRoute},
+    Contract,
+};
+
+use crate::{
+    contract::{BLOCK_SIZE, SHADE_VAULT_VIEWKEY},
+    query::{pair_reserves, staking_unbonding_period},
+    state::{
+        config_r, config_w, released_lp_token_r, released_lp_token_w, shares_r, shares_w,
+        total_lp_token_r, total_lp_token_w, total_shares_r, total_shares_w, unbonding_r,
+        unbonding_w, withdrawals_r, withdrawals_w, Config,
+    },
+};
+
+pub const MAX_HARVEST_BOUNTY_BPS: u16 = 1000;
+const BPS_DENOMINATOR: u128 = 10000;
+
+/// Mint vault shares for deposited LP token and stake it
+pub fn deposit(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    depositor: &Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    if config.lp_token.address != info.sender {
+        return Err(StdError::generic_err("Sender was not LP Token".to_string()));
+    }
+    assert_no_unbonding_period(deps.as_ref(), &config.staking_contract)?;
+
+    let total_shares = total_shares_r(deps.storage).may_load()?.unwrap_or_default();
+    let total_lp_token = total_lp_token_r(deps.storage).may_load()?.unwrap_or_default();
+    let shares = shares_for_deposit(amount, total_shares, total_lp_token);
+    if shares.is_zero() {
+        return Err(StdError::generic_err(
+            "Deposit is too small to mint vault shares.",
+        ));
+    }
+
+    let user_shares = shares_r(deps.storage)
+        .may_load(depositor.as_bytes())?
+        .unwrap_or_default();
+    shares_w(deps.storage).save(depositor.as_bytes(), &(user_shares + shares))?;
+    total_shares_w(deps.storage).save(&(total_shares + shares))?;
+    total_lp_token_w(deps.storage).save(&(total_lp_token + amount))?;
+
+    Ok(Response::new()
+        .add_message(stake_msg(&config, env, amount)?)
+        .add_attributes(vec![
+            Attribute::new("action", "deposit"),
+            Attribute::new("depositor", depositor.as_str()),
+            Attribute::new("amount", amount),
+            Attribute::new("shares", shares),
+        ]))
+}
+
+/// Burn vault shares and return their LP token. With an unbonding period the LP token
+/// is queued and sent by ClaimWithdrawals once staking releases it.
+pub fn withdraw(deps: DepsMut, env: &Env, owner: &Addr, shares: Uint128) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    let user_shares = shares_r(deps.storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or_default();
+    if shares.is_zero() || shares > user_shares {
+        return Err(StdError::generic_err("Insufficient vault shares."));
+    }
+    let unbonding_period = staking_unbonding_period(deps.as_ref(), &config.staking_contract)?;
+
+    let total_shares = total_shares_r(deps.storage).load()?;
+    let total_lp_token = total_lp_token_r(deps.storage).load()?;
+    let amount = lp_token_for_shares(shares, total_shares, total_lp_token);
+
+    shares_w(deps.storage).save(owner.as_bytes(), &(user_shares - shares))?;
+    total_shares_w(deps.storage).save(&(total_shares - shares))?;
+    total_lp_token_w(deps.storage).save(&(total_lp_token - amount))?;
+
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        response = response.add_message(
+            StakingExecuteMsg::Unstake {
+                amount,
+                remove_liquidity: Some(false),
+            }
+            .to_cosmos_msg(&config.staking_contract, vec![])?,
+        );
+        if unbonding_period == 0 {
+            response = response.add_message(lp_token_transfer_msg(&config, owner, amount)?);
+        } else {
+            // Staking releases the unstaked LP token at the same time
+            let withdrawal = PendingWithdrawal {
+                amount,
+                release_at: env.block.time.seconds() + unbonding_period,
+            };
+            let mut withdrawals = withdrawals_r(deps.storage)
+                .may_load(owner.as_bytes())?
+                .unwrap_or_default();
+            withdrawals.push(withdrawal.clone());
+            withdrawals_w(deps.storage).save(owner.as_bytes(), &withdrawals)?;
+            let mut unbonding = unbonding_r(deps.storage).may_load()?.unwrap_or_default();
+            unbonding.push(withdrawal.clone());
+            unbonding_w(deps.storage).save(&unbonding)?;
+            response = response.add_attribute("release_at", withdrawal.release_at.to_string());
+        }
+    }
+    Ok(response.add_attributes(vec![
+        Attribute::new("action", "withdraw"),
+        Attribute::new("owner", owner.as_str()),
+        Attribute::new("amount", amount),
+        Attribute::new("shares", shares),
+    ]))
+}
+
+/// Send the owner's withdrawals that finished unbonding. The first claim after a release
+/// claims it from staking for every owner, later claims are paid from what the vault holds.
+pub fn claim_withdrawals(deps: DepsMut, env: &Env, owner: &Addr) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    let current_timestamp = env.block.time.seconds();
+    let (matured, pending): (Vec<PendingWithdrawal>, Vec<PendingWithdrawal>) =
+        withdrawals_r(deps.storage)
+            .may_load(owner.as_bytes())?
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|withdrawal| withdrawal.release_at <= current_timestamp);
+    if matured.is_empty() {
+        return Err(StdError::generic_err("No withdrawn LP token to claim."));
+    }
+    withdrawals_w(deps.storage).save(owner.as_bytes(), &pending)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut released = released_lp_token_r(deps.storage).may_load()?.unwrap_or_default();
+    let (claimable, unbonding): (Vec<PendingWithdrawal>, Vec<PendingWithdrawal>) =
+        unbonding_r(deps.storage)
+            .may_load()?
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|withdrawal| withdrawal.release_at <= current_timestamp);
+    if !claimable.is_empty() {
+        messages.push(
+            StakingExecuteMsg::ClaimUnbonded {}.to_cosmos_msg(&config.staking_contract, vec![])?,
+        );
+        for withdrawal in &claimable {
+            released = released.checked_add(withdrawal.amount)?;
+        }
+        unbonding_w(deps.storage).save(&unbonding)?;
+    }
+
+    let mut amount = Uint128::zero();
+    for withdrawal in &matured {
+        amount = amount.checked_add(withdrawal.amount)?;
+    }
+    released_lp_token_w(deps.storage).save(&released.checked_sub(amount)?)?;
+    messages.push(lp_token_transfer_msg(&config, owner, amount)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        Attribute::new("action", "claim_withdrawals"),
+        Attribute::new("owner", owner.as_str()),
+        Attribute::new("amount", amount),
+    ]))
+}
+
+/// Claim the staking rewards, the remaining steps run as vault callbacks
+pub fn harvest(deps: DepsMut, env: &Env, harvester: &Addr) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    Ok(Response::new()
+        .add_message(
+            StakingExecuteMsg::ClaimRewards {
+                recipient: None,
+                tokens: None,
+            }
+            .to_cosmos_msg(&config.staking_contract, vec![])?,
+        )
+        .add_message(
+            ExecuteMsg::SwapRewards {
+                harvester: harvester.to_string(),
+            }
+            .to_cosmos_msg(&self_contract(env), vec![])?,
+        )
+        .add_attributes(vec![
+            Attribute::new("action", "harvest"),
+            Attribute::new("harvester", harvester.as_str()),
+        ]))
+}
+
+/// Pay the harvest bounty out of each claimed reward and swap the rest into pair tokens
+pub fn swap_rewards(deps: DepsMut, env: &Env, harvester: &Addr) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for route in &config.reward_routes {
+        let balance = balance_query(
+            &deps.querier,
+            env.contract.address.clone(),
+            SHADE_VAULT_VIEWKEY.to_string(),
+            &route.reward_token,
+        )?;
+        if balance.is_zero() {
+            continue;
+        }
+
+        let bounty = balance.multiply_ratio(config.harvest_bounty_bps as u128, BPS_DENOMINATOR);
+        if !bounty.is_zero() {
+            messages.push(
+                snip20::ExecuteMsg::Transfer {
+                    recipient: harvester.to_string(),
+                    amount: bounty,
+                    memo: None,
+                    padding: None,
+                }
+                .to_cosmos_msg(&route.reward_token, vec![])?,
+            );
+        }
+
+        // Pair tokens are added as liquidity as they are
+        if route.path.is_empty() {
+            continue;
+        }
+        let amount = balance - bounty;
+        messages.push(
+            snip20::ExecuteMsg::Send {
+                recipient: config.router.address.to_string(),
+                recipient_code_hash: Some(config.router.code_hash.clone()),
+                amount,
+                msg: Some(to_binary(&RouterInvokeMsg::SwapTokensForExact {
+                    path: route.path.clone(),
+                    expected_return: Some(amount * route.min_return_rate),
+                    recipient: None,
+                    max_price_impact_bps: route.max_price_impact_bps,
+                    referral: None,
+                })?),
+                memo: None,
+                padding: None,
+            }
+            .to_cosmos_msg(&route.reward_token, vec![])?,
+        );
+    }
+    messages.push(ExecuteMsg::ProvideLiquidity {}.to_cosmos_msg(&self_contract(env), vec![])?);
+
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Add the pair tokens held by the vault as liquidity, letting the pair swap any imbalance
+pub fn provide_liquidity(deps: DepsMut, env: &Env) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    let mut amounts: Vec<Uint128> = vec![];
+    for token in config.pair.into_iter() {
+        let amount = token.query_balance(
+            deps.as_ref(),
+            env.contract.address.to_string(),
+            SHADE_VAULT_VIEWKEY.to_string(),
+        )?;
+        if !amount.is_zero() {
+            match token {
+                TokenType::CustomToken {
+                    contract_addr,
+                    token_code_hash,
+                } => messages.push(increase_allowance_msg(
+                    config.pair_contract.address.clone(),
+                    amount,
+                    None,
+                    None,
+                    BLOCK_SIZE,
+                    &Contract {
+                        address: contract_addr.clone(),
+                        code_hash: token_code_hash.clone(),
+                    },
+                    vec![],
+                )?),
+                TokenType::NativeToken { denom } => funds.push(Coin {
+                    denom: denom.clone(),
+                    amount,
+                }),
+            }
+        }
+        amounts.push(amount);
+    }
+
+    if amounts.iter().any(|amount| !amount.is_zero()) {
+        let (reserves, total_liquidity) = pair_reserves(deps.as_ref(), &config.pair_contract)?;
+        let expected_return = min_liquidity(
+            [amounts[0], amounts[1]],
+            reserves,
+            total_liquidity,
+            config.reference_price,
+            config.max_liquidity_slippage_bps,
+        )?;
+        messages.push(
+            AMMPairExecuteMsg::AddLiquidityToAMMContract {
+                deposit: TokenPairAmount {
+                    pair: config.pair.clone(),
+                    amount_0: amounts[0],
+                    amount_1: amounts[1],
+                },
+                expected_return,
+                staking: None,
+                execute_sslp_virtual_swap: Some(true),
+            }
+            .to_cosmos_msg(&config.pair_contract, funds)?,
+        );
+    }
+    messages.push(ExecuteMsg::Restake {}.to_cosmos_msg(&self_contract(env), vec![])?);
+
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Stake the LP token minted by the harvest, raising the LP token per share
+pub fn restake(deps: DepsMut, env: &Env) -> StdResult<Response> {
+    let config = config_r(deps.storage).load()?;
+    let balance = balance_query(
+        &deps.querier,
+        env.contract.address.clone(),
+        SHADE_VAULT_VIEWKEY.to_string(),
+        &config.lp_token,
+    )?;
+    let released = released_lp_token_r(deps.storage).may_load()?.unwrap_or_default();
+    let amount = balance.checked_sub(released)?;
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        let total_lp_token = total_lp_token_r(deps.storage).may_load()?.unwrap_or_default();
+        total_lp_token_w(deps.storage).save(&(total_lp_token + amount))?;
+        response = response.add_message(stake_msg(&config, env, amount)?);
+    }
+    Ok(response.add_attributes(vec![
+        Attribute::new("action", "restake"),
+        Attribute::new("amount", amount),
+    ]))
+}
+
+pub fn set_reward_routes(deps: DepsMut, routes: Vec<RewardRoute>) -> StdResult<Response> {
+    let mut config = config_r(deps.storage).load()?;
+    // The vault needs a viewing key to read its reward balances
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for route in &routes {
+        if !route.path.is_empty() && route.min_return_rate.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "Reward route of {} needs a min_return_rate.",
+                route.reward_token.address
+            )));
+        }
+        messages.push(snip20::helpers::set_viewing_key_msg(
+            SHADE_VAULT_VIEWKEY.to_string(),
+            None,
+            &route.reward_token,
+        )?);
+    }
+    config.reward_routes = routes;
+    config_w(deps.storage).save(&config)?;
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "set_reward_routes"))
+}
+
+pub fn set_harvest_bounty(deps: DepsMut, harvest_bounty_bps: u16) -> StdResult<Response> {
+    validate_harvest_bounty(harvest_bounty_bps)?;
+    let mut config = config_r(deps.storage).load()?;
+    config.harvest_bounty_bps = harvest_bounty_bps;
+    config_w(deps.storage).save(&config)?;
+    Ok(Response::new().add_attribute("action", "set_harvest_bounty"))
+}
+
+pub fn set_max_liquidity_slippage(
+    deps: DepsMut,
+    max_liquidity_slippage_bps: u16,
+) -> StdResult<Response> {
+    validate_max_liquidity_slippage(max_liquidity_slippage_bps)?;
+    let mut config = config_r(deps.storage).load()?;
+    config.max_liquidity_slippage_bps = max_liquidity_slippage_bps;
+    config_w(deps.storage).save(&config)?;
+    Ok(Response::new().add_attribute("action", "set_max_liquidity_slippage"))
+}
+
+pub fn set_reference_price(deps: DepsMut, reference_price: Decimal) -> StdResult<Response> {
+    validate_reference_price(reference_price)?;
+    let mut config = config_r(deps.storage).load()?;
+    config.reference_price = reference_price;
+    config_w(deps.storage).save(&config)?;
+    Ok(Response::new().add_attribute("action", "set_reference_price"))
+}
+
+pub fn validate_reference_price(reference_price: Decimal) -> StdResult<()> {
+    if reference_price.is_zero() {
+        return Err(StdError::generic_err(
+            "Reference price must be greater than zero.",
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_max_liquidity_slippage(max_liquidity_slippage_bps: u16) -> StdResult<()> {
+    if max_liquidity_slippage_bps as u128 > BPS_DENOMINATOR {
+        return Err(StdError::generic_err(format!(
+            "Liquidity slippage can not exceed {} bps.",
+            BPS_DENOMINATOR
+        )));
+    }
+    Ok(())
+}
+
+/// New deposits would be locked for the unbonding period, while the LP token of
+/// depositors who are already in can still be withdrawn through it
+pub fn assert_no_unbonding_period(deps: Deps, staking_contract: &Contract) -> StdResult<()> {
+    if staking_unbonding_period(deps, staking_contract)? != 0 {
+        return Err(StdError::generic_err(
+            "The vault requires a staking contract without an unbonding period.",
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_harvest_bounty(harvest_bounty_bps: u16) -> StdResult<()> {
+    if harvest_bounty_bps > MAX_HARVEST_BOUNTY_BPS {
+        return Err(StdError::generic_err(format!(
+            "Harvest bounty can not exceed {} bps.",
+            MAX_HARVEST_BOUNTY_BPS
+        )));
+    }
+    Ok(())
+}
+
+/// Harvest steps are messages the vault sends to itself
+pub fn assert_vault_callback(env: &Env, info: &MessageInfo) -> StdResult<()> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err(
+            "Only the vault can execute this step.",
+        ));
+    }
+    Ok(())
+}
+
+pub fn shares_for_deposit(
+    amount: Uint128,
+    total_shares: Uint128,
+    total_lp_token: Uint128,
+) -> Uint128 {
+    if total_shares.is_zero() || total_lp_token.is_zero() {
+        return amount;
+    }
+    amount.multiply_ratio(total_shares, total_lp_token)
+}
+
+pub fn lp_token_for_shares(
+    shares: Uint128,
+    total_shares: Uint128,
+    total_lp_token: Uint128,
+) -> Uint128 {
+    if total_shares.is_zero() {
+        return Uint128::zero();
+    }
+    shares.multiply_ratio(total_lp_token, total_shares)
+}
+
+/// LP token the deposit is worth at the reference price, less the allowed slippage.
+/// Bounds what the swap the pair makes to balance the deposit can cost. The pool is valued
+/// from its invariant rather than its reserves, so swaps in the same transaction can not
+/// lower the bound.
+pub fn min_liquidity(
+    amounts: [Uint128; 2],
+    reserves: [Uint128; 2],
+    total_liquidity: Uint128,
+    reference_price: Decimal,
+    max_slippage_bps: u16,
+) -> StdResult<Option<Uint128>> {
+    // Values in token 0, at the reference price the pool holds half its value in each token
+    let invariant =
+        Uint256::from(reserves[0]).checked_mul(Uint256::from(reserves[1] * reference_price))?;
+    let pool_value = Uint128::try_from(sqrt(invariant)?)?.checked_mul(Uint128::new(2))?;
+    if total_liquidity.is_zero() || pool_value.is_zero() {
+        return Ok(None);
+    }
+    let value = amounts[0].checked_add(amounts[1] * reference_price)?;
+    let liquidity = total_liquidity.multiply_ratio(value, pool_value);
+    Ok(Some(liquidity.multiply_ratio(
+        BPS_DENOMINATOR - max_slippage_bps as u128,
+        BPS_DENOMINATOR,
+    )))
+}
+
+fn stake_msg(config: &Config, env: &Env, amount: Uint128) -> StdResult<CosmosMsg> {
+    snip20::ExecuteMsg::Send {
+        recipient: config.staking_contract.address.to_string(),
+        recipient_code_hash: Some(config.staking_contract.code_hash.clone()),
+        amount,
+        msg: Some(to_binary(&StakingInvokeMsg::Stake {
+            from: env.contract.address.to_string(),
+        })?),
+        memo: None,
+        padding: None,
+    }
+    .to_cosmos_msg(&config.lp_token, vec![])
+}
+
+fn lp_token_transfer_msg(config: &Config, owner: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    snip20::ExecuteMsg::Transfer {
+        recipient: owner.to_string(),
+        amount,
+        memo: None,
+        padding: None,
+    }
+    .to_cosmos_msg(&config.lp_token, vec![])
+}
+
+fn self_contract(env: &Env) -> Contract {
+    Contract {
+        address: env.contract.address.clone(),
+        code_hash: env.contract.code_hash.clone(),
+    }
+}
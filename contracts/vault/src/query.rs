@@ -0,0 +1,89 @@
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, QueryRequest, StdError, StdResult, Uint128, WasmQuery,
+};
+use shadeswap_shared::{
+    amm_pair::{QueryMsg as AMMPairQueryMsg, QueryMsgResponse as AMMPairQueryResponse},
+    staking::{QueryMsg as StakingQueryMsg, QueryResponse as StakingQueryResponse},
+    vault::QueryResponse,
+    Contract,
+};
+
+use crate::{
+    operations::lp_token_for_shares,
+    state::{config_r, shares_r, total_lp_token_r, total_shares_r, withdrawals_r},
+};
+
+pub fn config(deps: Deps) -> StdResult<Binary> {
+    let config = config_r(deps.storage).load()?;
+    to_binary(&QueryResponse::GetConfig {
+        pair_contract: config.pair_contract,
+        staking_contract: config.staking_contract,
+        router: config.router,
+        lp_token: config.lp_token,
+        harvest_bounty_bps: config.harvest_bounty_bps,
+        max_liquidity_slippage_bps: config.max_liquidity_slippage_bps,
+        reference_price: config.reference_price,
+        reward_routes: config.reward_routes,
+        admin_auth: config.admin_auth,
+    })
+}
+
+pub fn staking_unbonding_period(deps: Deps, staking_contract: &Contract) -> StdResult<u64> {
+    let result: StakingQueryResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: staking_contract.address.to_string(),
+        msg: to_binary(&StakingQueryMsg::GetConfig {})?,
+        code_hash: staking_contract.code_hash.to_string(),
+    }))?;
+
+    match result {
+        StakingQueryResponse::GetConfig { unbonding_period, .. } => Ok(unbonding_period),
+        _ => Err(StdError::generic_err(
+            "An error occurred while trying to retrieve the staking config.",
+        )),
+    }
+}
+
+/// Pool amounts of the pair and the LP token supply
+pub fn pair_reserves(deps: Deps, pair_contract: &Contract) -> StdResult<([Uint128; 2], Uint128)> {
+    let result: AMMPairQueryResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_contract.address.to_string(),
+        msg: to_binary(&AMMPairQueryMsg::GetPairInfo {})?,
+        code_hash: pair_contract.code_hash.to_string(),
+    }))?;
+
+    match result {
+        AMMPairQueryResponse::GetPairInfo {
+            amount_0,
+            amount_1,
+            total_liquidity,
+            ..
+        } => Ok(([amount_0, amount_1], total_liquidity)),
+        _ => Err(StdError::generic_err("Invalid pair info response")),
+    }
+}
+
+pub fn vault_info(deps: Deps) -> StdResult<Binary> {
+    to_binary(&QueryResponse::GetVaultInfo {
+        total_shares: total_shares_r(deps.storage).may_load()?.unwrap_or_default(),
+        total_lp_token: total_lp_token_r(deps.storage).may_load()?.unwrap_or_default(),
+    })
+}
+
+pub fn shares(deps: Deps, owner: Addr) -> StdResult<Binary> {
+    let shares = shares_r(deps.storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or_default();
+    let lp_token = lp_token_for_shares(
+        shares,
+        total_shares_r(deps.storage).may_load()?.unwrap_or_default(),
+        total_lp_token_r(deps.storage).may_load()?.unwrap_or_default(),
+    );
+    let pending_withdrawals = withdrawals_r(deps.storage)
+        .may_load(owner.as_bytes())?
+        .unwrap_or_default();
+    to_binary(&QueryResponse::GetShares {
+        shares,
+        lp_token,
+        pending_withdrawals,
+    })
+}
@@ -0,0 +1,92 @@
+use cosmwasm_std::{Decimal, Storage, Uint128};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
+use serde::{Deserialize, Serialize};
+use shadeswap_shared::{
+    core::TokenPair,
+    vault::{PendingWithdrawal, RewardRoute},
+    Contract,
+};
+
+pub static CONFIG: &[u8] = b"CONFIG";
+pub static SHARES: &[u8] = b"SHARES";
+pub static TOTAL_SHARES: &[u8] = b"TOTAL_SHARES";
+pub static TOTAL_LP_TOKEN: &[u8] = b"TOTAL_LP_TOKEN";
+pub static WITHDRAWALS: &[u8] = b"WITHDRAWALS";
+pub static UNBONDING: &[u8] = b"UNBONDING";
+pub static RELEASED_LP_TOKEN: &[u8] = b"RELEASED_LP_TOKEN";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Config {
+    pub pair_contract: Contract,
+    pub pair: TokenPair,
+    pub lp_token: Contract,
+    pub staking_contract: Contract,
+    pub router: Contract,
+    pub harvest_bounty_bps: u16,
+    pub max_liquidity_slippage_bps: u16,
+    pub reference_price: Decimal,
+    pub reward_routes: Vec<RewardRoute>,
+    pub authenticator: Option<Contract>,
+    pub admin_auth: Contract,
+}
+
+pub fn config_w(storage: &mut dyn Storage) -> Singleton<Config> {
+    singleton(storage, CONFIG)
+}
+
+pub fn config_r(storage: &dyn Storage) -> ReadonlySingleton<Config> {
+    singleton_read(storage, CONFIG)
+}
+
+pub fn shares_w(storage: &mut dyn Storage) -> Bucket<Uint128> {
+    bucket(storage, SHARES)
+}
+
+pub fn shares_r(storage: &dyn Storage) -> ReadonlyBucket<Uint128> {
+    bucket_read(storage, SHARES)
+}
+
+pub fn total_shares_w(storage: &mut dyn Storage) -> Singleton<Uint128> {
+    singleton(storage, TOTAL_SHARES)
+}
+
+pub fn total_shares_r(storage: &dyn Storage) -> ReadonlySingleton<Uint128> {
+    singleton_read(storage, TOTAL_SHARES)
+}
+
+pub fn total_lp_token_w(storage: &mut dyn Storage) -> Singleton<Uint128> {
+    singleton(storage, TOTAL_LP_TOKEN)
+}
+
+pub fn total_lp_token_r(storage: &dyn Storage) -> ReadonlySingleton<Uint128> {
+    singleton_read(storage, TOTAL_LP_TOKEN)
+}
+
+pub fn withdrawals_w(storage: &mut dyn Storage) -> Bucket<Vec<PendingWithdrawal>> {
+    bucket(storage, WITHDRAWALS)
+}
+
+pub fn withdrawals_r(storage: &dyn Storage) -> ReadonlyBucket<Vec<PendingWithdrawal>> {
+    bucket_read(storage, WITHDRAWALS)
+}
+
+// Withdrawals of all owners that the staking contract still holds
+pub fn unbonding_w(storage: &mut dyn Storage) -> Singleton<Vec<PendingWithdrawal>> {
+    singleton(storage, UNBONDING)
+}
+
+pub fn unbonding_r(storage: &dyn Storage) -> ReadonlySingleton<Vec<PendingWithdrawal>> {
+    singleton_read(storage, UNBONDING)
+}
+
+// Claimed from staking but not sent to its owners yet, restake must leave it alone
+pub fn released_lp_token_w(storage: &mut dyn Storage) -> Singleton<Uint128> {
+    singleton(storage, RELEASED_LP_TOKEN)
+}
+
+pub fn released_lp_token_r(storage: &dyn Storage) -> ReadonlySingleton<Uint128> {
+    singleton_read(storage, RELEASED_LP_TOKEN)
+}
@@ -0,0 +1,550 @@
+pub const VAULT: &str = "secret12qzz6uuapxgz7t0zed82wckl4mff5pt5czcmy2";
+pub const LP_TOKEN: &str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czczzz";
+pub const STAKING: &str = "secret12qmz6uuapxgz7t0zed82wckl4mff5pt5czcmy6";
+pub const DEPOSITOR_A: &str = "secret1pf42ypa2awg0pxkx8lfyyrjvm28vq0qpffa8qx";
+pub const DEPOSITOR_B: &str = "secret1nulgwu6es24us9urgyvms7y02txyg0s02msgzw";
+
+#[cfg(test)]
+pub mod tests {
+    use std::{collections::HashMap, marker::PhantomData};
+
+    use cosmwasm_std::{
+        from_slice,
+        testing::{mock_env, mock_info, MockApi, MockStorage},
+        to_binary, Addr, BalanceResponse, BankQuery, Coin, ContractResult, CosmosMsg, Decimal,
+        DepsMut, Empty, Env, OwnedDeps, Querier, QuerierResult, QueryRequest, StdError,
+        StdResult, Uint128, WasmQuery,
+    };
+    use shadeswap_shared::{
+        amm_pair::{ExecuteMsg as AMMPairExecuteMsg, FeeInfo, QueryMsgResponse as AMMPairQueryResponse},
+        core::{Fee, TokenPair, TokenPairAmount, TokenType},
+        router::{Hop, InvokeMsg as RouterInvokeMsg},
+        snip20::{self, helpers::increase_allowance_msg, QueryAnswer},
+        staking::{
+            ExecuteMsg as StakingExecuteMsg, InvokeMsg as StakingInvokeMsg,
+            QueryResponse as StakingQueryResponse,
+        },
+        utils::ExecuteCallback,
+        vault::{ExecuteMsg, PendingWithdrawal, RewardRoute},
+        Contract,
+    };
+
+    use super::*;
+    use crate::{
+        contract::BLOCK_SIZE,
+        operations::{
+            assert_vault_callback, claim_withdrawals, deposit, harvest, lp_token_for_shares,
+            min_liquidity, provide_liquidity, restake, set_reward_routes, shares_for_deposit,
+            swap_rewards, validate_harvest_bounty, validate_max_liquidity_slippage,
+            validate_reference_price, withdraw,
+        },
+        state::{
+            config_w, released_lp_token_r, shares_r, shares_w, total_lp_token_r,
+            total_lp_token_w, total_shares_r, total_shares_w, withdrawals_r, Config,
+        },
+    };
+
+    fn contract(address: &str) -> Contract {
+        Contract {
+            address: Addr::unchecked(address),
+            code_hash: "".to_string(),
+        }
+    }
+
+    // Answers the staking config, the pair info and the vault's token balances
+    struct VaultQuerier {
+        unbonding_period: u64,
+        // By token address or native denom
+        balances: HashMap<String, u128>,
+    }
+
+    impl VaultQuerier {
+        fn balance(&self, token: &str) -> Uint128 {
+            Uint128::new(*self.balances.get(token).unwrap_or(&0))
+        }
+    }
+
+    impl Querier for VaultQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            let response = match request {
+                QueryRequest::Bank(BankQuery::Balance { denom, .. }) => to_binary(&BalanceResponse {
+                    amount: Coin {
+                        amount: self.balance(&denom),
+                        denom,
+                    },
+                }),
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, .. }) => {
+                    match contract_addr.as_str() {
+                        STAKING => to_binary(&StakingQueryResponse::GetConfig {
+                            reward_token: contract("reward_token"),
+                            lp_token: contract(LP_TOKEN),
+                            amm_pair: "pair".to_string(),
+                            admin_auth: contract("admin"),
+                            total_staked_lp_token: Uint128::zero(),
+                            unbonding_period: self.unbonding_period,
+                            emergency_mode: false,
+                            receipt_token: None,
+                        }),
+                        // A 2000 uscrt / 4000 token pool with 400 LP token
+                        "pair" => to_binary(&AMMPairQueryResponse::GetPairInfo {
+                            liquidity_token: contract(LP_TOKEN),
+                            factory: None,
+                            pair: pair(),
+                            amount_0: Uint128::new(2000),
+                            amount_1: Uint128::new(4000),
+                            total_liquidity: Uint128::new(400),
+                            contract_version: 1,
+                            fee_info: FeeInfo {
+                                shade_dao_address: Addr::unchecked("dao"),
+                                lp_fee: Fee::new(3, 1000),
+                                shade_dao_fee: Fee::new(0, 1000),
+                            },
+                        }),
+                        token => to_binary(&QueryAnswer::Balance {
+                            amount: self.balance(token),
+                        }),
+                    }
+                }
+                _ => unimplemented!(),
+            };
+            QuerierResult::Ok(ContractResult::Ok(response.unwrap()))
+        }
+    }
+
+    fn mock_dependencies(
+        unbonding_period: u64,
+    ) -> OwnedDeps<MockStorage, MockApi, VaultQuerier> {
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: VaultQuerier {
+                unbonding_period,
+                balances: HashMap::new(),
+            },
+            custom_query_type: PhantomData,
+        }
+    }
+
+    fn pair() -> TokenPair {
+        TokenPair(
+            TokenType::NativeToken {
+                denom: "uscrt".to_string(),
+            },
+            TokenType::CustomToken {
+                contract_addr: Addr::unchecked("token"),
+                token_code_hash: "".to_string(),
+            },
+        )
+    }
+
+    fn lp_transfer(recipient: &str, amount: u128) -> StdResult<CosmosMsg> {
+        snip20::ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+            memo: None,
+            padding: None,
+        }
+        .to_cosmos_msg(&contract(LP_TOKEN), vec![])
+    }
+
+    fn vault_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(VAULT);
+        env
+    }
+
+    fn init_config(deps: DepsMut) -> StdResult<()> {
+        config_w(deps.storage).save(&Config {
+            pair_contract: contract("pair"),
+            pair: pair(),
+            lp_token: contract(LP_TOKEN),
+            staking_contract: contract(STAKING),
+            router: contract("router"),
+            harvest_bounty_bps: 100,
+            max_liquidity_slippage_bps: 100,
+            // 4000 token is worth 2000 uscrt
+            reference_price: Decimal::percent(50),
+            reward_routes: vec![],
+            authenticator: None,
+            admin_auth: contract("admin"),
+        })
+    }
+
+    #[test]
+    fn assert_share_math() -> StdResult<()> {
+        assert_eq!(
+            shares_for_deposit(Uint128::new(100), Uint128::zero(), Uint128::zero()),
+            Uint128::new(100)
+        );
+        // Compounded LP token makes each share worth more
+        assert_eq!(
+            shares_for_deposit(Uint128::new(100), Uint128::new(100), Uint128::new(200)),
+            Uint128::new(50)
+        );
+        assert_eq!(
+            lp_token_for_shares(Uint128::new(50), Uint128::new(150), Uint128::new(300)),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            lp_token_for_shares(Uint128::new(50), Uint128::zero(), Uint128::zero()),
+            Uint128::zero()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn assert_deposit_and_withdraw_track_shares() -> StdResult<()> {
+        let mut deps = mock_dependencies(0);
+        let env = vault_env();
+        init_config(deps.as_mut())?;
+        let depositor_a = Addr::unchecked(DEPOSITOR_A);
+        let depositor_b = Addr::unchecked(DEPOSITOR_B);
+
+        let result = deposit(
+            deps.as_mut(),
+            &env,
+            &mock_info(LP_TOKEN, &[]),
+            &depositor_a,
+            Uint128::new(1000),
+        )?;
+        assert_eq!(result.messages.len(), 1);
+
+        // Simulate a harvest compounding 1000 LP token into the vault
+        total_lp_token_w(deps.as_mut().storage).save(&Uint128::new(2000))?;
+        deposit(
+            deps.as_mut(),
+            &env,
+            &mock_info(LP_TOKEN, &[]),
+            &depositor_b,
+            Uint128::new(1000),
+        )?;
+        assert_eq!(
+            shares_r(&deps.storage).load(depositor_b.as_bytes())?,
+            Uint128::new(500)
+        );
+        assert_eq!(total_shares_r(&deps.storage).load()?, Uint128::new(1500));
+
+        let result = withdraw(deps.as_mut(), &env, &depositor_a, Uint128::new(1000))?;
+        assert_eq!(result.messages.len(), 2);
+        assert_eq!(
+            shares_r(&deps.storage).load(depositor_a.as_bytes())?,
+            Uint128::zero()
+        );
+        assert_eq!(total_lp_token_r(&deps.storage).load()?, Uint128::new(1000));
+
+        let err = withdraw(deps.as_mut(), &env, &depositor_b, Uint128::new(501)).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Insufficient vault shares."));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_unbonding_period_blocks_deposit_and_queues_withdraw() -> StdResult<()> {
+        let mut deps = mock_dependencies(100);
+        let mut env = vault_env();
+        init_config(deps.as_mut())?;
+        let depositor_a = Addr::unchecked(DEPOSITOR_A);
+        let depositor_b = Addr::unchecked(DEPOSITOR_B);
+
+        let err = deposit(
+            deps.as_mut(),
+            &env,
+            &mock_info(LP_TOKEN, &[]),
+            &depositor_a,
+            Uint128::new(1000),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(
+                "The vault requires a staking contract without an unbonding period."
+            )
+        );
+
+        // Shares minted before the unbonding period was set can still leave
+        shares_w(deps.as_mut().storage).save(depositor_a.as_bytes(), &Uint128::new(1000))?;
+        shares_w(deps.as_mut().storage).save(depositor_b.as_bytes(), &Uint128::new(1000))?;
+        total_shares_w(deps.as_mut().storage).save(&Uint128::new(2000))?;
+        total_lp_token_w(deps.as_mut().storage).save(&Uint128::new(2000))?;
+        let release_at = env.block.time.seconds() + 100;
+        let result = withdraw(deps.as_mut(), &env, &depositor_a, Uint128::new(1000))?;
+        // Only the unstake, the LP token is unbonding
+        assert_eq!(result.messages.len(), 1);
+        withdraw(deps.as_mut(), &env, &depositor_b, Uint128::new(1000))?;
+        assert_eq!(
+            withdrawals_r(&deps.storage).load(depositor_a.as_bytes())?,
+            vec![PendingWithdrawal {
+                amount: Uint128::new(1000),
+                release_at,
+            }]
+        );
+        assert_eq!(total_lp_token_r(&deps.storage).load()?, Uint128::zero());
+
+        let err = claim_withdrawals(deps.as_mut(), &env, &depositor_a).unwrap_err();
+        assert_eq!(err, StdError::generic_err("No withdrawn LP token to claim."));
+
+        // The first claim releases both withdrawals from staking
+        env.block.time = env.block.time.plus_seconds(100);
+        let result = claim_withdrawals(deps.as_mut(), &env, &depositor_b)?;
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![
+                StakingExecuteMsg::ClaimUnbonded {}.to_cosmos_msg(&contract(STAKING), vec![])?,
+                lp_transfer(DEPOSITOR_B, 1000)?,
+            ]
+        );
+        assert_eq!(released_lp_token_r(&deps.storage).load()?, Uint128::new(1000));
+
+        let result = claim_withdrawals(deps.as_mut(), &env, &depositor_a)?;
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![lp_transfer(DEPOSITOR_A, 1000)?]
+        );
+        assert_eq!(released_lp_token_r(&deps.storage).load()?, Uint128::zero());
+        assert!(claim_withdrawals(deps.as_mut(), &env, &depositor_a).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn assert_min_liquidity_values_deposit_at_reference_price() -> StdResult<()> {
+        let price = Decimal::percent(50);
+        // 1000 token 0 is worth a quarter of a 2000/4000 pool minting 400 LP token
+        assert_eq!(
+            min_liquidity(
+                [Uint128::new(1000), Uint128::zero()],
+                [Uint128::new(2000), Uint128::new(4000)],
+                Uint128::new(400),
+                price,
+                0,
+            )?,
+            Some(Uint128::new(100))
+        );
+        assert_eq!(
+            min_liquidity(
+                [Uint128::new(500), Uint128::new(1000)],
+                [Uint128::new(2000), Uint128::new(4000)],
+                Uint128::new(400),
+                price,
+                100,
+            )?,
+            Some(Uint128::new(99))
+        );
+        // Swapping the pool to 4000/2000 first does not lower the bound,
+        // valued at the pool price the deposit would only need 50 LP token
+        assert_eq!(
+            min_liquidity(
+                [Uint128::new(1000), Uint128::zero()],
+                [Uint128::new(4000), Uint128::new(2000)],
+                Uint128::new(400),
+                price,
+                0,
+            )?,
+            Some(Uint128::new(100))
+        );
+        assert_eq!(
+            min_liquidity(
+                [Uint128::new(1000), Uint128::zero()],
+                [Uint128::zero(), Uint128::zero()],
+                Uint128::zero(),
+                price,
+                100,
+            )?,
+            None
+        );
+
+        assert!(validate_max_liquidity_slippage(10000).is_ok());
+        assert_eq!(
+            validate_max_liquidity_slippage(10001).unwrap_err(),
+            StdError::generic_err("Liquidity slippage can not exceed 10000 bps.")
+        );
+        assert_eq!(
+            validate_reference_price(Decimal::zero()).unwrap_err(),
+            StdError::generic_err("Reference price must be greater than zero.")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn assert_harvest_swaps_provides_and_restakes() -> StdResult<()> {
+        let mut deps = mock_dependencies(0);
+        let env = vault_env();
+        init_config(deps.as_mut())?;
+        let harvester = Addr::unchecked(DEPOSITOR_B);
+        let vault = Contract {
+            address: env.contract.address.clone(),
+            code_hash: env.contract.code_hash.clone(),
+        };
+        let swap_route = RewardRoute {
+            reward_token: contract("reward"),
+            path: vec![Hop {
+                addr: "reward_pair".to_string(),
+                code_hash: "".to_string(),
+            }],
+            max_price_impact_bps: Some(500),
+            min_return_rate: Decimal::zero(),
+        };
+        let err = set_reward_routes(deps.as_mut(), vec![swap_route.clone()]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Reward route of reward needs a min_return_rate.")
+        );
+        let swap_route = RewardRoute {
+            // Set from a price outside the pool, 2 token per reward
+            min_return_rate: Decimal::from_ratio(2u128, 1u128),
+            ..swap_route
+        };
+        let pair_token_route = RewardRoute {
+            reward_token: contract("token"),
+            path: vec![],
+            max_price_impact_bps: None,
+            min_return_rate: Decimal::zero(),
+        };
+        set_reward_routes(deps.as_mut(), vec![swap_route.clone(), pair_token_route])?;
+
+        let result = harvest(deps.as_mut(), &env, &harvester)?;
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![
+                StakingExecuteMsg::ClaimRewards {
+                    recipient: None,
+                    tokens: None,
+                }
+                .to_cosmos_msg(&contract(STAKING), vec![])?,
+                ExecuteMsg::SwapRewards {
+                    harvester: harvester.to_string(),
+                }
+                .to_cosmos_msg(&vault, vec![])?,
+            ]
+        );
+
+        // Claimed rewards, the bounty is 1% of each
+        deps.querier.balances.insert("reward".to_string(), 1000);
+        deps.querier.balances.insert("token".to_string(), 200);
+        let result = swap_rewards(deps.as_mut(), &env, &harvester)?;
+        let bounty = |token: &str, amount: u128| {
+            snip20::ExecuteMsg::Transfer {
+                recipient: harvester.to_string(),
+                amount: Uint128::new(amount),
+                memo: None,
+                padding: None,
+            }
+            .to_cosmos_msg(&contract(token), vec![])
+        };
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![
+                bounty("reward", 10)?,
+                snip20::ExecuteMsg::Send {
+                    recipient: "router".to_string(),
+                    recipient_code_hash: Some("".to_string()),
+                    amount: Uint128::new(990),
+                    msg: Some(to_binary(&RouterInvokeMsg::SwapTokensForExact {
+                        path: swap_route.path.clone(),
+                        expected_return: Some(Uint128::new(1980)),
+                        recipient: None,
+                        max_price_impact_bps: Some(500),
+                        referral: None,
+                    })?),
+                    memo: None,
+                    padding: None,
+                }
+                .to_cosmos_msg(&contract("reward"), vec![])?,
+                bounty("token", 2)?,
+                ExecuteMsg::ProvideLiquidity {}.to_cosmos_msg(&vault, vec![])?,
+            ]
+        );
+
+        // Swapped rewards, worth 1000 uscrt at the reference price
+        deps.querier.balances.insert("uscrt".to_string(), 500);
+        deps.querier.balances.insert("token".to_string(), 1000);
+        let result = provide_liquidity(deps.as_mut(), &env)?;
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![
+                increase_allowance_msg(
+                    Addr::unchecked("pair"),
+                    Uint128::new(1000),
+                    None,
+                    None,
+                    BLOCK_SIZE,
+                    &contract("token"),
+                    vec![],
+                )?,
+                AMMPairExecuteMsg::AddLiquidityToAMMContract {
+                    deposit: TokenPairAmount {
+                        pair: pair(),
+                        amount_0: Uint128::new(500),
+                        amount_1: Uint128::new(1000),
+                    },
+                    // A quarter of the 400 LP token less 1% slippage
+                    expected_return: Some(Uint128::new(99)),
+                    staking: None,
+                    execute_sslp_virtual_swap: Some(true),
+                }
+                .to_cosmos_msg(
+                    &contract("pair"),
+                    vec![Coin {
+                        denom: "uscrt".to_string(),
+                        amount: Uint128::new(500),
+                    }],
+                )?,
+                ExecuteMsg::Restake {}.to_cosmos_msg(&vault, vec![])?,
+            ]
+        );
+
+        total_lp_token_w(deps.as_mut().storage).save(&Uint128::new(1000))?;
+        deps.querier.balances.insert(LP_TOKEN.to_string(), 100);
+        let result = restake(deps.as_mut(), &env)?;
+        assert_eq!(
+            result.messages.iter().map(|m| m.msg.clone()).collect::<Vec<CosmosMsg>>(),
+            vec![snip20::ExecuteMsg::Send {
+                recipient: STAKING.to_string(),
+                recipient_code_hash: Some("".to_string()),
+                amount: Uint128::new(100),
+                msg: Some(to_binary(&StakingInvokeMsg::Stake {
+                    from: VAULT.to_string(),
+                })?),
+                memo: None,
+                padding: None,
+            }
+            .to_cosmos_msg(&contract(LP_TOKEN), vec![])?]
+        );
+        assert_eq!(total_lp_token_r(&deps.storage).load()?, Uint128::new(1100));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_deposit_rejects_other_tokens() -> StdResult<()> {
+        let mut deps = mock_dependencies(0);
+        let env = vault_env();
+        init_config(deps.as_mut())?;
+
+        let err = deposit(
+            deps.as_mut(),
+            &env,
+            &mock_info("other_token", &[]),
+            &Addr::unchecked(DEPOSITOR_A),
+            Uint128::new(1000),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("Sender was not LP Token"));
+        Ok(())
+    }
+
+    #[test]
+    fn assert_callbacks_and_bounty_are_guarded() -> StdResult<()> {
+        let env = vault_env();
+        assert!(assert_vault_callback(&env, &mock_info(VAULT, &[])).is_ok());
+        assert_eq!(
+            assert_vault_callback(&env, &mock_info(DEPOSITOR_A, &[])).unwrap_err(),
+            StdError::generic_err("Only the vault can execute this step.")
+        );
+
+        assert!(validate_harvest_bounty(1000).is_ok());
+        assert_eq!(
+            validate_harvest_bounty(1001).unwrap_err(),
+            StdError::generic_err("Harvest bounty can not exceed 1000 bps.")
+        );
+        Ok(())
+    }
+}
@@ -115,7 +115,9 @@ pub mod amm_pairs_mock {
                 }
                 QueryMsg::GetTradeHistory {
                     api_key: _,
-                    pagination: _,
+                    start_after: _,
+                    limit: _,
+                    order: _,
                 } => to_binary(""),
                 QueryMsg::GetWhiteListAddress {} => to_binary(""),
                 QueryMsg::GetTradeCount {} => to_binary(""),
@@ -131,6 +133,8 @@ pub mod amm_pairs_mock {
                             return_amount: offer.amount,
                         },
                         price: "1.2".to_string(),
+                        mid_price: "1.2".to_string(),
+                        price_impact_bps: 0u64,
                     };
                     return to_binary(&response);
                 }
@@ -156,6 +160,7 @@ pub mod amm_pairs_mock {
                     expected_return: _,
                     to: _,
                     execute_arbitrage: _,
+                    max_price_impact_bps: _,
                 } => Ok(Response::new()),
                 ExecuteMsg::Receive {
                     from: _,
@@ -174,6 +179,8 @@ pub mod amm_pairs_mock {
                 } => Ok(Response::new()),
                 ExecuteMsg::SetConfig { admin_auth: _ } => Ok(Response::new()),
                 ExecuteMsg::SetArbitrageContract { arbitrage_contract : _} => todo!(),
+                ExecuteMsg::CreateStakingContract { staking_init: _ } => todo!(),
+                ExecuteMsg::SetStakingContract { staking_contract: _, force: _ } => todo!(),
             },
             BLOCK_SIZE,
         )
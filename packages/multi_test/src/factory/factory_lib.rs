@@ -1,7 +1,6 @@
 pub mod factory_lib{
     use cosmwasm_std::{StdResult, ContractInfo, Addr, Empty, to_binary};
     use secret_multi_test::{App, Executor, Contract, ContractWrapper};
-    use shadeswap_shared::Pagination;
     use shadeswap_shared::amm_pair::AMMSettings;
     use shadeswap_shared::core::{ContractInstantiationInfo, TokenPair};
     use shadeswap_shared::staking::StakingContractInit;
@@ -145,19 +144,18 @@ pub mod factory_lib{
     pub fn list_amm_pairs_from_factory(
         router: &mut App,
         factory_contract: &ContractInfo,
-        start: u64,
+        start_after: Option<u64>,
         limit: u8
     ) -> StdResult<Vec<AMMPair>>{
         let query_msg = to_binary(&QueryMsg::ListAMMPairs { 
-            pagination:  Pagination{
-                start: start,
-                limit: limit,
-            }
+            start_after,
+            limit: Some(limit),
+            order: None,
         })?;
 
         let response: QueryResponse = router.query_test(factory_contract.clone(), query_msg).unwrap();
         match response {
-            QueryResponse::ListAMMPairs { amm_pairs } => Ok(amm_pairs),
+            QueryResponse::ListAMMPairs { amm_pairs, .. } => Ok(amm_pairs),
             _ => panic!("wrong response")
         }
     }
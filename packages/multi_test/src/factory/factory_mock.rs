@@ -15,7 +15,7 @@ pub mod factory_mock {
     use shadeswap_shared::{
         amm_pair::AMMSettings,
         core::{ContractInstantiationInfo, Fee},
-        factory::{ExecuteMsg, QueryMsg, QueryResponse},
+        factory::{CreationPolicy, ExecuteMsg, QueryMsg, QueryResponse},
         utils::{pad_query_result, pad_response_result},
     };
     use factory::state::ephemeral_storage_w;
@@ -45,7 +45,12 @@ pub mod factory_mock {
     pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         pad_query_result(
             match msg {
-                QueryMsg::ListAMMPairs { pagination: _ } => to_binary(""),
+                QueryMsg::ListAMMPairs {
+                    start_after: _,
+                    limit: _,
+                    order: _,
+                } => to_binary(""),
+                QueryMsg::GetPairCount {} => to_binary(""),
                 QueryMsg::GetAMMPairAddress { pair: _ } => to_binary(""),
                 QueryMsg::GetConfig {} => {
                     println!("getconfig factory");
@@ -69,9 +74,34 @@ pub mod factory_mock {
                         },
                         authenticator: None,
                         admin_auth: admin_auth.clone(),
+                        creation_policy: CreationPolicy::AdminOnly,
                     })
                 },
                 QueryMsg::AuthorizeApiKey { api_key: _ } => to_binary(""),
+                QueryMsg::ListPairsForToken { token: _, pagination: _ } => to_binary(""),
+                QueryMsg::ListTokens { pagination: _ } => to_binary(""),
+                QueryMsg::GetPairsByAddress { addresses: _ } => to_binary(""),
+                QueryMsg::GetPairSettings { pair: _ } => {
+                    let admin_auth: Contract = singleton_read(deps.storage, CONFIG).load()?;
+                    to_binary(&QueryResponse::GetPairSettings {
+                        amm_settings: AMMSettings {
+                            lp_fee: Fee::new(3, 100),
+                            shade_dao_fee: Fee::new(3, 100),
+                            shade_dao_address: sContract {
+                                address: Addr::unchecked(OWNER),
+                                code_hash: "".to_string(),
+                            },
+                        },
+                        authenticator: None,
+                        admin_auth: admin_auth.clone(),
+                        fee_override: None,
+                    })
+                },
+                QueryMsg::ListFeeOverrides { pagination: _ } => to_binary(""),
+                QueryMsg::ListAMMPairsWithInfo { pagination: _ } => to_binary(""),
+                QueryMsg::GetPairByLabel { label: _ } => to_binary(""),
+                QueryMsg::GetNextPairLabel {} => to_binary(""),
+                QueryMsg::PendingConfigChanges {} => to_binary(""),
             },
             BLOCK_SIZE,
         )
@@ -92,9 +122,21 @@ pub mod factory_mock {
                     amm_settings: _,
                     api_key: _,
                     admin_auth: _,
+                    creation_policy: _,
+                    config_timelock: _,
                 } => Ok(Response::new()),
+                ExecuteMsg::ProposeConfig { changes: _ } => Ok(Response::new()),
+                ExecuteMsg::ExecuteConfig { id: _ } => Ok(Response::new()),
+                ExecuteMsg::CancelConfig { id: _ } => Ok(Response::new()),
+                ExecuteMsg::ProposeAdminAuth { admin_auth: _ } => Ok(Response::new()),
+                ExecuteMsg::AcceptAdminAuth {} => Ok(Response::new()),
                 ExecuteMsg::CreateAMMPair {pair:_,entropy:_,staking_contract:_,lp_token_decimals:_u8, lp_token_custom_label: _, amm_pair_custom_label } => Ok(Response::new()),
-                ExecuteMsg::AddAMMPairs { amm_pairs: _ } => Ok(Response::new())
+                ExecuteMsg::AddAMMPairs { amm_pairs: _ } => Ok(Response::new()),
+                ExecuteMsg::RemoveAMMPairs { pairs: _ } => Ok(Response::new()),
+                ExecuteMsg::MigratePairs { code_id: _, code_hash: _, pairs: _, msg: _ } => Ok(Response::new()),
+                ExecuteMsg::SetPairFee { pair: _, fee: _ } => Ok(Response::new()),
+                ExecuteMsg::ClearPairFee { pair: _ } => Ok(Response::new()),
+                ExecuteMsg::CreateStakingForPair { pair: _, staking_init: _ } => Ok(Response::new())
             },
             BLOCK_SIZE,
         )
@@ -1,6 +1,6 @@
 pub mod staking_lib{
 
-    use cosmwasm_std::{Uint128, Empty};
+    use cosmwasm_std::Empty;
     use secret_multi_test::{Contract, ContractWrapper};
     use staking::contract::{execute as staking_execute, instantiate as staking_instantiate, query as staking_query};
     use shadeswap_shared::{staking::StakingContractInit, core::{TokenType, ContractInstantiationInfo}};
@@ -13,9 +13,7 @@ pub mod staking_lib{
     pub fn create_staking_info_contract(
         code_id: u64,
         code_hash: &str,
-        daily_reward_amount: Uint128,
         reward_token: TokenType,
-        valid_to: Uint128,
         custom_label: Option<String>
     ) -> StakingContractInit {
         StakingContractInit{
@@ -23,9 +21,7 @@ pub mod staking_lib{
                 code_hash: code_hash.to_string(),
                 id: code_id,
             },
-            daily_reward_amount: daily_reward_amount,
             reward_token: reward_token,
-            valid_to: valid_to,
             custom_label: custom_label,
         }
     }
@@ -36,7 +36,14 @@ pub mod staking_mock {
                     permit: _,
                     query: _,
                 } => to_binary(""),
-                QueryMsg::GetRewardTokens {} => to_binary("")
+                QueryMsg::GetRewardTokens { include_retired: _ } => to_binary(""),
+                QueryMsg::GetRewardPrograms {} => to_binary(""),
+                QueryMsg::GetLockConfig {} => to_binary(""),
+                QueryMsg::WithViewingKey {
+                    address: _,
+                    key: _,
+                    query: _,
+                } => to_binary("")
             },
             BLOCK_SIZE,
         )
@@ -51,7 +58,11 @@ pub mod staking_mock {
     ) -> StdResult<Response> {
         pad_response_result(
             match msg {
-                ExecuteMsg::ClaimRewards {} => Ok(Response::new()),
+                ExecuteMsg::ClaimRewards {
+                    recipient: _,
+                    tokens: _,
+                } => Ok(Response::new()),
+                ExecuteMsg::SetRewardRecipient { recipient: _ } => Ok(Response::new()),
                 ExecuteMsg::ProxyUnstake {
                     for_addr: _,
                     amount: _,
@@ -65,10 +76,17 @@ pub mod staking_mock {
                     msg: _,
                     amount: _,
                 } => Ok(Response::new()),
-                ExecuteMsg::SetRewardToken {
-                    reward_token: _,
-                    daily_reward_amount: _,
-                    valid_to: _,
+                ExecuteMsg::SetRewardToken { reward_token: _ } => Ok(Response::new()),
+                ExecuteMsg::RemoveRewardToken { token: _, refund_to: _ } => Ok(Response::new()),
+                ExecuteMsg::SetRewardFunders { funders: _ } => Ok(Response::new()),
+                ExecuteMsg::Unlock {
+                    position_id: _,
+                    remove_liquidity: _,
+                } => Ok(Response::new()),
+                ExecuteMsg::SetLockConfig {
+                    tiers: _,
+                    early_unlock_penalty: _,
+                    penalty_recipient: _,
                 } => Ok(Response::new()),
                 ExecuteMsg::SetAuthenticator { authenticator: _ } => Ok(Response::new()),
                 ExecuteMsg::RecoverFunds {
@@ -77,7 +95,19 @@ pub mod staking_mock {
                     to: _,
                     msg: _,
                 } => Ok(Response::new()),
-                ExecuteMsg::SetConfig { admin_auth: _ } => todo!(),
+                ExecuteMsg::SetConfig {
+                    admin_auth: _,
+                    unbonding_period: _,
+                } => todo!(),
+                ExecuteMsg::ClaimUnbonded {} => Ok(Response::new()),
+                ExecuteMsg::EmergencyUnstake { amount: _ } => Ok(Response::new()),
+                ExecuteMsg::SetEmergencyMode { enabled: _ } => Ok(Response::new()),
+                ExecuteMsg::CreateReceiptToken {
+                    contract_info: _,
+                    custom_label: _,
+                } => Ok(Response::new()),
+                ExecuteMsg::CreateViewingKey { entropy: _ } => Ok(Response::new()),
+                ExecuteMsg::SetViewingKey { key: _ } => Ok(Response::new()),
             },
             BLOCK_SIZE,
         )
@@ -287,6 +287,7 @@ pub mod factory_lib {
                 address: Addr::unchecked(admin_contract.to_string()),
                 code_hash: admin_contract_code_hash.to_string(),
             },
+            creation_policy: None,
         };
 
         let factory_contract =
@@ -630,7 +631,7 @@ pub mod amm_pair_lib {
             },
             staking::{ExecuteMsg as StakingExecuteMsg, StakingContractInit},
         },
-        Contract, Pagination,
+        Contract,
     };
     use std::io;
 
@@ -671,8 +672,6 @@ pub mod amm_pair_lib {
         entropy: &str,
         reward_contract_address: Option<String>,
         reward_contract_code_hash: Option<String>,
-        reward_amount: Option<u128>,
-        valid_to: Option<u128>,
         lp_token_decimals: u8,
         reports: &mut Vec<Report>,
         amm_pair_custom_label: Option<String>,
@@ -716,12 +715,10 @@ pub mod amm_pair_lib {
                     code_hash: staking_contract.code_hash.to_string(),
                     id: staking_contract.id.clone().parse::<u64>().unwrap(),
                 },
-                daily_reward_amount: Uint128::from(reward_amount.unwrap()),
                 reward_token: TokenType::CustomToken {
                     contract_addr: Addr::unchecked(msg.clone()),
                     token_code_hash: reward_contract_code_hash.unwrap().to_string(),
                 },
-                valid_to: Uint128::new(valid_to.unwrap()),
                 custom_label: None,
             }),
             None => None,
@@ -751,7 +748,7 @@ pub mod amm_pair_lib {
 
     pub fn list_pair_from_factory(
         factory_addr: String,
-        start: u64,
+        start_after: Option<u64>,
         limit: u8,
     ) -> io::Result<Vec<AMMPair>> {
         let factory_contract = NetContract {
@@ -761,13 +758,12 @@ pub mod amm_pair_lib {
             code_hash: "".to_string(),
         };
         let msg = FactoryQueryMsg::ListAMMPairs {
-            pagination: Pagination {
-                start: start,
-                limit: limit,
-            },
+            start_after,
+            limit: Some(limit),
+            order: None,
         };
         let factory_query: FactoryQueryResponse = query(&factory_contract, msg, None)?;
-        if let FactoryQueryResponse::ListAMMPairs { amm_pairs } = factory_query {
+        if let FactoryQueryResponse::ListAMMPairs { amm_pairs, .. } = factory_query {
             for i in 0..amm_pairs.len() {
                 println!("{:?}", amm_pairs[i]);
             }
@@ -802,8 +798,6 @@ pub mod amm_pair_lib {
         staking_addr: &str,
         token_addr: &str,
         token_code_hash: &str,
-        daily_reward_amount: Uint128,
-        valid_to: Uint128,
         reports: &mut Vec<Report>,
     ) -> io::Result<()> {
         let staking_contract = NetContract {
@@ -816,8 +810,6 @@ pub mod amm_pair_lib {
         handle(
             &StakingExecuteMsg::SetRewardToken {
                 reward_token: TokenType::CustomToken { contract_addr: Addr::unchecked(token_addr.to_string()), token_code_hash: token_code_hash.to_string() } ,
-                daily_reward_amount: daily_reward_amount,
-                valid_to: valid_to,
             },
             &staking_contract,
             account_name,
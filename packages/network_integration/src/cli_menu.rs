@@ -339,16 +339,12 @@ pub fn parse_args(args: &[String], reports: &mut Vec<Report>) -> io::Result<()>
         let staking_addr = args[4].clone();
         let reward_token_addr = args[5].clone();
         let reward_token_hash = args[6].clone();
-        let daily_reward_amount = args[5].clone().parse::<u128>().unwrap();
-        let valid_to = args[6].clone().parse::<u128>().unwrap();
         set_reward_token(
             &account_name,
             &backend,
             &staking_addr,
             &reward_token_addr,
             &reward_token_hash,
-            Uint128::new(daily_reward_amount),
-            Uint128::new(valid_to),
             reports,
         )?;
     }
@@ -384,25 +380,17 @@ pub fn parse_args(args: &[String], reports: &mut Vec<Report>) -> io::Result<()>
         // CHECK ROUTER
         let mut reward_addr: Option<String> = None;
         let mut reward_addr_code_hash: Option<String> = None;
-        let amount: Option<String>;
-        let mut amount_u128: Option<u128> = None;
-        let mut valid_to: Option<u128> = None;
 
         if staking_enabled == true {
-            if args.len() < 16 {
+            if args.len() < 15 {
                 return Err(Error::new(ErrorKind::Other, "Please provide all args"));
             }
             reward_addr = Some(args[13].clone());
             reward_addr_code_hash = Some(args[14].clone());
-            amount = Some(args[15].clone());
-            amount_u128 = Some(amount.unwrap_or_default().parse::<u128>().unwrap());
-            valid_to = Some(args[16].clone().parse::<u128>().unwrap());
             println!(
-                "STAKING INFO {} - {} - {} - {}",
+                "STAKING INFO {} - {}",
                 reward_addr.clone().unwrap(),
-                reward_addr_code_hash.clone().unwrap(),
-                amount_u128.unwrap(),
-                valid_to.unwrap()
+                reward_addr_code_hash.clone().unwrap()
             );
         }
 
@@ -418,8 +406,6 @@ pub fn parse_args(args: &[String], reports: &mut Vec<Report>) -> io::Result<()>
             &entropy,
             reward_addr,
             reward_addr_code_hash,
-            amount_u128,
-            valid_to,
             decimals.parse::<u8>().unwrap(),
             reports,
             None,
@@ -441,7 +427,7 @@ pub fn parse_args(args: &[String], reports: &mut Vec<Report>) -> io::Result<()>
         let limit = args[4].clone();
         let start_u64 = start.parse::<u64>().unwrap();
         let limit_u8 = limit.parse::<u8>().unwrap();
-        list_pair_from_factory(factory_add.clone(), start_u64, limit_u8)?;
+        list_pair_from_factory(factory_add.clone(), start_u64.checked_sub(1), limit_u8)?;
     }
 
     if args_command == CMDBALANCE {
@@ -521,8 +507,7 @@ pub fn print_help() -> io::Result<()> {
     handle.write_all(b"\n\t8. Command:: store_stake <account_name> <keyring_backend> -- Store Staking Contract Contract")?;
     handle.write_all(b"\n\t9. Command:: add_amm_pair <account_name> <keyring_backend> <factory_addr> <token_0_addr> <token_code_1_hash> <token_1_addr> <token_code_1_hash> <entropy> 
                                                         <router_addr> <router_code_hash> <staking - bool>? 
-                                                            if yes additional args <reward_addr> <reward_addr_code_hash> 
-                                                                                   <reward_amount> <valid_to>) 
+                                                            if yes additional args <reward_addr> <reward_addr_code_hash>) 
                                                         Add new Pair for Factory")?;
 
     handle.write_all(b"\n\t10. Command:: list_amm_pair <factory_addr> <start> <limit> -- List All Pairs for Factory")?;
@@ -531,7 +516,7 @@ pub fn print_help() -> io::Result<()> {
                                                       <staking - bool> <expected_return> 
                                                       Add Liquidity to the AMM Pair")?;
     handle.write_all(b"\n\t12. Command:: snip20_bal <snip20_addr> <spender> <viewing_key> -- Balance Snip 20 for spender")?;
-    handle.write_all(b"\n\t13. Command:: set_reward_token <account_name> <keyring_backend> <staking_addr> <reward_token_addr> <reward_token_hash> <amount> <valid_to> -- Set Reward Token for Staking Contract")?;
+    handle.write_all(b"\n\t13. Command:: set_reward_token <account_name> <keyring_backend> <staking_addr> <reward_token_addr> <reward_token_hash> -- Register Reward Token for Staking Contract, fund it with send_with_msg and a fund_rewards msg")?;
     handle.write_all(b"\n\t14. Command:: deposit <account_name> <keyring_backend> <token_addr> <amount> -- Deposit to Snip20 Token")?;
     handle.write_all(b"\n\t15. Command:: set_viewing_key <account_name> <keyring_backend> <token_addr> <key> -- Set Viewing Key")?;
     handle.write_all(b"\n\t16. Command:: send_with_msg <account_name> <keyring_backend> <token_addr> <amount> <recipient> <recipient_hash> <msg> -- Send Amount & Msg with Callback")?;
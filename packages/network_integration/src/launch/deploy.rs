@@ -31,7 +31,7 @@ use shadeswap_shared::{
     },
     snip20::QueryMsg,
     snip20,
-    Contract, Pagination,
+    Contract,
 };
 
 use shadeswap_shared::snip20 as snip20_reference_impl;
@@ -363,6 +363,7 @@ fn deploy_fresh() -> serde_json::Result<()> {
             address: Addr::unchecked(admin_contract.address.to_string()),
             code_hash: admin_contract.code_hash.clone(),
         },
+        creation_policy: None,
     };
 
     let factory_contract = init(
@@ -381,14 +382,13 @@ fn deploy_fresh() -> serde_json::Result<()> {
     print_header("\n\tGetting Pairs from Factory");
     {
         let msg = FactoryQueryMsg::ListAMMPairs {
-            pagination: Pagination {
-                start: 0,
-                limit: 10,
-            },
+            start_after: None,
+            limit: Some(10),
+            order: None,
         };
 
         let factory_query: FactoryQueryResponse = query(&factory_contract, msg, None)?;
-        if let FactoryQueryResponse::ListAMMPairs { amm_pairs } = factory_query {
+        if let FactoryQueryResponse::ListAMMPairs { amm_pairs, .. } = factory_query {
             assert_eq!(amm_pairs.len(), 0);
 
             print_header("\n\tInitializing Router");
@@ -474,6 +474,31 @@ fn deploy_fresh() -> serde_json::Result<()> {
             )
             .unwrap();
 
+            handle(
+                &RouterExecuteMsg::SetConfig {
+                    admin_auth: None,
+                    max_referral_fee_bps: None,
+                    trusted_factories: Some(vec![Contract {
+                        address: Addr::unchecked(factory_contract.address.to_string()),
+                        code_hash: factory_contract.code_hash.clone(),
+                    }]),
+                    trusted_pairs: None,
+                },
+                &NetContract {
+                    label: "".to_string(),
+                    id: "".to_string(),
+                    address: router_contract.address.to_string().to_string(),
+                    code_hash: "".to_string(),
+                },
+                ACCOUNT_KEY,
+                Some(GAS),
+                Some("test"),
+                None,
+                &mut reports,
+                None,
+            )
+            .unwrap();
+
             {
                 handle(
                     &FactoryExecuteMsg::CreateAMMPair {
@@ -484,12 +509,10 @@ fn deploy_fresh() -> serde_json::Result<()> {
                                 code_hash: staking_contract.code_hash.to_string(),
                                 id: staking_contract.id.clone().parse::<u64>().unwrap(),
                             },
-                            daily_reward_amount: Uint128::from(100000u128),
                             reward_token: TokenType::CustomToken {
                                 contract_addr: Addr::unchecked(usdt_contract.address.clone()),
                                 token_code_hash: usdt_contract.code_hash.to_string(),
                             },
-                            valid_to: Uint128::new(3747905010000u128),
                             custom_label: None
                         }),
                         lp_token_decimals: 18u8,
@@ -517,12 +540,10 @@ fn deploy_fresh() -> serde_json::Result<()> {
                                 code_hash: staking_contract.code_hash.to_string(),
                                 id: staking_contract.id.clone().parse::<u64>().unwrap(),
                             },
-                            daily_reward_amount: Uint128::from(100000u128),
                             reward_token: TokenType::CustomToken {
                                 contract_addr: Addr::unchecked(usdt_contract.address.clone()),
                                 token_code_hash: usdt_contract.code_hash.to_string(),
                             },
-                            valid_to: Uint128::new(3747905010000u128),
                             custom_label: None
                         }),
                         lp_token_decimals: 18u8,
@@ -543,14 +564,13 @@ fn deploy_fresh() -> serde_json::Result<()> {
             print_header("\n\tGetting Pairs from Factory");
             {
                 let msg = FactoryQueryMsg::ListAMMPairs {
-                    pagination: Pagination {
-                        start: 0,
-                        limit: 10,
-                    },
+                    start_after: None,
+                    limit: Some(10),
+                    order: None,
                 };
 
                 let factory_query: FactoryQueryResponse = query(&factory_contract, msg, None)?;
-                if let FactoryQueryResponse::ListAMMPairs { amm_pairs } = factory_query {
+                if let FactoryQueryResponse::ListAMMPairs { amm_pairs, .. } = factory_query {
                     {
                         let amm_pair = amm_pairs[0].clone();
 
@@ -726,6 +746,8 @@ fn test_trade(router_contract: String, pair_contract_address: String, pair_contr
                         code_hash: pair_contract_code_hash.to_string(),
                     }],
                     recipient: None,
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -50,7 +50,7 @@ use shadeswap_shared::{
     router::Hop,
     snip20,
     staking::{AuthQuery, QueryData},
-    Contract, Pagination,
+    Contract,
 };
 
 use secretcli::{
@@ -296,8 +296,6 @@ fn run_testnet() -> Result<()> {
         "seed",
         Some(reward_token.address.to_string()),
         Some(reward_token.code_hash.to_string()),
-        Some(3450000000000u128),
-        Some(3450000000000u128),
         18u8,
         &mut reports,
         None,
@@ -319,8 +317,6 @@ fn run_testnet() -> Result<()> {
         "seed",
         None,
         None,
-        None,
-        None,
         18u8,
         &mut reports,
         Some("PAIR_CONTRACT".to_owned() + &factory_contract.address.to_string()),
@@ -329,7 +325,7 @@ fn run_testnet() -> Result<()> {
     .unwrap();
 
     print_header("\n\tGetting Pairs from Factory");
-    let amm_pairs = list_pair_from_factory(factory_contract.address.clone(), 0, 10).unwrap();
+    let amm_pairs = list_pair_from_factory(factory_contract.address.clone(), None, 10).unwrap();
     assert_eq!(amm_pairs.len(), 2);
     let amm_pair_1 = amm_pairs[0].clone();
     let amm_pair_2 = amm_pairs[1].clone();
@@ -484,10 +480,9 @@ fn run_testnet() -> Result<()> {
 
     {
         let trade_count_info_msg = AMMPairQueryMsg::GetTradeHistory {
-            pagination: Pagination {
-                start: 0u64,
-                limit: 10u8,
-            },
+            start_after: None,
+            limit: Some(10u8),
+            order: None,
             api_key: API_KEY.to_string(),
         };
         let trade_count_info_query: AMMPairQueryMsgResponse = query(
@@ -501,7 +496,7 @@ fn run_testnet() -> Result<()> {
             None,
         )?;
 
-        if let AMMPairQueryMsgResponse::GetTradeHistory { data } = trade_count_info_query {
+        if let AMMPairQueryMsgResponse::GetTradeHistory { data, .. } = trade_count_info_query {
             assert_eq!(data.len(), 0u32 as usize);
         } else {
             panic!("Trade count couldnt pass")
@@ -539,6 +534,8 @@ fn run_testnet() -> Result<()> {
         shade_dao_fee_amount,
         result,
         price,
+        mid_price: _,
+        price_impact_bps: _,
     } = simulation_query
     {
         test_amount = result.return_amount;
@@ -560,6 +557,8 @@ fn run_testnet() -> Result<()> {
                         code_hash: pair_contract_code_hash.clone(),
                     }],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -625,10 +624,9 @@ fn run_testnet() -> Result<()> {
 
     {
         let trade_count_info_msg = AMMPairQueryMsg::GetTradeHistory {
-            pagination: Pagination {
-                start: 0u64,
-                limit: 10u8,
-            },
+            start_after: None,
+            limit: Some(10u8),
+            order: None,
             api_key: API_KEY.to_string(),
         };
         let trade_count_info_query: AMMPairQueryMsgResponse = query(
@@ -642,7 +640,7 @@ fn run_testnet() -> Result<()> {
             None,
         )?;
 
-        if let AMMPairQueryMsgResponse::GetTradeHistory { data } = trade_count_info_query {
+        if let AMMPairQueryMsgResponse::GetTradeHistory { data, .. } = trade_count_info_query {
             assert_eq!(data.len(), 1u32 as usize);
         } else {
             panic!("Trade count couldnt pass")
@@ -665,6 +663,8 @@ fn run_testnet() -> Result<()> {
                         code_hash: pair_contract_code_hash.clone(),
                     }],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -723,6 +723,8 @@ fn run_testnet() -> Result<()> {
                         code_hash: pair_contract_code_hash.clone(),
                     }],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -781,6 +783,8 @@ fn run_testnet() -> Result<()> {
                         code_hash: pair_contract_code_hash.clone(),
                     }],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -840,6 +844,8 @@ fn run_testnet() -> Result<()> {
                         code_hash: pair_contract_code_hash.clone(),
                     }],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -900,6 +906,8 @@ fn run_testnet() -> Result<()> {
                 code_hash: pair_contract_code_hash.clone(),
             }],
             recipient: None,
+            max_price_impact_bps: None,
+            referral: None,
         },
         &router_contract,
         ACCOUNT_KEY,
@@ -937,6 +945,8 @@ fn run_testnet() -> Result<()> {
                 },
             ],
             recipient: None,
+            max_price_impact_bps: None,
+            referral: None,
         },
         &router_contract,
         ACCOUNT_KEY,
@@ -979,6 +989,8 @@ fn run_testnet() -> Result<()> {
                         },
                     ],
                     recipient: Some(account.to_string()),
+                    max_price_impact_bps: None,
+                    referral: None,
                 })
                 .unwrap(),
             ),
@@ -1032,6 +1044,8 @@ fn run_testnet() -> Result<()> {
         shade_dao_fee_amount: _,
         result: _,
         price,
+        mid_price: _,
+        price_impact_bps: _,
     } = estimated_price_query
     {
         assert_eq!(price, "0.9".to_string());
@@ -1154,6 +1168,9 @@ fn run_testnet() -> Result<()> {
             lp_token,
             amm_pair,
             admin_auth,
+            unbonding_period: _,
+            emergency_mode: _,
+            receipt_token: _,
         } = total_currently_staked_msg
         {
             old_total_staked = total_staked_lp_token;
@@ -1198,6 +1215,9 @@ fn run_testnet() -> Result<()> {
             lp_token,
             amm_pair,
             admin_auth,
+            unbonding_period: _,
+            emergency_mode: _,
+            receipt_token: _,
         } = total_currently_staked_msg
         {
             println!("{} - {}", old_total_staked, total_staked_lp_token);
@@ -1538,6 +1558,8 @@ fn run_testnet() -> Result<()> {
             shade_dao_fee_amount: _,
             result,
             price: _,
+            mid_price: _,
+            price_impact_bps: _,
         } = swap_result_response
         {
             assert_ne!(result.return_amount, Uint128::new(0u128));
@@ -1606,6 +1628,9 @@ fn run_testnet() -> Result<()> {
             amm_pair: _,
             admin_auth: _,
             total_staked_lp_token,
+            unbonding_period: _,
+            emergency_mode: _,
+            receipt_token: _,
         } = config_query_response
         {
             assert_eq!(
@@ -1712,10 +1737,9 @@ fn run_testnet() -> Result<()> {
             amm_pair_1.address.to_string(),
             AMMPairQueryMsg::GetTradeHistory {
                 api_key: API_KEY.to_string(),
-                pagination: Pagination {
-                    limit: 10,
-                    start: 0
-                }
+                start_after: None,
+                limit: Some(10),
+                order: None,
             }
         )?,
         AMMPairQueryMsgResponse::GetTradeHistory { .. }
@@ -1804,10 +1828,9 @@ fn run_testnet() -> Result<()> {
         test_query_successful(
             factory_contract.address.to_string(),
             FactoryQueryMsg::ListAMMPairs {
-                pagination: Pagination {
-                    start: 0,
-                    limit: 10
-                }
+                start_after: None,
+                limit: Some(10),
+                order: None,
             },
         )?,
         FactoryQueryResponse::ListAMMPairs { .. }
@@ -1845,7 +1868,9 @@ fn run_testnet() -> Result<()> {
     assert!(matches!(
         test_query_successful(
             found_staking_contract.address.to_string(),
-            StakingQueryMsg::GetRewardTokens {},
+            StakingQueryMsg::GetRewardTokens {
+                include_retired: None,
+            },
         )?,
         StakingQueryMsgResponse::GetRewardTokens { .. }
     ));
@@ -1991,12 +2016,10 @@ fn run_testnet() -> Result<()> {
             code_hash: staking_contract.code_hash.to_string(),
             id: staking_contract.id.clone().parse::<u64>().unwrap(),
         },
-        daily_reward_amount: Uint128::new(30000u128),
         reward_token: TokenType::CustomToken {
             contract_addr: Addr::unchecked(reward_token.address.to_string()),
             token_code_hash: reward_token.code_hash.to_string(),
         },
-        valid_to: Uint128::new(40000000u128),
         custom_label: Some(("THIS IS A TEST".to_owned() + &factory_contract.address.to_string()).to_string()),
     });
 
@@ -2082,7 +2105,7 @@ fn run_testnet() -> Result<()> {
     )?;
 
     print_header("\n\tGetting New Pairs from Factory");
-    let amm_pairs = list_pair_from_factory(factory_contract.address.clone(), 0, 10).unwrap();
+    let amm_pairs = list_pair_from_factory(factory_contract.address.clone(), None, 10).unwrap();
     assert_eq!(amm_pairs.len(), 3);
     assert_eq!(
         amm_pairs[2].address.to_string(),
@@ -2277,6 +2300,7 @@ fn run_testnet() -> Result<()> {
                         expected_return: Some(Uint128::new(10u128)),
                         to: Some(account.to_string()),
                         execute_arbitrage: None,
+                        max_price_impact_bps: None,
                     })
                     .unwrap(),
                 ),
@@ -2318,6 +2342,8 @@ fn run_testnet() -> Result<()> {
                             code_hash: pair_contract_code_hash.clone(),
                         }],
                         recipient: Some(account.to_string()),
+                        max_price_impact_bps: None,
+                        referral: None,
                     })
                     .unwrap(),
                 ),
@@ -23,3 +23,42 @@ pub struct Pagination {
     pub start: u64,
     pub limit: u8,
 }
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PageOrder {
+    Ascending,
+    Descending,
+}
+
+/// Resolves one page of a cursor query over the indexes `first..first + count`.
+/// Returns the indexes to load and the cursor of the next page, which is `None`
+/// once the last item was returned.
+pub fn cursor_page(
+    first: u64,
+    count: u64,
+    start_after: Option<u64>,
+    limit: u8,
+    order: PageOrder,
+) -> (Vec<u64>, Option<u64>) {
+    let end = first.saturating_add(count);
+    let limit = limit.max(1) as usize;
+    let indexes: Vec<u64> = match order {
+        PageOrder::Ascending => {
+            let start = start_after.map_or(first, |index| index.saturating_add(1)).max(first);
+            (start..end).take(limit).collect()
+        }
+        PageOrder::Descending => {
+            let stop = start_after.map_or(end, |index| index.min(end)).max(first);
+            (first..stop).rev().take(limit).collect()
+        }
+    };
+
+    let next = match (indexes.last(), order) {
+        (Some(&last), PageOrder::Ascending) if indexes.len() == limit && last + 1 < end => Some(last),
+        (Some(&last), PageOrder::Descending) if indexes.len() == limit && last > first => Some(last),
+        _ => None,
+    };
+
+    (indexes, next)
+}
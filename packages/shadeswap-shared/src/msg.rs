@@ -18,6 +18,7 @@ pub mod router {
         core::{TokenAmount, TokenType},
         Contract,
     };
+    use cosmwasm_std::Addr;
 
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
@@ -36,7 +37,44 @@ pub mod router {
             path: Vec<Hop>,
             expected_return: Option<Uint128>,
             recipient: Option<String>,
+            /// Maximum price impact in basis points allowed on each hop of the path.
+            max_price_impact_bps: Option<u64>,
+            referral: Option<Referral>,
+        },
+        /// Swap once and split the output between recipients pro rata to their shares.
+        SwapTokensForExactBatch {
+            expected_return: Option<Uint128>,
+            path: Vec<Hop>,
+            recipients: Vec<BatchRecipient>,
+            max_price_impact_bps: Option<u64>,
         },
+        /// Swap along a path that ends in the sent token, reverting unless it returns at least `min_profit` more.
+        CyclicSwap {
+            path: Vec<Hop>,
+            min_profit: Uint128,
+            max_price_impact_bps: Option<u64>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct BatchRecipient {
+        pub address: String,
+        pub share: Uint128,
+    }
+
+    /// Integrator paid `fee_bps` of the swap output, capped by the router config.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct Referral {
+        pub address: String,
+        pub fee_bps: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct ReferralVolume {
+        pub token: TokenType,
+        /// Total output routed by the referrer, before the referral fee.
+        pub volume: Uint128,
+        pub fee_amount: Uint128,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,12 +84,36 @@ pub mod router {
         pub admin_auth: Contract,
     }
 
+    /// A hop is either an amm pair or, when its address is the configured
+    /// `WrappedNativeToken`, a wrap/unwrap step between the denom and its SNIP20.
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     pub struct Hop {
         pub addr: String,
         pub code_hash: String,
     }
 
+    /// SNIP20 token that mints and burns 1:1 against a native denom via `Deposit` and `Redeem`.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct WrappedNativeToken {
+        pub token: Contract,
+        pub denom: String,
+    }
+
+    impl WrappedNativeToken {
+        pub fn native_token(&self) -> TokenType {
+            TokenType::NativeToken {
+                denom: self.denom.clone(),
+            }
+        }
+
+        pub fn custom_token(&self) -> TokenType {
+            TokenType::CustomToken {
+                contract_addr: self.token.address.clone(),
+                token_code_hash: self.token.code_hash.clone(),
+            }
+        }
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
@@ -67,11 +129,40 @@ pub mod router {
             expected_return: Option<Uint128>,
             path: Vec<Hop>,
             recipient: Option<String>,
+            /// Maximum price impact in basis points allowed on each hop of the path.
+            max_price_impact_bps: Option<u64>,
+            referral: Option<Referral>,
+        },
+        /// Swap a native token once and split the output between recipients pro rata to their shares.
+        SwapTokensForExactBatch {
+            offer: TokenAmount,
+            expected_return: Option<Uint128>,
+            path: Vec<Hop>,
+            recipients: Vec<BatchRecipient>,
+            max_price_impact_bps: Option<u64>,
+        },
+        /// Swap a native token along a path that ends in the same token, reverting unless it returns at least `min_profit` more.
+        CyclicSwap {
+            offer: TokenAmount,
+            path: Vec<Hop>,
+            min_profit: Uint128,
+            max_price_impact_bps: Option<u64>,
         },
         RegisterSNIP20Token {
             token_addr: String,
             token_code_hash: String,
         },
+        SetWrappedNativeToken {
+            wrapped_native_token: Option<WrappedNativeToken>,
+        },
+        SetConfig {
+            admin_auth: Option<Contract>,
+            max_referral_fee_bps: Option<u64>,
+            /// Factories whose pairs can be used as hops, no pair is accepted while both lists are empty.
+            trusted_factories: Option<Vec<Contract>>,
+            /// Standalone pairs allowed as hops without a trusted factory.
+            trusted_pairs: Option<Vec<String>>,
+        },
         RecoverFunds {
             token: TokenType,
             amount: Uint128,
@@ -93,6 +184,9 @@ pub mod router {
             exclude_fee: Option<bool>,
         },
         GetConfig {},
+        GetReferralVolume {
+            referrer: String,
+        },
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -104,9 +198,21 @@ pub mod router {
             shade_dao_fee_amount: Uint128,
             result: SwapResult,
             price: String,
+            /// Product of the mid prices of every hop before the trade.
+            mid_price: String,
+            /// Combined price impact of the path in basis points, fees excluded.
+            price_impact_bps: u64,
         },
         GetConfig {
             admin_auth: Contract,
+            wrapped_native_token: Option<WrappedNativeToken>,
+            max_referral_fee_bps: u64,
+            trusted_factories: Vec<Contract>,
+            trusted_pairs: Vec<Addr>,
+        },
+        GetReferralVolume {
+            swap_count: u64,
+            volumes: Vec<ReferralVolume>,
         },
     }
 }
@@ -119,7 +225,7 @@ pub mod amm_pair {
             TokenType,
         },
         staking::StakingContractInit,
-        Contract, Pagination,
+        Contract, PageOrder,
     };
     use cosmwasm_std::Addr;
     use schemars::JsonSchema;
@@ -163,7 +269,12 @@ pub mod amm_pair {
         pub lp_fee_amount: Uint128,
         pub shade_dao_fee_amount: Uint128,
         pub result: SwapResult,
+        /// Execution price of the trade, fees included.
         pub price: String,
+        /// Pool price of the output token per input token before the trade.
+        pub mid_price: String,
+        /// Deviation of the pre-fee execution price from the mid price in basis points.
+        pub price_impact_bps: u64,
         pub new_input_pool: Uint128,
         pub new_output_pool: Uint128,
         pub index_of_input_token: u8,
@@ -221,6 +332,10 @@ pub mod amm_pair {
         pub lp_token_decimals: u8,
         pub lp_token_custom_label: Option<String>
     }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct MigrateMsg {}
+
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
@@ -236,6 +351,8 @@ pub mod amm_pair {
             expected_return: Option<Uint128>,
             to: Option<String>,
             execute_arbitrage: Option<ArbitrageCallback>,
+            /// Rejects the swap if its price impact in basis points exceeds this value.
+            max_price_impact_bps: Option<u64>,
         },
         // SNIP20 receiver interface
         Receive {
@@ -258,6 +375,17 @@ pub mod amm_pair {
         SetArbitrageContract {
             arbitrage_contract: Option<Contract>,
         },
+        /// Instantiates a staking contract for the LP token, replacing the current one.
+        /// Callable by admins and the factory.
+        CreateStakingContract {
+            staking_init: StakingContractInit,
+        },
+        /// Replaces the staking contract, none detaches it.
+        SetStakingContract {
+            staking_contract: Option<Contract>,
+            /// Replace even if the current staking contract holds stake or can not be queried.
+            force: Option<bool>,
+        },
         SetViewingKey {
             viewing_key: String,
         },
@@ -295,6 +423,8 @@ pub mod amm_pair {
             expected_return: Option<Uint128>,
             to: Option<String>,
             execute_arbitrage: Option<ArbitrageCallback>,
+            /// Rejects the swap if its price impact in basis points exceeds this value.
+            max_price_impact_bps: Option<u64>,
         },
         RemoveLiquidity {
             from: Option<String>,
@@ -309,7 +439,10 @@ pub mod amm_pair {
         GetPairInfo {},
         GetTradeHistory {
             api_key: String,
-            pagination: Pagination,
+            // Trade index the page starts after, trades are indexed from 1
+            start_after: Option<u64>,
+            limit: Option<u8>,
+            order: Option<PageOrder>,
         },
         GetWhiteListAddress {},
         GetTradeCount {},
@@ -340,6 +473,8 @@ pub mod amm_pair {
         },
         GetTradeHistory {
             data: Vec<TradeHistory>,
+            // Pass as start_after to get the next page, None once the last trade was returned
+            next: Option<u64>,
         },
         GetWhiteListAddress {
             addresses: Vec<Addr>,
@@ -359,6 +494,8 @@ pub mod amm_pair {
             shade_dao_fee_amount: Uint128,
             result: SwapResult,
             price: String,
+            mid_price: String,
+            price_impact_bps: u64,
         },
         GetShadeDaoInfo {
             shade_dao_address: String,
@@ -382,11 +519,12 @@ pub mod amm_pair {
 
 pub mod factory {
     use super::*;
-    use crate::amm_pair::AMMPair;
-    use crate::core::TokenPair;
+    use cosmwasm_std::Addr;
+    use crate::amm_pair::{AMMPair, FeeInfo};
+    use crate::core::{CustomFee, TokenAmount, TokenPair, TokenType};
     use crate::staking::StakingContractInit;
     use crate::Contract;
-    use crate::{amm_pair::AMMSettings, Pagination};
+    use crate::{amm_pair::AMMSettings, PageOrder, Pagination};
     use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
 
@@ -400,8 +538,13 @@ pub mod factory {
         //Set the default authenticator for all permits on the contracts
         pub authenticator: Option<Contract>,
         pub admin_auth: Contract,
+        /// Defaults to `Open`, anyone can create pairs.
+        pub creation_policy: Option<CreationPolicy>,
     }
 
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct MigrateMsg {}
+
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
@@ -410,8 +553,27 @@ pub mod factory {
             lp_token_contract: Option<ContractInstantiationInfo>,
             amm_settings: Option<AMMSettings>,
             api_key: Option<String>,
+            /// Rejected, the admin is changed with `ProposeAdminAuth` and `AcceptAdminAuth`.
             admin_auth: Option<Contract>,
+            creation_policy: Option<CreationPolicy>,
+            config_timelock: Option<u64>,
         },
+        /// Queues config changes that can be executed once the config timelock has passed.
+        ProposeConfig {
+            changes: ConfigChanges,
+        },
+        ExecuteConfig {
+            id: u64,
+        },
+        CancelConfig {
+            id: u64,
+        },
+        /// First step of an admin handover, the new admin auth contract has to accept it.
+        ProposeAdminAuth {
+            admin_auth: Contract,
+        },
+        /// Called by an admin of the proposed admin auth contract.
+        AcceptAdminAuth {},
         CreateAMMPair {
             pair: TokenPair,
             entropy: Binary,
@@ -423,17 +585,110 @@ pub mod factory {
         AddAMMPairs {
             amm_pairs: Vec<AMMPair>,
         },
+        RemoveAMMPairs {
+            pairs: Vec<Addr>,
+        },
+        /// Migrate the given pairs, or the next page of pairs not yet on `code_hash`, to a new code id.
+        MigratePairs {
+            code_id: u64,
+            code_hash: String,
+            pairs: Option<Vec<Addr>>,
+            msg: Binary,
+        },
+        /// Overrides the factory default fee for a registered pair.
+        SetPairFee {
+            pair: TokenPair,
+            fee: CustomFee,
+        },
+        ClearPairFee {
+            pair: TokenPair,
+        },
+        /// Deploys a staking contract for a registered pair, replacing its current one.
+        CreateStakingForPair {
+            pair: Addr,
+            staking_init: StakingContractInit,
+        },
     }
 
     impl ExecuteCallback for ExecuteMsg {
         const BLOCK_SIZE: usize = 256;
     }
 
+    #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+    pub struct ConfigChanges {
+        pub pair_contract: Option<ContractInstantiationInfo>,
+        pub lp_token_contract: Option<ContractInstantiationInfo>,
+        pub amm_settings: Option<AMMSettings>,
+        pub api_key: Option<String>,
+        pub creation_policy: Option<CreationPolicy>,
+        /// Delay in seconds between proposing and executing config changes.
+        pub config_timelock: Option<u64>,
+        /// Fee overrides to set on registered pairs.
+        pub pair_fees: Option<Vec<PairFeeOverride>>,
+        /// Pairs whose fee override is removed.
+        pub cleared_pair_fees: Option<Vec<TokenPair>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct PendingConfigChange {
+        pub id: u64,
+        pub changes: ConfigChanges,
+        /// Block time in seconds from which the change can be executed.
+        pub executable_at: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct PairFeeOverride {
+        pub pair: TokenPair,
+        pub fee: CustomFee,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct AMMPairWithInfo {
+        pub amm_pair: AMMPair,
+        /// None when the pair contract could not be queried.
+        pub info: Option<AMMPairInfo>,
+    }
+
+    /// Live data of a pair, combined from its `GetPairInfo` and `GetConfig` queries.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct AMMPairInfo {
+        pub liquidity_token: Contract,
+        pub amount_0: Uint128,
+        pub amount_1: Uint128,
+        pub total_liquidity: Uint128,
+        pub contract_version: u32,
+        pub fee_info: FeeInfo,
+        pub staking_contract: Option<Contract>,
+        pub custom_fee: Option<CustomFee>,
+    }
+
+    /// Who can call `CreateAMMPair`, admins are never charged the fee.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CreationPolicy {
+        Open,
+        AdminOnly,
+        /// Native fees are sent with the message, SNIP20 fees are taken through an allowance.
+        FeeGated { fee: TokenAmount },
+    }
+
+    impl Default for CreationPolicy {
+        fn default() -> Self {
+            CreationPolicy::Open
+        }
+    }
+
     #[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq)]
     #[serde(rename_all = "snake_case")]
     pub enum QueryResponse {
         ListAMMPairs {
             amm_pairs: Vec<AMMPair>,
+            // Pass as start_after to get the next page, None once the last pair was returned
+            next: Option<u64>,
+        },
+        GetPairCount {
+            count: u64,
         },
         GetConfig {
             pair_contract: ContractInstantiationInfo,
@@ -441,6 +696,9 @@ pub mod factory {
             lp_token_contract: ContractInstantiationInfo,
             authenticator: Option<Contract>,
             admin_auth: Contract,
+            // Missing from the responses of factories without creation policies
+            #[serde(default)]
+            creation_policy: CreationPolicy,
         },
         GetAMMPairAddress {
             address: String,
@@ -448,22 +706,73 @@ pub mod factory {
         AuthorizeApiKey {
             authorized: bool,
         },
+        ListPairsForToken {
+            amm_pairs: Vec<AMMPair>,
+        },
+        ListTokens {
+            tokens: Vec<TokenType>,
+        },
+        GetPairsByAddress {
+            amm_pairs: Vec<AMMPair>,
+        },
+        GetPairSettings {
+            amm_settings: AMMSettings,
+            authenticator: Option<Contract>,
+            admin_auth: Contract,
+            fee_override: Option<CustomFee>,
+        },
+        ListFeeOverrides {
+            overrides: Vec<PairFeeOverride>,
+        },
+        ListAMMPairsWithInfo {
+            amm_pairs: Vec<AMMPairWithInfo>,
+        },
+        GetPairByLabel {
+            amm_pair: AMMPair,
+        },
+        GetNextPairLabel {
+            label: String,
+        },
+        PendingConfigChanges {
+            admin_auth: Option<Contract>,
+            config_timelock: u64,
+            changes: Vec<PendingConfigChange>,
+        },
     }
 
     #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
     #[serde(rename_all = "snake_case")]
     pub enum QueryMsg {
-        // GetCount returns the current count as a json-encoded number
-        ListAMMPairs { pagination: Pagination },
+        // Pairs are paged by id, ids follow registration order and are never reused, limit defaults to the pagination limit
+        ListAMMPairs {
+            start_after: Option<u64>,
+            limit: Option<u8>,
+            order: Option<PageOrder>,
+        },
+        GetPairCount {},
         GetAMMPairAddress { pair: TokenPair },
         GetConfig {},
         AuthorizeApiKey { api_key: String },
+        ListPairsForToken { token: TokenType, pagination: Pagination },
+        ListTokens { pagination: Pagination },
+        // Unknown addresses are skipped
+        GetPairsByAddress { addresses: Vec<String> },
+        // AMM settings with the pair's fee override applied, queried by pairs on every swap
+        GetPairSettings { pair: TokenPair },
+        ListFeeOverrides { pagination: Pagination },
+        // Registered pairs with their pool balances, fees and staking contract
+        ListAMMPairsWithInfo { pagination: Pagination },
+        GetPairByLabel { label: String },
+        // Label the next CreateAMMPair without a custom label will use
+        GetNextPairLabel {},
+        PendingConfigChanges {},
     }
 }
 
 pub mod staking {
     use crate::{core::TokenType, query_auth::QueryPermit, Contract};
     use super::*;
+    use cosmwasm_std::{Addr, Decimal};
     use cosmwasm_schema::cw_serde;
     use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
@@ -471,9 +780,8 @@ pub mod staking {
     #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
     pub struct StakingContractInit {
         pub contract_info: ContractInstantiationInfo,
-        pub daily_reward_amount: Uint128,
+        // Registered as the first reward token, emission starts once it is funded
         pub reward_token: TokenType,
-        pub valid_to: Uint128,
         pub custom_label: Option<String>
     }
 
@@ -492,14 +800,70 @@ pub mod staking {
         pub last_update_time: Uint128
     }
 
+    /// Reward token removed with RemoveRewardToken, its accrued rewards stay claimable
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct RetiredRewardToken {
+        pub reward_token: TokenType,
+        // Final reward per token, nothing is emitted after retirement
+        pub reward_per_token: Uint128,
+        pub retired_at: u64,
+        // Reward weight of stakers whose rewards have not been settled yet
+        pub unsettled_weight: Uint128,
+        // Settled rewards that have not been claimed yet
+        pub unclaimed: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct RewardProgram {
+        pub reward_token: TokenType,
+        // Tokens emitted per second across all stakers
+        pub reward_rate: Uint128,
+        // Funded tokens that have not been emitted yet
+        pub remaining_budget: Uint128,
+        pub ends_at: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct LockTier {
+        // Lock duration in seconds
+        pub duration: u64,
+        // Applied to the locked amount to get its reward weight, at least 1
+        pub multiplier: Decimal,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct LockConfig {
+        pub tiers: Vec<LockTier>,
+        // Share of a position taken when unlocking right after locking, decays linearly to zero at unlock_at
+        pub early_unlock_penalty: Decimal,
+        // Receives penalties, None distributes them to the remaining stakers
+        pub penalty_recipient: Option<Addr>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct LockPosition {
+        pub id: u64,
+        pub amount: Uint128,
+        // Amount times the tier multiplier, used for reward calculations
+        pub weight: Uint128,
+        pub locked_at: u64,
+        pub unlock_at: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+    pub struct Unbonding {
+        pub amount: Uint128,
+        // Timestamp in seconds after which ClaimUnbonded releases the amount
+        pub release_at: u64,
+        pub remove_liquidity: bool,
+    }
+
     #[cw_serde]
     pub struct QueryData {}
 
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     pub struct InitMsg {
-        pub daily_reward_amount: Uint128,
         pub reward_token: TokenType,
-        pub valid_to: Uint128,
         pub pair_contract: Contract,
         pub prng_seed: Binary,
         pub lp_token: Contract,
@@ -508,10 +872,21 @@ pub mod staking {
         pub admin_auth: Contract,
     }
 
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct MigrateMsg {}
+
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
-        ClaimRewards {},
+        // Pays to recipient, or the staker's reward recipient, only the listed tokens when set
+        ClaimRewards {
+            recipient: Option<String>,
+            tokens: Option<Vec<TokenType>>,
+        },
+        // Where rewards of the sender are paid by default, including claims made by Unstake and Unlock
+        SetRewardRecipient {
+            recipient: Option<String>,
+        },
         ProxyUnstake {
             for_addr: String,
             amount: Uint128,
@@ -525,16 +900,58 @@ pub mod staking {
             msg: Option<Binary>,
             amount: Uint128,
         },
+        // Registers a reward token so it can be funded with FundRewards
         SetRewardToken {
             reward_token: TokenType,
-            daily_reward_amount: Uint128,
-            valid_to: Uint128,
+        },
+        // Stops emission of a reward token and refunds its unemitted budget to refund_to
+        RemoveRewardToken {
+            token: TokenType,
+            refund_to: String,
+        },
+        // Restricts FundRewards to these addresses, None lets anyone fund
+        SetRewardFunders {
+            funders: Option<Vec<String>>,
+        },
+        // Releases a lock position, early unlocks pay the early unlock penalty
+        Unlock {
+            position_id: u64,
+            remove_liquidity: Option<bool>,
+        },
+        SetLockConfig {
+            tiers: Vec<LockTier>,
+            early_unlock_penalty: Decimal,
+            penalty_recipient: Option<String>,
         },
         SetAuthenticator {
             authenticator: Option<Contract>,
         },
         SetConfig {
             admin_auth: Option<Contract>,
+            // Seconds unstaked LP token waits before it can be claimed, zero returns it immediately
+            unbonding_period: Option<u64>,
+        },
+        // Releases unstaked LP token whose unbonding period has passed
+        ClaimUnbonded {},
+        // Returns LP token without paying rewards, pending rewards are forfeited
+        EmergencyUnstake {
+            amount: Uint128,
+        },
+        // Emergency mode stops deposits and lets EmergencyUnstake release locked LP token immediately
+        SetEmergencyMode {
+            enabled: bool,
+        },
+        // Instantiates the receipt token from the LP token code, staking is its only minter
+        CreateReceiptToken {
+            contract_info: ContractInstantiationInfo,
+            custom_label: Option<String>,
+        },
+        // Generates a viewing key for the sender, returned as ExecuteMsgResponse::CreateViewingKey
+        CreateViewingKey {
+            entropy: String,
+        },
+        SetViewingKey {
+            key: String,
         },
         RecoverFunds {
             token: TokenType,
@@ -548,22 +965,53 @@ pub mod staking {
         const BLOCK_SIZE: usize = 256;
     }
 
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ExecuteMsgResponse {
+        CreateViewingKey { key: String },
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum InvokeMsg {
         Stake { from: String },
         ProxyStake { for_addr: String },
+        // Sent with a registered reward token, emits the amount plus any unemitted budget over duration seconds
+        FundRewards { duration: u64 },
+        // Locks the sent LP token for the lock tier matching duration
+        LockStake { duration: u64 },
+        // Stakes the sent LP token into the receipt pool and mints receipt tokens 1:1 to the sender.
+        // Receipt tokens are bearer claims on the pool's stake and rewards.
+        StakeWithReceipt {},
+        // Sent with receipt tokens, burns them and moves their pooled stake and rewards to recipient
+        TransferStake {
+            recipient: String,
+        },
+        // Sent with receipt tokens, burns them and unstakes their pooled stake and rewards to the sender
+        UnstakeReceipt {
+            remove_liquidity: Option<bool>,
+        },
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, PartialEq)]
     #[serde(rename_all = "snake_case")]
     pub enum QueryMsg {
         GetConfig {},
-        GetRewardTokens {},
+        GetRewardTokens {
+            include_retired: Option<bool>,
+        },
+        GetRewardPrograms {},
+        GetLockConfig {},
         WithPermit {
             permit: QueryPermit,
             query: AuthQuery,
         },
+        // Same queries as WithPermit, authenticated with the address's viewing key
+        WithViewingKey {
+            address: String,
+            key: String,
+            query: AuthQuery,
+        },
     }
 
     #[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Clone)]
@@ -571,6 +1019,9 @@ pub mod staking {
     pub enum AuthQuery {
         GetStakerLpTokenInfo {},
         GetClaimReward { time: Uint128 },
+        GetLockPositions {},
+        GetUnbondings {},
+        GetRewardRecipient {},
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, PartialEq)]
@@ -588,10 +1039,36 @@ pub mod staking {
             lp_token: Contract,
             amm_pair: String,
             admin_auth: Contract,
-            total_staked_lp_token: Uint128
+            total_staked_lp_token: Uint128,
+            unbonding_period: u64,
+            emergency_mode: bool,
+            receipt_token: Option<Contract>,
         },
         GetRewardTokens {
             tokens: Vec<RewardTokenInfo>,
+            // Only listed when include_retired is set
+            retired_tokens: Vec<RetiredRewardToken>,
+        },
+        GetRewardPrograms {
+            programs: Vec<RewardProgram>,
+            funders: Option<Vec<Addr>>,
+        },
+        GetLockConfig {
+            lock_config: LockConfig,
+        },
+        GetLockPositions {
+            positions: Vec<LockPosition>,
+            // Reward weight of all the staker's LP token, locked or not
+            weight: Uint128,
+            total_weight: Uint128,
+        },
+        GetUnbondings {
+            unbondings: Vec<Unbonding>,
+            // Sum of the unbondings that can be claimed now
+            claimable: Uint128,
+        },
+        GetRewardRecipient {
+            recipient: Addr,
         },
     }
 }
@@ -632,3 +1109,141 @@ pub mod lp_token {
         pub config: Option<InitConfig>,
     }
 }
+
+pub mod vault {
+    use super::*;
+    use crate::{query_auth::QueryPermit, router::Hop, Contract};
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::Decimal;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct InitMsg {
+        pub pair_contract: Contract,
+        pub staking_contract: Contract,
+        pub router: Contract,
+        // Share of harvested rewards paid to the caller of Harvest, in basis points
+        pub harvest_bounty_bps: u16,
+        // LP token the harvest may lose to the swap balancing its deposit, in basis points
+        pub max_liquidity_slippage_bps: u16,
+        // Pair token 0 per pair token 1, harvested liquidity is valued at it instead of the pool price
+        pub reference_price: Decimal,
+        //Used for permits
+        pub authenticator: Option<Contract>,
+        pub admin_auth: Contract,
+    }
+
+    /// Router path that turns a reward token into one of the pair tokens.
+    /// Leave the path empty when the reward token is already a pair token.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct RewardRoute {
+        pub reward_token: Contract,
+        pub path: Vec<Hop>,
+        /// Maximum price impact in basis points allowed on each hop of the path.
+        pub max_price_impact_bps: Option<u64>,
+        /// Minimum amount of the path's last token per reward token swapped.
+        /// Set it from a price outside the pool, it can not be moved within the harvest.
+        pub min_return_rate: Decimal,
+    }
+
+    /// LP token of burned shares that is still unbonding in the staking contract
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct PendingWithdrawal {
+        pub amount: Uint128,
+        // Timestamp in seconds after which ClaimWithdrawals sends the amount
+        pub release_at: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ExecuteMsg {
+        Receive {
+            from: String,
+            msg: Option<Binary>,
+            amount: Uint128,
+        },
+        // Burns vault shares and returns their LP token, once unbonded if staking has an unbonding period
+        Withdraw {
+            shares: Uint128,
+        },
+        // Sends the LP token of withdrawals that finished unbonding
+        ClaimWithdrawals {},
+        // Compounds the staking rewards, anyone can call it for the harvest bounty
+        Harvest {},
+        SetRewardRoutes {
+            routes: Vec<RewardRoute>,
+        },
+        SetHarvestBounty {
+            harvest_bounty_bps: u16,
+        },
+        SetMaxLiquiditySlippage {
+            max_liquidity_slippage_bps: u16,
+        },
+        SetReferencePrice {
+            reference_price: Decimal,
+        },
+        // Steps of Harvest, only the vault itself can execute them
+        SwapRewards {
+            harvester: String,
+        },
+        ProvideLiquidity {},
+        Restake {},
+    }
+
+    impl ExecuteCallback for ExecuteMsg {
+        const BLOCK_SIZE: usize = 256;
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum InvokeMsg {
+        // Sent with LP token to mint vault shares
+        Deposit {},
+    }
+
+    #[cw_serde]
+    pub struct QueryData {}
+
+    #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    pub enum QueryMsg {
+        GetConfig {},
+        GetVaultInfo {},
+        WithPermit {
+            permit: QueryPermit,
+            query: AuthQuery,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Clone)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AuthQuery {
+        GetShares {},
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    pub enum QueryResponse {
+        GetConfig {
+            pair_contract: Contract,
+            staking_contract: Contract,
+            router: Contract,
+            lp_token: Contract,
+            harvest_bounty_bps: u16,
+            max_liquidity_slippage_bps: u16,
+            reference_price: Decimal,
+            reward_routes: Vec<RewardRoute>,
+            admin_auth: Contract,
+        },
+        GetVaultInfo {
+            total_shares: Uint128,
+            // LP token staked by the vault, including compounded rewards
+            total_lp_token: Uint128,
+        },
+        GetShares {
+            shares: Uint128,
+            // LP token the shares can be withdrawn for
+            lp_token: Uint128,
+            pending_withdrawals: Vec<PendingWithdrawal>,
+        },
+    }
+}
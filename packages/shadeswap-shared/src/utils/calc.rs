@@ -1,5 +1,8 @@
 use crate::c_std::{Uint256, StdResult};
 
+// Denominator used for values expressed in basis points
+pub const BASIS_POINTS: u64 = 10_000;
+
 // For generic purpose math formulas
 pub fn sqrt(value: Uint256) -> StdResult<Uint256> {
     let mut z = Uint256::zero();
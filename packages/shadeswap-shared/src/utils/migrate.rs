@@ -0,0 +1,28 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read};
+
+const CONTRACT_VERSION: &[u8] = b"contract_version";
+
+/// Store the state version written by the running code
+pub fn set_contract_version(storage: &mut dyn Storage, version: u32) -> StdResult<()> {
+    singleton(storage, CONTRACT_VERSION).save(&version)
+}
+
+/// State version of the contract, contracts instantiated before versioning are version 1
+pub fn get_contract_version(storage: &dyn Storage) -> StdResult<u32> {
+    Ok(singleton_read(storage, CONTRACT_VERSION)
+        .may_load()?
+        .unwrap_or(1u32))
+}
+
+/// Returns the stored state version, failing when it is newer than the code being migrated to
+pub fn assert_can_migrate(storage: &dyn Storage, version: u32) -> StdResult<u32> {
+    let stored_version = get_contract_version(storage)?;
+    if stored_version > version {
+        return Err(StdError::generic_err(format!(
+            "Can not migrate state version {} to older version {}.",
+            stored_version, version
+        )));
+    }
+    Ok(stored_version)
+}
@@ -14,6 +14,7 @@ pub mod cycle;
 pub mod wrap;
 pub mod price;
 pub mod calc;
+pub mod migrate;
 pub mod addr;
 pub use addr::*;
 